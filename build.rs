@@ -0,0 +1,24 @@
+//! Records the git commit and host target for `cargo tako --version --verbose`
+
+use std::process::Command;
+
+fn main() {
+    let commit = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=TAKO_GIT_COMMIT={commit}");
+    println!(
+        "cargo:rustc-env=TAKO_HOST={}",
+        std::env::var("TARGET").unwrap_or_default()
+    );
+    // Outside a checkout (e.g. installed from crates.io) the commit stays empty
+    for path in [".git/HEAD", ".git/refs/heads"] {
+        if std::path::Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Address book stored in Tako.lock
+//!
+//! Maps contract names to deployed addresses per network. The file lives next
+//! to the nearest Tako.toml and is meant to be committed, so every checkout
+//! refers to the same deployments.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::find_config_file;
+use crate::error::{Error, Result};
+
+const HEADER: &str = "# Deployed contract addresses, maintained by `cargo tako addresses`.\n\
+# Commit this file so all checkouts refer to the same deployments.\n\n";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AddressBook {
+    /// Network name -> contract name -> address
+    #[serde(default)]
+    pub networks: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl AddressBook {
+    /// Path of Tako.lock: next to the nearest Tako.toml, else in the current directory
+    pub fn path() -> Result<PathBuf> {
+        let current_dir = env::current_dir()?;
+        let dir = find_config_file(&current_dir)
+            .and_then(|config| config.parent().map(PathBuf::from))
+            .unwrap_or(current_dir);
+        Ok(dir.join("Tako.lock"))
+    }
+
+    /// Load the address book, returning an empty one if none exists yet
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)?;
+        toml::from_str(&content)
+            .map_err(|e| Error::Config(format!("Invalid {}: {e}", path.display())))
+    }
+
+    /// Write the address book to Tako.lock
+    pub fn save(&self) -> Result<()> {
+        let content = toml::to_string_pretty(self)?;
+        fs::write(Self::path()?, format!("{HEADER}{content}"))?;
+        Ok(())
+    }
+
+    /// Get the address of a contract on a network
+    pub fn get(&self, network: &str, name: &str) -> Option<&str> {
+        self.networks
+            .get(network)
+            .and_then(|contracts| contracts.get(name))
+            .map(String::as_str)
+    }
+
+    /// Record the address of a contract, returning the previous one
+    pub fn set(&mut self, network: &str, name: &str, address: &str) -> Option<String> {
+        self.networks
+            .entry(network.to_string())
+            .or_default()
+            .insert(name.to_string(), address.to_string())
+    }
+
+    /// Remove a contract, returning its address
+    pub fn remove(&mut self, network: &str, name: &str) -> Option<String> {
+        let contracts = self.networks.get_mut(network)?;
+        let address = contracts.remove(name);
+        if contracts.is_empty() {
+            self.networks.remove(network);
+        }
+        address
+    }
+}
@@ -0,0 +1,129 @@
+//! Target compatibility scan of dependencies
+//!
+//! Dependencies written for Solana often gate code on `target_os = "solana"`
+//! or `target_arch = "sbf"`, pick prebuilt objects in their build scripts, or
+//! ship prebuilt blobs. None of that applies to `tbpf*-tos-tos` targets, and
+//! the result usually surfaces much later as a confusing linker failure.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::metadata::Package;
+
+/// cfg predicates (with whitespace removed) that never hold for TBPF targets
+const FOREIGN_CFGS: &[&str] = &[
+    "target_os=\"solana\"",
+    "target_arch=\"sbf\"",
+    "target_arch=\"bpf\"",
+    "target_family=\"solana\"",
+];
+
+/// Target names build scripts compare against
+const FOREIGN_TARGETS: &[&str] = &["\"sbf", "\"bpf", "solana"];
+
+/// Extensions of prebuilt objects and libraries
+const BLOB_EXTENSIONS: &[&str] = &["so", "a", "o", "lib", "bc"];
+
+/// Problems found in one dependency
+#[derive(Debug, Default)]
+pub struct PackageFindings {
+    pub name: String,
+    pub version: String,
+    /// Locations (`src/lib.rs:12`) of cfg gates for other BPF targets
+    pub cfg_gates: Vec<String>,
+    /// Locations of build script lines matching other BPF targets
+    pub build_script: Vec<String>,
+    /// Prebuilt blobs shipped with the package
+    pub blobs: Vec<PathBuf>,
+}
+
+impl PackageFindings {
+    pub fn is_empty(&self) -> bool {
+        self.cfg_gates.is_empty() && self.build_script.is_empty() && self.blobs.is_empty()
+    }
+}
+
+/// Scan a dependency's sources, build script and files
+pub fn scan_package(package: &Package) -> PackageFindings {
+    let root = package.root();
+    let mut findings = PackageFindings {
+        name: package.name.clone(),
+        version: package.version.clone(),
+        ..Default::default()
+    };
+
+    for entry in WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| !matches!(e.file_name().to_str(), Some("target" | ".git")))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let path = entry.path();
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default();
+
+        if BLOB_EXTENSIONS.contains(&extension) {
+            findings.blobs.push(relative.to_path_buf());
+            continue;
+        }
+        if extension != "rs" {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(path) else {
+            continue;
+        };
+
+        let is_build_script = relative == Path::new("build.rs");
+        for (line, text) in content.lines().enumerate() {
+            let location = format!("{}:{}", relative.display(), line + 1);
+            if foreign_cfg(text) {
+                findings.cfg_gates.push(location);
+            } else if is_build_script && foreign_target(text) {
+                findings.build_script.push(location);
+            }
+        }
+    }
+
+    findings
+}
+
+/// Whether a line gates code on a Solana/SBF target without covering TBPF
+fn foreign_cfg(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    (compact.contains("cfg(") || compact.contains("cfg!("))
+        && FOREIGN_CFGS.iter().any(|cfg| compact.contains(cfg))
+        && !covers_tbpf(&compact)
+}
+
+/// Whether a build script line compares the target against Solana/SBF names
+fn foreign_target(line: &str) -> bool {
+    let code = line.split("//").next().unwrap_or_default();
+    code.contains('"') && FOREIGN_TARGETS.iter().any(|t| code.contains(t)) && !covers_tbpf(code)
+}
+
+fn covers_tbpf(text: &str) -> bool {
+    text.contains("tbpf") || text.contains("\"tos\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_foreign_cfg() {
+        assert!(foreign_cfg(r#"#[cfg(target_os = "solana")]"#));
+        assert!(foreign_cfg(r#"if cfg!(target_arch="sbf") {"#));
+        assert!(!foreign_cfg(
+            r#"#[cfg(any(target_os = "solana", target_os = "tos"))]"#
+        ));
+        assert!(!foreign_cfg(r#"let os = "solana";"#));
+
+        assert!(foreign_target(r#"if target.starts_with("sbf") {"#));
+        assert!(!foreign_target(r#"if target.starts_with("tbpf") {"#));
+        assert!(!foreign_target("// works on solana too"));
+    }
+}
@@ -0,0 +1,209 @@
+//! Build environment captured next to each artifact as tako-build-info.json
+//!
+//! Records what is needed to reproduce a build: the compiler version, host,
+//! environment variables that affect compilation and the cargo command line.
+//! `cargo tako verify-build` compares two of these for reproducibility audits.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::{Error, Result};
+use crate::logs;
+use crate::schema::{self, SCHEMA_VERSION};
+use crate::util::sha256_hex;
+
+/// File name of the build info, written in the artifact directory
+pub const FILE_NAME: &str = "tako-build-info.json";
+
+/// Environment variables that change the output of a build
+const BUILD_ENV_PREFIXES: &[&str] = &["CARGO_PROFILE_", "CARGO_BUILD_", "CARGO_TARGET_"];
+const BUILD_ENV_VARS: &[&str] = &[
+    "RUSTFLAGS",
+    "CARGO_ENCODED_RUSTFLAGS",
+    "RUSTC_WRAPPER",
+    "RUSTC_BOOTSTRAP",
+    "SOURCE_DATE_EPOCH",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildInfo {
+    #[serde(default = "schema::unversioned")]
+    pub schema_version: u32,
+
+    /// Output of `rustc -vV` for the compiler used
+    pub rustc: String,
+    pub host: String,
+    pub arch: String,
+    pub target: String,
+    pub profile: String,
+
+    /// Build-affecting environment variables
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+
+    /// Cargo arguments, with paths inside the project made relative
+    pub command: Vec<String>,
+
+    pub artifact_sha256: String,
+}
+
+impl BuildInfo {
+    /// Capture the environment of a finished build
+    ///
+    /// # Arguments
+    /// * `cmd` - The cargo command that produced the artifact
+    /// * `rustc` - Compiler used (the platform-tools rustc, or rustc from PATH)
+    /// * `artifact` - Path to the built (and stripped) artifact
+    pub fn capture(
+        cmd: &Command,
+        rustc: Option<&Path>,
+        arch: &str,
+        target: &str,
+        profile: &str,
+        artifact: &Path,
+    ) -> Result<Self> {
+        let rustc_bin = rustc.unwrap_or(Path::new("rustc"));
+        let output = logs::output(Command::new(rustc_bin).arg("-vV"))
+            .map_err(|e| Error::Other(format!("Failed to run {} -vV: {e}", rustc_bin.display())))?;
+        let rustc = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let host = rustc
+            .lines()
+            .find_map(|line| line.strip_prefix("host: "))
+            .unwrap_or_default()
+            .to_string();
+
+        let mut env: BTreeMap<String, String> =
+            env::vars().filter(|(name, _)| is_build_env(name)).collect();
+        for (name, value) in cmd.get_envs() {
+            let name = name.to_string_lossy().to_string();
+            if !is_build_env(&name) {
+                continue;
+            }
+            match value {
+                Some(value) => env.insert(name, value.to_string_lossy().to_string()),
+                None => env.remove(&name),
+            };
+        }
+
+        let project = env::current_dir()?;
+        let project = project.to_string_lossy();
+        let command = std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|arg| arg.to_string_lossy().replace(project.as_ref(), "."))
+            .collect();
+
+        Ok(Self {
+            schema_version: SCHEMA_VERSION,
+            rustc,
+            host,
+            arch: arch.to_string(),
+            target: target.to_string(),
+            profile: profile.to_string(),
+            env,
+            command,
+            artifact_sha256: sha256_hex(&fs::read(artifact)?),
+        })
+    }
+
+    /// Path of the build info for an artifact
+    pub fn path_for(artifact: &Path) -> PathBuf {
+        artifact.with_file_name(FILE_NAME)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::Other(format!("Failed to read {}: {e}", path.display())))?;
+        serde_json::from_str(&content)
+            .map_err(|e| Error::Other(format!("Invalid {}: {e}", path.display())))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Other(format!("Failed to serialize build info: {e}")))?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Compare with a reference build, returning (field, ours, theirs) for
+    /// every field that differs
+    pub fn differences(&self, reference: &Self) -> Vec<(String, String, String)> {
+        let mut diffs = Vec::new();
+        let mut compare = |field: &str, ours: &str, theirs: &str| {
+            if ours != theirs {
+                diffs.push((field.to_string(), ours.to_string(), theirs.to_string()));
+            }
+        };
+
+        compare("rustc", &self.rustc, &reference.rustc);
+        compare("host", &self.host, &reference.host);
+        compare("arch", &self.arch, &reference.arch);
+        compare("target", &self.target, &reference.target);
+        compare("profile", &self.profile, &reference.profile);
+
+        // The program path depends on where the toolchain is installed
+        let args = |info: &Self| info.command.iter().skip(1).cloned().collect::<Vec<_>>();
+        compare("command", &args(self).join(" "), &args(reference).join(" "));
+
+        let names: BTreeSet<&String> = self.env.keys().chain(reference.env.keys()).collect();
+        for name in names {
+            let value = |info: &Self| info.env.get(name).cloned().unwrap_or_default();
+            compare(&format!("env {name}"), &value(self), &value(reference));
+        }
+
+        compare(
+            "artifact sha256",
+            &self.artifact_sha256,
+            &reference.artifact_sha256,
+        );
+        diffs
+    }
+}
+
+fn is_build_env(name: &str) -> bool {
+    BUILD_ENV_VARS.contains(&name) || BUILD_ENV_PREFIXES.iter().any(|p| name.starts_with(p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(rustc: &str, env: &[(&str, &str)]) -> BuildInfo {
+        BuildInfo {
+            schema_version: SCHEMA_VERSION,
+            rustc: rustc.to_string(),
+            host: "x86_64-unknown-linux-gnu".to_string(),
+            arch: "v3".to_string(),
+            target: "tbpfv3-tos-tos".to_string(),
+            profile: "release".to_string(),
+            env: env
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            command: vec!["/home/a/cargo".to_string(), "build".to_string()],
+            artifact_sha256: "ab".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_differences() {
+        let ours = info("rustc 1.84.1", &[("CARGO_PROFILE_RELEASE_LTO", "fat")]);
+        let mut theirs = info("rustc 1.84.1", &[("CARGO_PROFILE_RELEASE_LTO", "fat")]);
+        theirs.command[0] = "/home/b/cargo".to_string();
+        assert!(ours.differences(&theirs).is_empty());
+
+        let theirs = info("rustc 1.85.0", &[("RUSTFLAGS", "-Copt-level=3")]);
+        let fields: Vec<String> = ours
+            .differences(&theirs)
+            .into_iter()
+            .map(|(field, _, _)| field)
+            .collect();
+        assert_eq!(
+            fields,
+            vec!["rustc", "env CARGO_PROFILE_RELEASE_LTO", "env RUSTFLAGS"]
+        );
+    }
+}
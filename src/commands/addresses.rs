@@ -0,0 +1,67 @@
+//! Addresses command implementation
+
+use crate::address_book::AddressBook;
+use crate::error::{Error, Result};
+
+/// List recorded addresses, optionally for a single network
+pub fn list(network: Option<&str>) -> Result<()> {
+    let book = AddressBook::load()?;
+
+    let mut found = false;
+    for (name, contracts) in &book.networks {
+        if network.is_some_and(|n| n != name) {
+            continue;
+        }
+        found = true;
+        println!("{name}:");
+        for (contract, address) in contracts {
+            println!("  {contract:<24} {address}");
+        }
+    }
+
+    if !found {
+        println!(
+            "No addresses recorded in {}",
+            AddressBook::path()?.display()
+        );
+    }
+    Ok(())
+}
+
+/// Print the address of a contract (for use in scripts)
+pub fn get(network: &str, name: &str) -> Result<()> {
+    let book = AddressBook::load()?;
+    let address = book
+        .get(network, name)
+        .ok_or_else(|| Error::Other(format!("No address recorded for '{name}' on {network}")))?;
+    println!("{address}");
+    Ok(())
+}
+
+/// Record the address of a contract
+pub fn set(network: &str, name: &str, address: &str) -> Result<()> {
+    if address.is_empty() || address.contains(char::is_whitespace) {
+        return Err(Error::Other(format!("Invalid address '{address}'")));
+    }
+
+    let mut book = AddressBook::load()?;
+    match book.set(network, name, address) {
+        Some(previous) if previous != address => {
+            println!("✓ {name} on {network}: {previous} -> {address}")
+        }
+        _ => println!("✓ {name} on {network}: {address}"),
+    }
+    book.save()
+}
+
+/// Remove a contract from the address book
+pub fn remove(network: &str, name: &str) -> Result<()> {
+    let mut book = AddressBook::load()?;
+    if book.remove(network, name).is_none() {
+        return Err(Error::Other(format!(
+            "No address recorded for '{name}' on {network}"
+        )));
+    }
+    println!("✓ Removed {name} from {network}");
+    book.save()
+}
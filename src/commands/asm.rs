@@ -0,0 +1,357 @@
+//! Assembly inspection for a single function
+//!
+//! Compiles the contract crate with `--emit asm` for the TBPF target and
+//! prints the assembly of one function, with the Rust source lines it was
+//! generated from interleaved (from the `.loc` directives). The compile uses
+//! its own target directory, target/tako/asm/, so the regular build is not
+//! invalidated; cargo reuses it while the sources are unchanged.
+
+use colored::Colorize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use crate::commands::build::{self, OptimizationSettings};
+use crate::error::{Error, Result};
+use crate::logs;
+use crate::style::Styled;
+use crate::toolchain::{find_platform_tools, DEFAULT_PLATFORM_TOOLS_VERSION};
+use crate::util::tako_dir;
+
+/// A function in the emitted assembly
+#[derive(Debug)]
+struct AsmFunction {
+    /// Demangled name without the hash suffix
+    name: String,
+    /// Raw lines between the label and the end of the function
+    body: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum AsmLine {
+    /// Source line the following instructions were generated from
+    Source {
+        file: String,
+        line: u32,
+    },
+    Label(String),
+    Instruction(String),
+}
+
+/// Show the assembly of a function, or list the functions
+///
+/// # Arguments
+/// * `symbol` - Function name or path suffix (e.g. `transfer`,
+///   `Token::transfer`); lists all functions when None
+/// * `release` - Compile with the release profile (what gets deployed)
+/// * `arch` - TBPF architecture version to compile for
+/// * `optimization` - Release profile settings, as for `cargo tako build`
+pub fn show_asm(
+    symbol: Option<&str>,
+    release: bool,
+    arch: &str,
+    optimization: &OptimizationSettings,
+) -> Result<()> {
+    let asm_path = emit_asm(release, arch, optimization)?;
+    let asm = fs::read_to_string(&asm_path)?;
+    let (files, functions) = parse(&asm);
+
+    let Some(symbol) = symbol else {
+        println!("Functions in {}:", asm_path.display());
+        for function in &functions {
+            let instructions = lines(&function.body, &files)
+                .iter()
+                .filter(|l| matches!(l, AsmLine::Instruction(_)))
+                .count();
+            println!("  {:>6}  {}", instructions, function.name);
+        }
+        return Ok(());
+    };
+
+    let matches = find(&functions, symbol);
+    let function = match matches.as_slice() {
+        [function] => function,
+        [] => {
+            return Err(Error::Other(format!(
+                "No function matching `{symbol}` in {} (run `cargo tako asm` without a symbol to list them; #[inline] functions may have been inlined into their callers)",
+                asm_path.display()
+            )))
+        }
+        several => {
+            let names: Vec<&str> = several.iter().map(|f| f.name.as_str()).collect();
+            return Err(Error::Other(format!(
+                "`{symbol}` matches several functions, use a longer path:\n  {}",
+                names.join("\n  ")
+            )));
+        }
+    };
+
+    println!("{}:", function.name.bold());
+    let project = std::env::current_dir()?;
+    let mut sources: HashMap<String, Option<Vec<String>>> = HashMap::new();
+    for line in lines(&function.body, &files) {
+        match line {
+            AsmLine::Source { file, line } => {
+                let text = sources
+                    .entry(file.clone())
+                    .or_insert_with(|| {
+                        fs::read_to_string(&file)
+                            .ok()
+                            .map(|c| c.lines().map(str::to_string).collect())
+                    })
+                    .as_ref()
+                    .and_then(|lines| lines.get(line as usize - 1))
+                    .map(|text| text.trim().to_string())
+                    .unwrap_or_default();
+                let shown = Path::new(&file)
+                    .strip_prefix(&project)
+                    .map(|p| p.display().to_string())
+                    .unwrap_or(file);
+                println!("  {} {text}", format!("; {shown}:{line}").dimmed());
+            }
+            AsmLine::Label(label) => println!("{}", label.accent()),
+            AsmLine::Instruction(instruction) => println!("        {instruction}"),
+        }
+    }
+    Ok(())
+}
+
+/// Compile the library crate with `--emit asm` and return the .s file
+fn emit_asm(release: bool, arch: &str, optimization: &OptimizationSettings) -> Result<PathBuf> {
+    let target = build::get_target_triple(arch);
+    let profile = if release { "release" } else { "debug" };
+    let target_dir = tako_dir().join("asm");
+
+    let platform_tools = find_platform_tools(Some(DEFAULT_PLATFORM_TOOLS_VERSION));
+    let (cargo_bin, _) = build::get_cargo_and_rustc(&platform_tools);
+
+    let mut cmd = Command::new(&cargo_bin);
+    cmd.args(["rustc", "--lib", "--target", &target])
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .arg("-Zbuild-std=core,alloc");
+    if release {
+        cmd.arg("--release");
+        optimization.apply(&mut cmd);
+        // LTO happens at link time and cannot apply to emitted assembly
+        cmd.env("CARGO_PROFILE_RELEASE_LTO", "off");
+    }
+    // One codegen unit gives one .s file; line tables give the .loc directives
+    cmd.args([
+        "--",
+        "--emit=asm",
+        "-C",
+        "codegen-units=1",
+        "-C",
+        "debuginfo=1",
+    ]);
+    cmd.envs(build::toolchain_env(&platform_tools));
+
+    println!(
+        "Running: cargo rustc --lib --target {target}{} -- --emit=asm",
+        if release { " --release" } else { "" }
+    );
+    let status =
+        logs::status(&mut cmd).map_err(|e| Error::spawn("cargo", e, Error::BuildFailed))?;
+    if !status.success() {
+        return Err(Error::BuildFailed(
+            "cargo rustc --emit=asm failed".to_string(),
+        ));
+    }
+
+    // Only the contract crate is compiled with --emit; take the newest .s
+    let deps = target_dir.join(&target).join(profile).join("deps");
+    fs::read_dir(&deps)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "s"))
+        .max_by_key(|p| {
+            fs::metadata(p)
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH)
+        })
+        .ok_or_else(|| Error::BuildFailed(format!("No assembly written to {}", deps.display())))
+}
+
+/// Split assembly into its `.file` table and functions
+fn parse(asm: &str) -> (BTreeMap<u32, String>, Vec<AsmFunction>) {
+    let mut files = BTreeMap::new();
+    let mut functions = Vec::new();
+    // Symbols declared with `.type name,@function`; other labels are data
+    let mut function_symbols = HashSet::new();
+    let mut current: Option<(String, AsmFunction)> = None;
+
+    for line in asm.lines() {
+        let trimmed = line.trim();
+        if let Some(rest) = trimmed.strip_prefix(".file") {
+            if let Some((number, path)) = parse_file_directive(rest) {
+                files.insert(number, path);
+            }
+            continue;
+        }
+
+        if let Some(rest) = trimmed.strip_prefix(".type") {
+            if let Some(symbol) = rest.trim().strip_suffix(",@function") {
+                function_symbols.insert(symbol.to_string());
+            }
+        }
+
+        if let Some((mangled, function)) = &mut current {
+            let ends = trimmed.starts_with(".Lfunc_end")
+                || trimmed.starts_with(&format!(".size\t{mangled},"))
+                || trimmed.starts_with(&format!(".size {mangled},"));
+            if ends {
+                functions.push(current.take().expect("current function").1);
+            } else {
+                function.body.push(line.to_string());
+            }
+            continue;
+        }
+
+        if let Some(label) = line.strip_suffix(':') {
+            if function_symbols.contains(label) {
+                let name = format!("{:#}", rustc_demangle::demangle(label));
+                current = Some((
+                    label.to_string(),
+                    AsmFunction {
+                        name,
+                        body: Vec::new(),
+                    },
+                ));
+            }
+        }
+    }
+    if let Some((_, function)) = current {
+        functions.push(function);
+    }
+    (files, functions)
+}
+
+/// `.file 2 "dir" "name" md5 0x...` (DWARF 5) or `.file 2 "path"`
+fn parse_file_directive(rest: &str) -> Option<(u32, String)> {
+    let rest = rest.trim();
+    let (number, rest) = rest.split_once(char::is_whitespace)?;
+    let number = number.parse().ok()?;
+    let quoted: Vec<&str> = rest.split('"').skip(1).step_by(2).collect();
+    let path = match quoted.as_slice() {
+        [dir, name, ..] if !name.starts_with('/') => Path::new(dir).join(name),
+        [.., name] => PathBuf::from(name),
+        [] => return None,
+    };
+    Some((number, path.to_string_lossy().to_string()))
+}
+
+/// Instructions and labels of a function, with a source line before each
+/// run of instructions generated from a different line
+fn lines(body: &[String], files: &BTreeMap<u32, String>) -> Vec<AsmLine> {
+    let mut lines = Vec::new();
+    let mut last_location = None;
+    for line in body {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(rest) = trimmed.strip_prefix(".loc") {
+            let mut fields = rest.split_whitespace();
+            let file = fields.next().and_then(|f| f.parse::<u32>().ok());
+            let line = fields.next().and_then(|l| l.parse::<u32>().ok());
+            if let (Some(file), Some(line)) = (file, line) {
+                if line > 0 && last_location != Some((file, line)) {
+                    last_location = Some((file, line));
+                    if let Some(path) = files.get(&file) {
+                        lines.push(AsmLine::Source {
+                            file: path.clone(),
+                            line,
+                        });
+                    }
+                }
+            }
+            continue;
+        }
+        if trimmed.ends_with(':') {
+            // Basic block labels are jump targets; other local labels only
+            // anchor debug info
+            if trimmed.starts_with(".LBB") {
+                lines.push(AsmLine::Label(trimmed.to_string()));
+            }
+        } else if !trimmed.starts_with('.') {
+            // Drop trailing comments
+            let instruction = trimmed.split(" #").next().unwrap_or(trimmed).trim_end();
+            lines.push(AsmLine::Instruction(instruction.replace('\t', " ")));
+        }
+    }
+    lines
+}
+
+/// Functions whose name is `symbol` or ends with `::symbol`
+fn find<'a>(functions: &'a [AsmFunction], symbol: &str) -> Vec<&'a AsmFunction> {
+    let exact: Vec<&AsmFunction> = functions.iter().filter(|f| f.name == symbol).collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+    let suffix = format!("::{symbol}");
+    functions
+        .iter()
+        .filter(|f| f.name.ends_with(&suffix))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASM: &str = "\t.text
+\t.file\t\"counter\" \"src/lib.rs\"
+\t.file\t1 \"/proj\" \"src/lib.rs\" md5 0x0123
+\t.section\t.text._ZN7counter9increment17h0123456789abcdefE,\"ax\",@progbits
+\t.globl\t_ZN7counter9increment17h0123456789abcdefE
+\t.type\t_ZN7counter9increment17h0123456789abcdefE,@function
+_ZN7counter9increment17h0123456789abcdefE:
+.Lfunc_begin0:
+\t.loc\t1 10 0
+\t.cfi_startproc
+# %bb.0:
+\t.loc\t1 11 5 prologue_end
+\tr0 = *(u64 *)(r1 + 0)
+\t.loc\t1 11 9
+\tr0 += 1
+.LBB0_1:
+\t.loc\t1 12 5
+\texit
+.Lfunc_end0:
+\t.size\t_ZN7counter9increment17h0123456789abcdefE, .Lfunc_end0-_ZN7counter9increment17h0123456789abcdefE
+\t.type\tentrypoint,@function
+entrypoint:
+\texit
+.Lfunc_end1:
+";
+
+    #[test]
+    fn test_parse_and_lines() {
+        let (files, functions) = parse(ASM);
+        assert_eq!(files.get(&1).map(String::as_str), Some("/proj/src/lib.rs"));
+        let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["counter::increment", "entrypoint"]);
+
+        let lines = lines(&find(&functions, "increment")[0].body, &files);
+        let source = |line| AsmLine::Source {
+            file: "/proj/src/lib.rs".to_string(),
+            line,
+        };
+        assert_eq!(
+            lines,
+            [
+                source(10),
+                source(11),
+                AsmLine::Instruction("r0 = *(u64 *)(r1 + 0)".to_string()),
+                AsmLine::Instruction("r0 += 1".to_string()),
+                AsmLine::Label(".LBB0_1:".to_string()),
+                source(12),
+                AsmLine::Instruction("exit".to_string()),
+            ]
+        );
+        assert!(find(&functions, "decrement").is_empty());
+    }
+}
@@ -1,13 +1,34 @@
 //! Build command implementation
 
+use crate::build_info::BuildInfo;
+use crate::commands::check::{self, CheckStatus};
+use crate::config::{BuildConfig, ContractConfig, TakoConfig};
+use crate::elf;
 use crate::error::{Error, Result};
-use crate::toolchain::{find_platform_tools, PlatformTools, DEFAULT_PLATFORM_TOOLS_VERSION};
-use crate::util::find_contract_binary_for_target;
+use crate::interrupt;
+use crate::logs;
+use crate::manifest::{ArtifactRecord, BuildManifest, VerifyCache, VerifyReport, VerifyVerdict};
+use crate::progress::Progress;
+use crate::schema::SCHEMA_VERSION;
+use crate::source_map::SourceMap;
+use crate::style::Styled;
+use crate::target_spec::TargetSpec;
+use crate::toolchain::{
+    find_llvm_tool, find_platform_tools, sysroot_warning, touch_last_used, PlatformTools,
+    DEFAULT_PLATFORM_TOOLS_VERSION,
+};
+use crate::util::{ensure_dir, find_contract_binary_for_target, sha256_hex, tako_dir};
+use colored::Colorize;
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::thread;
 
 /// Get target triple from architecture version (aligned with Solana's cargo-build-sbf)
-fn get_target_triple(arch: &str) -> String {
+pub fn get_target_triple(arch: &str) -> String {
     if arch == "v0" {
         "tbpf-tos-tos".to_string()
     } else {
@@ -27,6 +48,265 @@ fn get_expected_flags(arch: &str) -> u32 {
     }
 }
 
+/// Release profile settings applied to the contract build
+///
+/// Resolved from the `[build]` section of Tako.toml, optionally overridden by
+/// an `--optimize` preset, and passed to cargo as profile overrides.
+#[derive(Debug, Clone)]
+pub struct OptimizationSettings {
+    pub opt_level: String,
+    pub lto: String,
+    pub codegen_units: u32,
+    pub panic: String,
+}
+
+/// Values accepted for `lto` (cargo's `profile.*.lto`)
+pub const LTO_VALUES: &[&str] = &["fat", "thin", "off", "true", "false"];
+
+/// Values accepted for `panic` (cargo's `profile.*.panic`)
+pub const PANIC_VALUES: &[&str] = &["abort", "unwind"];
+
+impl OptimizationSettings {
+    /// Resolve settings from the build config and command-line overrides
+    ///
+    /// Command-line values take precedence over `[build]` in Tako.toml, which
+    /// takes precedence over the defaults.
+    ///
+    /// Presets:
+    /// - `size`: opt-level "z" (smallest binary, default for contracts)
+    /// - `speed`: opt-level "3" (fewest compute units)
+    ///
+    /// # Arguments
+    /// * `config` - `[build]` section of Tako.toml
+    /// * `preset` - `--optimize` preset
+    /// * `lto` - `--lto` value
+    /// * `panic` - `--panic` value
+    pub fn resolve(
+        config: &BuildConfig,
+        preset: Option<&str>,
+        lto: Option<&str>,
+        panic: Option<&str>,
+    ) -> Result<Self> {
+        let mut settings = Self {
+            opt_level: config.opt_level.clone(),
+            lto: lto.unwrap_or(&config.lto).to_string(),
+            codegen_units: config.codegen_units,
+            panic: panic.unwrap_or(&config.panic).to_string(),
+        };
+
+        match preset {
+            None => {}
+            Some("size") => settings.opt_level = "z".to_string(),
+            Some("speed") => settings.opt_level = "3".to_string(),
+            Some(other) => {
+                return Err(Error::Config(format!(
+                    "Unknown optimization preset '{other}' (expected size or speed)"
+                )))
+            }
+        }
+
+        if !["0", "1", "2", "3", "s", "z"].contains(&settings.opt_level.as_str()) {
+            return Err(Error::Config(format!(
+                "Invalid opt_level '{}' (expected 0, 1, 2, 3, s or z)",
+                settings.opt_level
+            )));
+        }
+        if !LTO_VALUES.contains(&settings.lto.as_str()) {
+            return Err(Error::Config(format!(
+                "Invalid lto '{}' (expected {})",
+                settings.lto,
+                LTO_VALUES.join(", ")
+            )));
+        }
+        if !PANIC_VALUES.contains(&settings.panic.as_str()) {
+            return Err(Error::Config(format!(
+                "Invalid panic '{}' (expected {})",
+                settings.panic,
+                PANIC_VALUES.join(", ")
+            )));
+        }
+
+        Ok(settings)
+    }
+
+    /// Release profile overrides as environment variables
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("CARGO_PROFILE_RELEASE_OPT_LEVEL", self.opt_level.clone()),
+            ("CARGO_PROFILE_RELEASE_LTO", self.lto.clone()),
+            (
+                "CARGO_PROFILE_RELEASE_CODEGEN_UNITS",
+                self.codegen_units.to_string(),
+            ),
+            ("CARGO_PROFILE_RELEASE_PANIC", self.panic.clone()),
+        ]
+    }
+
+    /// Apply the settings to the cargo command as release profile overrides
+    pub fn apply(&self, cmd: &mut Command) {
+        cmd.envs(self.env());
+    }
+
+    /// One-line summary for build output
+    pub fn summary(&self) -> String {
+        format!(
+            "opt-level={}, lto={}, codegen-units={}, panic={}",
+            self.opt_level, self.lto, self.codegen_units, self.panic
+        )
+    }
+}
+
+/// Stable location the final artifact is copied to
+///
+/// Set with `--out-dir` or `[build] out_dir` / `out_name` in Tako.toml.
+#[derive(Debug, Clone)]
+pub struct OutputSettings {
+    pub dir: PathBuf,
+    /// File name template with `{name}`, `{lib}` (file stem of the built
+    /// artifact), `{version}`, `{arch}`, `{profile}`, `{target}` and `{hash}`
+    /// (first 8 hex digits of the SHA-256)
+    pub name: String,
+}
+
+impl OutputSettings {
+    /// Resolve the output location, or None if the artifact is not copied
+    ///
+    /// A name template without a directory copies to target/tako/out/.
+    pub fn resolve(config: &BuildConfig, out_dir: Option<&str>) -> Option<Self> {
+        let dir = match (out_dir.or(config.out_dir.as_deref()), &config.out_name) {
+            (Some(dir), _) => PathBuf::from(dir),
+            (None, Some(_)) => tako_dir().join("out"),
+            (None, None) => return None,
+        };
+        Some(Self {
+            dir,
+            name: config
+                .out_name
+                .clone()
+                .unwrap_or_else(|| "{name}.so".to_string()),
+        })
+    }
+}
+
+/// Copy the artifact, its build info and source map to the output directory
+///
+/// Companion files take the artifact's file stem: `<stem>.build-info.json`
+/// and `<stem>.srcmap.json`.
+///
+/// # Returns
+/// Path of the copied artifact
+pub fn export_artifact(
+    binary: &Path,
+    output: &OutputSettings,
+    arch: &str,
+    target: &str,
+    profile: &str,
+) -> Result<PathBuf> {
+    // C contracts have no Cargo.toml; {name} and {version} are empty then
+    let manifest = fs::read_to_string("Cargo.toml").unwrap_or_default();
+    let doc: toml_edit::DocumentMut = manifest
+        .parse()
+        .map_err(|e| Error::Config(format!("Cargo.toml: {e}")))?;
+    let package = |key: &str| {
+        doc.get("package")
+            .and_then(|p| p.get(key))
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let hash = sha256_hex(&fs::read(binary)?);
+    let lib = binary
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let name = render_out_name(
+        &output.name,
+        &[
+            ("name", &package("name")),
+            ("lib", &lib),
+            ("version", &package("version")),
+            ("arch", arch),
+            ("profile", profile),
+            ("target", target),
+            ("hash", &hash[..8]),
+        ],
+    )?;
+
+    ensure_dir(&output.dir)?;
+    let dest = output.dir.join(&name);
+    fs::copy(binary, &dest)?;
+
+    let stem = dest
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or(name);
+    let build_info = BuildInfo::path_for(binary);
+    if build_info.exists() {
+        fs::copy(
+            &build_info,
+            output.dir.join(format!("{stem}.build-info.json")),
+        )?;
+    }
+    let source_map = SourceMap::path_for(binary);
+    if source_map.exists() {
+        fs::copy(&source_map, SourceMap::path_for(&dest))?;
+    }
+    Ok(dest)
+}
+
+/// Hard link (or copy) the artifact to target/deploy/, replacing the
+/// previous one
+pub fn place_in_target_deploy(binary: &Path) -> Result<PathBuf> {
+    let dir = Path::new("target").join("deploy");
+    ensure_dir(&dir)?;
+    let file_name = binary
+        .file_name()
+        .ok_or_else(|| Error::Other(format!("Invalid artifact path {}", binary.display())))?;
+    let dest = dir.join(file_name);
+    if dest.exists() {
+        fs::remove_file(&dest)?;
+    }
+    if fs::hard_link(binary, &dest).is_err() {
+        fs::copy(binary, &dest)?;
+    }
+    Ok(dest)
+}
+
+/// Fill in the `{variable}` placeholders of an artifact name template
+fn render_out_name(template: &str, vars: &[(&str, &str)]) -> Result<String> {
+    // Placeholders are read from the template, so values containing braces
+    // (or other placeholder names) are inserted as they are
+    let mut name = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        name.push_str(&rest[..start]);
+        let end = rest[start..].find('}').map(|e| start + e).ok_or_else(|| {
+            Error::Config(format!("Unterminated placeholder in out_name '{template}'"))
+        })?;
+        let key = &rest[start + 1..end];
+        let value = vars
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| *v)
+            .ok_or_else(|| {
+                let expected: Vec<&str> = vars.iter().map(|(k, _)| *k).collect();
+                Error::Config(format!(
+                    "Unknown placeholder {{{key}}} in out_name (expected {})",
+                    expected.join(", ")
+                ))
+            })?;
+        name.push_str(value);
+        rest = &rest[end + 1..];
+    }
+    name.push_str(rest);
+
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return Err(Error::Config(format!("Invalid out_name '{template}'")));
+    }
+    Ok(name)
+}
+
 /// Build a TAKO smart contract
 ///
 /// Compiles the contract for the specified target architecture.
@@ -36,27 +316,80 @@ fn get_expected_flags(arch: &str) -> u32 {
 /// * `release` - Whether to build in release mode (optimized)
 /// * `arch` - Architecture version (v0, v1, v2, v3, v4)
 /// * `target` - Optional target override (auto-detected from arch if not specified)
+/// * `optimization` - Release profile settings (ignored for debug builds)
 ///
 /// # Returns
 /// Path to the built contract binary (.so file)
-pub fn build_contract(release: bool, arch: &str, target: Option<&str>) -> Result<PathBuf> {
+pub fn build_contract(
+    release: bool,
+    arch: &str,
+    target: Option<&str>,
+    optimization: &OptimizationSettings,
+    offline: bool,
+) -> Result<PathBuf> {
     // Determine target from arch or use override
     let target = target
         .map(|t| t.to_string())
         .unwrap_or_else(|| get_target_triple(arch));
 
+    // A custom target spec is passed to cargo by path; its file stem names
+    // the target directory, the link map and the manifest record
+    let spec = if TargetSpec::is_spec(&target) {
+        Some(TargetSpec::load(Path::new(&target))?)
+    } else {
+        None
+    };
+    let cargo_target = match &spec {
+        Some(spec) => spec.path.to_string_lossy().to_string(),
+        None => target.clone(),
+    };
+    let target = spec.as_ref().map(|s| s.name.clone()).unwrap_or(target);
+
     // Determine build profile
     let profile = if release { "release" } else { "debug" };
 
     println!("  Arch: {arch}");
     println!("  Target: {target}");
+    if let Some(spec) = &spec {
+        println!("  Target spec: {}", spec.path.display());
+    }
     println!("  Profile: {profile}");
+    if release {
+        println!("  Optimization: {}", optimization.summary());
+    }
+
+    // Surface allocator/panic handler problems, colliding error codes,
+    // dependencies that only target Solana/SBF and SDK/toolchain mismatches
+    // before they become linker errors
+    let mut checks = check::preflight(Path::new("src"))?;
+    checks.extend(check::arch_compat().unwrap_or_default());
+    checks.extend(check::sdk_compat(arch));
+    for result in &checks {
+        if result.status != CheckStatus::Pass {
+            result.print();
+        }
+    }
+    // Colliding error codes build fine but make errors indistinguishable
+    if checks
+        .iter()
+        .any(|r| r.name == "error codes" && r.status == CheckStatus::Fail)
+    {
+        return Err(Error::BuildFailed(
+            "custom errors share numeric codes".to_string(),
+        ));
+    }
 
     // Find TOS platform-tools (Solana-aligned search)
     let platform_tools = find_platform_tools(Some(DEFAULT_PLATFORM_TOOLS_VERSION));
 
     if let Some(ref tools) = platform_tools {
         println!("  Toolchain: {} ({})", tools.display_path(), tools.version);
+        touch_last_used(tools);
+        if spec.is_none() && target.starts_with("tbpf") {
+            if let Some(warning) = sysroot_warning(tools, &target) {
+                eprintln!("{} {warning}", "warning:".warning());
+            }
+        }
     } else {
         println!("  Toolchain: system (TOS platform-tools not found)");
         eprintln!("Warning: TOS platform-tools not found. TBPF targets may not be available.");
@@ -66,6 +399,19 @@ pub fn build_contract(release: bool, arch: &str, target: Option<&str>) -> Result
         eprintln!("  3. ~/.tos/platform-tools/rust/bin/");
     }
 
+    // An old or stable compiler fails with opaque edition or -Z errors
+    let rustc_checks = check::rustc_version(&platform_tools);
+    for result in &rustc_checks {
+        if result.status != CheckStatus::Pass {
+            result.print();
+        }
+    }
+    if rustc_checks.iter().any(|r| r.status == CheckStatus::Fail) {
+        return Err(Error::ToolchainMissing(
+            "the compiler cannot build this project".to_string(),
+        ));
+    }
+
     // Build cargo command
     let (cargo_bin, rustc_env) = get_cargo_and_rustc(&platform_tools);
 
@@ -74,9 +420,59 @@ pub fn build_contract(release: bool, arch: &str, target: Option<&str>) -> Result
 
     if release {
         cmd.arg("--release");
+        optimization.apply(&mut cmd);
+        // Emit debug info so an unstripped copy can be kept for symbolization;
+        // the deployed artifact is stripped after the build. Without
+        // llvm-objcopy it could not be, so debug info is not requested.
+        if find_llvm_tool("llvm-objcopy").is_some() {
+            cmd.env("CARGO_PROFILE_RELEASE_DEBUG", "true");
+        }
     }
 
-    cmd.arg("--target").arg(&target);
+    cmd.arg("--target").arg(&cargo_target);
+
+    if offline {
+        cmd.arg("--offline");
+    }
+
+    // Compiler messages on stdout drive the progress line; diagnostics are
+    // still rendered to stderr
+    cmd.args(["--message-format", "json-render-diagnostics"]);
+
+    // Have lld write a link map for size attribution, and make a custom
+    // [contract] entry the exported ELF entry. RUSTFLAGS overrides
+    // config-provided rustflags, so neither is passed when it is set.
+    let rustflags_set =
+        env::var_os("RUSTFLAGS").is_some() || env::var_os("CARGO_ENCODED_RUSTFLAGS").is_some();
+    let mut link_args = Vec::new();
+    let link_map = if (spec.is_some() || target.starts_with("tbpf")) && !rustflags_set {
+        let maps_dir = env::current_dir()?.join(tako_dir()).join("maps");
+        ensure_dir(&maps_dir)?;
+        let path = maps_dir.join(format!("{target}-{profile}.map"));
+        link_args.push(format!("--Map={}", path.display()));
+        Some(path)
+    } else {
+        None
+    };
+    let entry = TakoConfig::load_or_default()?.contract.entry;
+    if entry != ContractConfig::default().entry {
+        if rustflags_set {
+            eprintln!(
+                "Warning: RUSTFLAGS is set; add `-C link-arg=--entry={entry}` to it to use the configured entrypoint"
+            );
+        } else {
+            link_args.push(format!("--entry={entry}"));
+            link_args.push(format!("--export-dynamic-symbol={entry}"));
+        }
+    }
+    if !link_args.is_empty() {
+        let flags: Vec<String> = link_args
+            .iter()
+            .map(|arg| format!("\"-C\", {}", toml::Value::String(format!("link-arg={arg}"))))
+            .collect();
+        cmd.arg("--config")
+            .arg(format!("build.rustflags=[{}]", flags.join(", ")));
+    }
 
     // Add -Zbuild-std=core,alloc for building core and alloc libraries from source
     // This is required for TBPF V3+ targets as they don't have pre-built libraries
@@ -84,47 +480,341 @@ pub fn build_contract(release: bool, arch: &str, target: Option<&str>) -> Result
     // - alloc: Vec, String, Box etc. (needed by most contracts)
     cmd.arg("-Zbuild-std=core,alloc");
 
-    // Set TOS platform-tools as the Rust compiler if found
-    if let Some(rustc) = rustc_env {
-        cmd.env("RUSTC", &rustc);
+    // Set TOS platform-tools as the Rust compiler and LLVM tools if found
+    cmd.envs(toolchain_env(&platform_tools));
+
+    // Execute build
+    println!(
+        "Running: cargo build {} --target {} -Zbuild-std=core,alloc{}",
+        if release { "--release" } else { "" },
+        cargo_target,
+        if offline { " --offline" } else { "" }
+    );
+
+    run_with_progress(&mut cmd)?;
+
+    // Find the built binary
+    let binary_path = find_contract_binary_for_target(release, &target)?;
+
+    record_artifact(
+        &binary_path,
+        release,
+        arch,
+        &target,
+        link_map,
+        spec.as_ref(),
+    )?;
+
+    let build_info = BuildInfo::capture(
+        &cmd,
+        rustc_env.as_deref(),
+        arch,
+        &target,
+        profile,
+        &binary_path,
+    )?;
+    let build_info_path = BuildInfo::path_for(&binary_path);
+    build_info.save(&build_info_path)?;
+    println!("  Build info: {}", build_info_path.display());
+
+    if TakoConfig::load_or_default()?.build.target_deploy {
+        let deployed = place_in_target_deploy(&binary_path)?;
+        println!("  Deploy copy: {}", deployed.display());
     }
 
-    // Set LLVM tools environment variables if available
-    if let Some(ref tools) = platform_tools {
-        if tools.llvm_bin.exists() {
-            cmd.env("CC", tools.clang());
-            cmd.env("AR", tools.llvm_ar());
-            cmd.env("OBJDUMP", tools.llvm_objdump());
-            cmd.env("OBJCOPY", tools.llvm_objcopy());
+    println!("✓ Build successful");
+
+    Ok(binary_path)
+}
+
+/// Strip a release artifact and record it in the build manifest
+///
+/// Keeps an unstripped copy for symbolization, writes the source map and
+/// stores the artifact with its link map and target spec.
+///
+/// # Arguments
+/// * `binary_path` - Artifact produced by the build
+/// * `target` - Target triple, or the file stem of a custom target spec
+/// * `link_map` - Link map requested from lld, if any
+/// * `spec` - Custom target spec the artifact was built with
+pub fn record_artifact(
+    binary_path: &Path,
+    release: bool,
+    arch: &str,
+    target: &str,
+    link_map: Option<PathBuf>,
+    spec: Option<&TargetSpec>,
+) -> Result<()> {
+    let profile = if release { "release" } else { "debug" };
+    let mut manifest = BuildManifest::load()?;
+
+    // Binary carrying the DWARF the source map is generated from
+    let mut debug_binary = binary_path.to_path_buf();
+    if release {
+        let objcopy = find_llvm_tool("llvm-objcopy");
+        let symbols_dir = tako_dir().join("debug-symbols");
+        if let Some((hash, debug_copy)) =
+            strip_release_artifact(binary_path, objcopy.as_deref(), &symbols_dir)?
+        {
+            println!("  Debug symbols: {}", debug_copy.display());
+            manifest
+                .debug_symbols
+                .insert(hash, debug_copy.to_string_lossy().to_string());
+            debug_binary = debug_copy;
         }
     }
 
-    // Execute build
-    println!(
-        "Running: cargo build {} --target {} -Zbuild-std=core,alloc",
-        if release { "--release" } else { "" },
-        target
+    let link_map = link_map.filter(|path| path.exists());
+    if let Some(path) = &link_map {
+        println!("  Link map: {}", path.display());
+    }
+
+    let contents = fs::read(binary_path)?;
+    let sha256 = sha256_hex(&contents);
+
+    let source_map = match SourceMap::generate(&debug_binary, &sha256) {
+        Ok(Some(mut map)) => {
+            let path = SourceMap::path_for(binary_path);
+            map.save(&path)?;
+            println!("  Source map: {}", path.display());
+            Some(path)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!("Warning: no source map written: {e}");
+            None
+        }
+    };
+
+    manifest.artifact = Some(ArtifactRecord {
+        path: binary_path.to_string_lossy().to_string(),
+        sha256,
+        size: contents.len() as u64,
+        arch: arch.to_string(),
+        target: target.to_string(),
+        target_spec: spec.map(|s| s.path.to_string_lossy().to_string()),
+        target_spec_sha256: spec.map(|s| s.sha256.clone()),
+        profile: profile.to_string(),
+        link_map: link_map.map(|path| path.to_string_lossy().to_string()),
+        source_map: source_map.map(|path| path.to_string_lossy().to_string()),
+    });
+    manifest.save()?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct BuildMessage {
+    reason: String,
+    #[serde(default)]
+    target: Option<BuildTarget>,
+}
+
+#[derive(Deserialize)]
+struct BuildTarget {
+    name: String,
+}
+
+/// Run cargo build, showing each compiled crate on a progress line
+fn run_with_progress(cmd: &mut Command) -> Result<()> {
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| Error::spawn("cargo", e, Error::BuildFailed))?;
+    interrupt::track_child(&child);
+
+    // Collect stderr in the background so neither pipe blocks
+    let stderr = child.stderr.take().map(|stderr| {
+        thread::spawn(move || {
+            BufReader::new(stderr)
+                .lines()
+                .map_while(|r| r.ok())
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let mut progress = Progress::new("Compiling");
+    let mut messages = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(|r| r.ok()) {
+            let message = serde_json::from_str::<BuildMessage>(&line);
+            messages.push(line);
+            let Ok(message) = message else {
+                continue;
+            };
+            if message.reason == "compiler-artifact" {
+                if let Some(target) = message.target {
+                    match target.name.as_str() {
+                        "build-script-build" => progress.step("build script"),
+                        name => progress.step(name),
+                    }
+                }
+            }
+        }
+    }
+    progress.finish();
+
+    let status = child
+        .wait()
+        .map_err(|e| Error::BuildFailed(format!("Failed to wait for cargo: {e}")))?;
+    interrupt::untrack_child(&child);
+    let stderr = stderr
+        .and_then(|handle| handle.join().ok())
+        .unwrap_or_default()
+        .join("\n");
+    logs::record(
+        cmd,
+        &status,
+        messages.join("\n").as_bytes(),
+        stderr.as_bytes(),
     );
 
-    let output = cmd
-        .output()
-        .map_err(|e| Error::BuildFailed(format!("Failed to execute cargo: {e}")))?;
+    if !status.success() {
+        return Err(Error::BuildFailed(format!("Build failed:\n{stderr}")));
+    }
+    println!("  Compiled: {} crate(s)", progress.count());
+    Ok(())
+}
+
+/// All supported architecture versions, in build order
+pub const ALL_ARCHS: &[&str] = &["v0", "v1", "v2", "v3", "v4"];
+
+/// Build (and optionally verify) the contract for several architectures
+///
+/// Stops at the first failure unless `keep_going` is set, in which case all
+/// architectures are attempted. A status table is printed at the end and an
+/// error returned if any build failed. With `output` set, each artifact is
+/// also copied there; use an `out_name` containing `{arch}` to keep them apart.
+pub fn build_matrix(
+    archs: &[&str],
+    release: bool,
+    optimization: &OptimizationSettings,
+    offline: bool,
+    verify: bool,
+    keep_going: bool,
+    output: Option<&OutputSettings>,
+) -> Result<()> {
+    let mut results: Vec<(&str, Result<PathBuf>)> = Vec::new();
+    let profile = if release { "release" } else { "debug" };
+
+    for arch in archs {
+        println!();
+        println!("{} {arch}...", "Building".success().bold());
+        let result = build_contract(release, arch, None, optimization, offline).and_then(|path| {
+            if verify {
+                verify_contract(&path, arch, false, true)?;
+            }
+            match output {
+                Some(output) => {
+                    export_artifact(&path, output, arch, &get_target_triple(arch), profile)
+                }
+                None => Ok(path),
+            }
+        });
+        if let Err(e) = &result {
+            eprintln!("{} {arch}: {e}", "✗".failure().bold());
+        }
+
+        let failed = result.is_err();
+        results.push((arch, result));
+        if failed && !keep_going {
+            break;
+        }
+    }
+
+    println!();
+    println!("{:<6} {:<8} Result", "Arch", "Status");
+    for arch in archs {
+        match results.iter().find(|(a, _)| a == arch) {
+            Some((_, Ok(path))) => {
+                println!("{arch:<6} {:<8} {}", "ok".success(), path.display())
+            }
+            Some((_, Err(e))) => {
+                let message = e.to_string();
+                // Prefer the first compiler error over the generic prefix
+                let line = message
+                    .lines()
+                    .find(|l| l.trim_start().starts_with("error"))
+                    .or_else(|| message.lines().next())
+                    .unwrap_or_default()
+                    .trim();
+                println!("{arch:<6} {:<8} {line}", "failed".failure())
+            }
+            None => println!("{arch:<6} {}", "skipped".warning()),
+        }
+    }
+
+    let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+    if failed > 0 {
+        return Err(Error::BuildFailed(format!(
+            "{failed} of {} architecture build(s) failed",
+            archs.len()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Keep an unstripped copy of a release artifact, then strip it in place
+///
+/// The copy is stored as `<symbols_dir>/<name>-<hash>.so`, where `<hash>` is
+/// a prefix of the SHA-256 of the stripped binary, so debug info can be found
+/// again from any released artifact.
+///
+/// # Returns
+/// The full hash of the stripped binary and the path of the debug copy, or
+/// None if llvm-objcopy is unavailable and the artifact has no debug info.
+/// An artifact with debug info that cannot be stripped fails the build, so
+/// it is never deployed with DWARF.
+fn strip_release_artifact(
+    path: &Path,
+    objcopy: Option<&Path>,
+    symbols_dir: &Path,
+) -> Result<Option<(String, PathBuf)>> {
+    let Some(objcopy) = objcopy else {
+        if elf::has_debug_info(&elf::parse(&elf::read(path)?)?) {
+            return Err(Error::BuildFailed(format!(
+                "llvm-objcopy not found, cannot strip debug info from {}",
+                path.display()
+            )));
+        }
+        eprintln!("Warning: llvm-objcopy not found, release artifact left unstripped");
+        return Ok(None);
+    };
+
+    let unstripped = path.with_extension("debug.so");
+    fs::copy(path, &unstripped)?;
+
+    let output = logs::output(
+        Command::new(objcopy)
+            .arg("--strip-all")
+            .arg(&unstripped)
+            .arg(path),
+    )
+    .map_err(|e| Error::spawn("llvm-objcopy", e, Error::BuildFailed))?;
 
     if !output.status.success() {
+        let _ = fs::remove_file(&unstripped);
         let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(Error::BuildFailed(format!("Build failed:\n{stderr}")));
+        return Err(Error::BuildFailed(format!(
+            "Failed to strip artifact:\n{stderr}"
+        )));
     }
 
-    // Find the built binary
-    let binary_path = find_contract_binary_for_target(release, &target)?;
+    let hash = sha256_hex(&fs::read(path)?);
+    let name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("contract");
 
-    println!("✓ Build successful");
+    ensure_dir(symbols_dir)?;
+    let debug_copy = symbols_dir.join(format!("{name}-{}.so", &hash[..16]));
+    fs::rename(&unstripped, &debug_copy)?;
 
-    Ok(binary_path)
+    Ok(Some((hash, debug_copy)))
 }
 
 /// Get cargo binary path and optional RUSTC environment variable
-fn get_cargo_and_rustc(platform_tools: &Option<PlatformTools>) -> (String, Option<PathBuf>) {
+pub fn get_cargo_and_rustc(platform_tools: &Option<PlatformTools>) -> (String, Option<PathBuf>) {
     if let Some(ref tools) = platform_tools {
         let cargo = tools.cargo();
         let rustc = tools.rustc();
@@ -142,6 +832,24 @@ fn get_cargo_and_rustc(platform_tools: &Option<PlatformTools>) -> (String, Optio
     }
 }
 
+/// Environment variables pointing cargo at the platform-tools compiler
+/// and LLVM tools
+pub fn toolchain_env(platform_tools: &Option<PlatformTools>) -> Vec<(&'static str, PathBuf)> {
+    let mut env = Vec::new();
+    if let (_, Some(rustc)) = get_cargo_and_rustc(platform_tools) {
+        env.push(("RUSTC", rustc));
+    }
+    if let Some(tools) = platform_tools {
+        if tools.llvm_bin.exists() {
+            env.push(("CC", tools.clang()));
+            env.push(("AR", tools.llvm_ar()));
+            env.push(("OBJDUMP", tools.llvm_objdump()));
+            env.push(("OBJCOPY", tools.llvm_objcopy()));
+        }
+    }
+    env
+}
+
 /// Verify a built contract binary
 ///
 /// Checks that the contract is a valid ELF file and
@@ -157,14 +865,97 @@ fn get_cargo_and_rustc(platform_tools: &Option<PlatformTools>) -> (String, Optio
 /// - Correct e_flags for the architecture
 /// - File size is reasonable
 /// - 64-bit ELF format
-pub fn verify_contract(path: &Path, arch: &str) -> Result<()> {
-    use std::fs;
+/// - The `[contract] entry` symbol is exported and is the ELF entry (a
+///   warning, or an error with `strict`)
+/// - With `strict`, the loader requirements checked by `strict_problems`
+///
+/// The outcome is written to target/tako/verify-report.json. Verdicts are
+/// cached by binary hash, so verifying an unchanged binary again is instant
+/// unless `use_cache` is false.
+pub fn verify_contract(path: &Path, arch: &str, strict: bool, use_cache: bool) -> Result<()> {
+    let entry = TakoConfig::load_or_default()?.contract.entry;
+    let sha256 = fs::read(path).ok().map(|bytes| sha256_hex(&bytes));
+    let key = verify_cache_key(
+        sha256.as_deref(),
+        arch,
+        strict,
+        &entry,
+        VERIFY_RULES_VERSION,
+    );
+
+    let mut cache = VerifyCache::load();
+    let cached = key
+        .as_ref()
+        .filter(|_| use_cache)
+        .and_then(|key| cache.entries.get(key).cloned());
 
+    let result = match cached {
+        Some(verdict) => {
+            println!("Verifying contract... (cached verdict, use --no-cache to re-run)");
+            if verdict.passed {
+                println!("✓ Contract verified");
+                Ok(())
+            } else {
+                Err(Error::VerifyFailed(verdict.error.unwrap_or_else(|| {
+                    "verification failed (cached verdict)".to_string()
+                })))
+            }
+        }
+        None => {
+            let result = check_contract(path, arch, strict, &entry);
+            if let Some(key) = key {
+                cache.entries.insert(
+                    key,
+                    VerifyVerdict {
+                        passed: result.is_ok(),
+                        error: result.as_ref().err().map(|e| match e {
+                            Error::VerifyFailed(message) => message.clone(),
+                            e => e.to_string(),
+                        }),
+                    },
+                );
+                cache.save()?;
+            }
+            result
+        }
+    };
+
+    let report = VerifyReport {
+        schema_version: SCHEMA_VERSION,
+        path: path.to_string_lossy().to_string(),
+        sha256,
+        arch: arch.to_string(),
+        strict,
+        passed: result.is_ok(),
+        error: result.as_ref().err().map(|e| e.to_string()),
+    };
+    report.save()?;
+
+    result
+}
+
+/// Version of the verification rules; bump when checks change so cached
+/// verdicts are not reused
+const VERIFY_RULES_VERSION: u32 = 2;
+
+/// Key of a binary's cached verdict; None if the binary cannot be read
+fn verify_cache_key(
+    sha256: Option<&str>,
+    arch: &str,
+    strict: bool,
+    entry: &str,
+    rules_version: u32,
+) -> Option<String> {
+    sha256.map(|hash| VerifyCache::key(hash, arch, strict, entry, rules_version))
+}
+
+/// Run the checks of `verify_contract`
+fn check_contract(path: &Path, arch: &str, strict: bool, entry: &str) -> Result<()> {
     println!("Verifying contract...");
 
     // Check file exists
     if !path.exists() {
-        return Err(Error::BuildFailed(format!(
+        return Err(Error::VerifyFailed(format!(
             "Contract binary not found: {}",
             path.display()
         )));
@@ -172,11 +963,11 @@ pub fn verify_contract(path: &Path, arch: &str) -> Result<()> {
 
     // Read file
     let contents =
-        fs::read(path).map_err(|e| Error::BuildFailed(format!("Failed to read contract: {e}")))?;
+        fs::read(path).map_err(|e| Error::VerifyFailed(format!("Failed to read contract: {e}")))?;
 
     // Check minimum size
     if contents.len() < 64 {
-        return Err(Error::BuildFailed(format!(
+        return Err(Error::VerifyFailed(format!(
             "Contract file too small ({} bytes)",
             contents.len()
         )));
@@ -184,7 +975,7 @@ pub fn verify_contract(path: &Path, arch: &str) -> Result<()> {
 
     // Verify ELF magic
     if &contents[0..4] != b"\x7FELF" {
-        return Err(Error::BuildFailed(
+        return Err(Error::VerifyFailed(
             "Invalid ELF file: wrong magic bytes".to_string(),
         ));
     }
@@ -192,7 +983,7 @@ pub fn verify_contract(path: &Path, arch: &str) -> Result<()> {
     // Verify ELF class (64-bit)
     let elf_class = contents[4];
     if elf_class != 2 {
-        return Err(Error::BuildFailed(format!(
+        return Err(Error::VerifyFailed(format!(
             "Invalid ELF class: expected 64-bit (2), got {}",
             elf_class
         )));
@@ -203,12 +994,35 @@ pub fn verify_contract(path: &Path, arch: &str) -> Result<()> {
     let expected_flags = get_expected_flags(arch);
 
     if e_flags != expected_flags {
-        return Err(Error::BuildFailed(format!(
+        return Err(Error::VerifyFailed(format!(
             "Wrong e_flags: expected 0x{:x} for {}, got 0x{:x}",
             expected_flags, arch, e_flags
         )));
     }
 
+    // Strict verification turns entrypoint problems into errors
+    let entry_problems = elf::parse(&contents)
+        .map(|parsed| elf::entry_problems(&parsed, entry))
+        .unwrap_or_default();
+    if !strict {
+        for p in &entry_problems {
+            eprintln!("Warning: {p}");
+        }
+    }
+
+    if strict {
+        let problems = strict_problems(&contents, arch, entry)?;
+        if !problems.is_empty() {
+            for p in &problems {
+                eprintln!("  error: {p}");
+            }
+            return Err(Error::VerifyFailed(format!(
+                "Strict verification failed ({} problem(s))",
+                problems.len()
+            )));
+        }
+    }
+
     // Check file size (warn if too large)
     const MAX_REASONABLE_SIZE: usize = 10 * 1024 * 1024; // 10MB
     if contents.len() > MAX_REASONABLE_SIZE {
@@ -224,6 +1038,12 @@ pub fn verify_contract(path: &Path, arch: &str) -> Result<()> {
         contents.len(),
         contents.len() as f64 / 1024.0
     );
+    if entry_problems.is_empty() {
+        println!("  Entrypoint: {entry}");
+    }
+    if strict {
+        println!("  Strict: passed");
+    }
     println!(
         "  Type: TBPF {} contract (ready for deployment)",
         arch.to_uppercase()
@@ -232,53 +1052,284 @@ pub fn verify_contract(path: &Path, arch: &str) -> Result<()> {
     Ok(())
 }
 
-/// Dump ELF information using llvm-readelf
-pub fn dump_elf(path: &Path) -> Result<()> {
-    println!("ELF dump for {}", path.display());
-    println!();
+/// Start of the read-only data region in the V3 memory layout
+const MM_RODATA_START: u64 = 0x1_0000_0000;
 
-    // Try to use platform-tools llvm-readelf first
-    let platform_tools = find_platform_tools(None);
+/// Start of the stack region in the V3 memory layout
+const MM_STACK_START: u64 = 0x2_0000_0000;
 
-    let output = if let Some(ref tools) = platform_tools {
-        let llvm_readelf = tools.llvm_bin.join("llvm-readelf");
-        if llvm_readelf.exists() {
-            Command::new(&llvm_readelf)
-                .args(["-h", "-l", path.to_str().unwrap_or("")])
-                .output()
-                .ok()
-        } else {
-            None
+/// Check loader requirements that go beyond the ELF header
+///
+/// # Checks
+/// - Shared object (ET_DYN) exporting the configured entry symbol as the ELF
+///   entry point
+/// - No segment that is both writable and executable
+/// - V3 and later: code in the TEXT region, read-only data in the RODATA
+///   region, no writable segments and no dynamic relocations
+fn strict_problems(contents: &[u8], arch: &str, entry: &str) -> Result<Vec<String>> {
+    use goblin::elf::header::ET_DYN;
+    use goblin::elf::program_header::{PF_W, PF_X, PT_LOAD};
+
+    let elf = elf::parse(contents)?;
+    let mut problems = Vec::new();
+
+    if elf.header.e_type != ET_DYN {
+        problems.push(format!(
+            "ELF type is {}, expected a shared object (ET_DYN)",
+            elf.header.e_type
+        ));
+    }
+
+    problems.extend(elf::entry_problems(&elf, entry));
+
+    let static_layout = !matches!(arch, "v0" | "v1" | "v2");
+
+    for ph in elf.program_headers.iter().filter(|ph| ph.p_type == PT_LOAD) {
+        let writable = ph.p_flags & PF_W != 0;
+        let executable = ph.p_flags & PF_X != 0;
+        let start = ph.p_vaddr;
+        let end = ph.p_vaddr + ph.p_memsz;
+
+        if writable && executable {
+            problems.push(format!(
+                "Segment at 0x{start:x} is both writable and executable"
+            ));
         }
-    } else {
-        None
-    };
 
-    // Fall back to system tools
-    let output = output.or_else(|| {
-        Command::new("llvm-readelf")
-            .args(["-h", "-l", path.to_str().unwrap_or("")])
-            .output()
-            .ok()
-    }).or_else(|| {
-        Command::new("readelf")
-            .args(["-h", "-l", path.to_str().unwrap_or("")])
-            .output()
-            .ok()
-    });
+        if !static_layout {
+            continue;
+        }
 
-    match output {
-        Some(out) if out.status.success() => {
-            println!("{}", String::from_utf8_lossy(&out.stdout));
+        if writable {
+            problems.push(format!(
+                "Writable segment at 0x{start:x} (static data is not supported in {arch})"
+            ));
+        } else if executable && end > MM_RODATA_START {
+            problems.push(format!(
+                "Code segment 0x{start:x}..0x{end:x} extends beyond the TEXT region"
+            ));
+        } else if !executable && (start < MM_RODATA_START || end > MM_STACK_START) {
+            problems.push(format!(
+                "Read-only segment 0x{start:x}..0x{end:x} is outside the RODATA region"
+            ));
         }
-        Some(out) => {
-            let stderr = String::from_utf8_lossy(&out.stderr);
-            eprintln!("Warning: readelf failed: {stderr}");
+    }
+
+    if static_layout {
+        let relocations = elf.dynrels.len() + elf.dynrelas.len();
+        if relocations > 0 {
+            problems.push(format!(
+                "{relocations} dynamic relocation(s) found ({arch} requires static syscalls)"
+            ));
         }
-        None => {
-            return Err(Error::BuildFailed("Failed to run readelf or llvm-readelf".to_string()));
+    }
+
+    Ok(problems)
+}
+
+/// Find the most recently built contract for verification
+///
+/// Uses the artifact recorded in the build manifest, falling back to the
+/// release output directory for the architecture's target.
+pub fn latest_artifact(arch: &str) -> Result<PathBuf> {
+    if let Some(artifact) = BuildManifest::load()?.artifact {
+        let path = PathBuf::from(artifact.path);
+        if path.exists() {
+            return Ok(path);
         }
     }
+    find_contract_binary_for_target(true, &get_target_triple(arch))
+}
+
+/// Compare the build environment of the latest artifact with a reference build
+///
+/// # Arguments
+/// * `against` - tako-build-info.json of the reference build
+/// * `arch` - TBPF architecture version used to locate the latest build
+pub fn verify_build(against: &Path, arch: &str) -> Result<()> {
+    let artifact = latest_artifact(arch)?;
+    let path = BuildInfo::path_for(&artifact);
+    if !path.exists() {
+        return Err(Error::BuildFailed(format!(
+            "{} not found; rebuild with `cargo tako build` to record the build environment",
+            path.display()
+        )));
+    }
+
+    let ours = BuildInfo::load(&path)?;
+    if ours.artifact_sha256 != sha256_hex(&fs::read(&artifact)?) {
+        return Err(Error::BuildFailed(format!(
+            "{} does not describe {}; rebuild with `cargo tako build`",
+            path.display(),
+            artifact.display()
+        )));
+    }
+    let reference = BuildInfo::load(against)?;
+
+    println!("  Artifact: {}", artifact.display());
+    println!("  Reference: {}", against.display());
+
+    let differences = ours.differences(&reference);
+    if differences.is_empty() {
+        println!("✓ Build environment and artifact match");
+        return Ok(());
+    }
+
+    for (field, ours, theirs) in &differences {
+        println!("✗ {field}");
+        println!("    ours:   {}", ours.replace('\n', "\n            "));
+        println!("    theirs: {}", theirs.replace('\n', "\n            "));
+    }
+    Err(Error::VerifyFailed(format!(
+        "{} field(s) differ from the reference build",
+        differences.len()
+    )))
+}
+
+/// Dump ELF headers, segments, sections and dynamic entries
+pub fn dump_elf(path: &Path) -> Result<()> {
+    println!("ELF dump for {}", path.display());
+    println!();
+
+    let bytes = elf::read(path)?;
+    elf::print_report(&elf::parse(&bytes)?);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_cache_misses() {
+        let key = |bytes: &[u8], rules_version| {
+            let hash = sha256_hex(bytes);
+            verify_cache_key(Some(&hash), "v3", false, "entrypoint", rules_version).unwrap()
+        };
+        let mut cache = VerifyCache::default();
+        cache.entries.insert(
+            key(b"artifact", VERIFY_RULES_VERSION),
+            VerifyVerdict {
+                passed: true,
+                error: None,
+            },
+        );
+
+        assert!(cache
+            .entries
+            .contains_key(&key(b"artifact", VERIFY_RULES_VERSION)));
+        // A rebuilt artifact is verified again
+        assert!(!cache
+            .entries
+            .contains_key(&key(b"rebuilt artifact", VERIFY_RULES_VERSION)));
+        // So is every artifact after the rules change
+        assert!(!cache
+            .entries
+            .contains_key(&key(b"artifact", VERIFY_RULES_VERSION + 1)));
+        // And an unreadable one is never cached
+        assert_eq!(
+            verify_cache_key(None, "v3", false, "entrypoint", VERIFY_RULES_VERSION),
+            None
+        );
+    }
+
+    #[test]
+    fn test_render_out_name() {
+        let vars = [("name", "token"), ("version", "1.0.0"), ("arch", "v3")];
+        assert_eq!(
+            render_out_name("{name}-{version}-{arch}.so", &vars).unwrap(),
+            "token-1.0.0-v3.so"
+        );
+        assert_eq!(
+            render_out_name("contract.so", &vars).unwrap(),
+            "contract.so"
+        );
+
+        let err = render_out_name("{name}-{commit}.so", &vars).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Unknown placeholder {commit} in out_name (expected name, version, arch)"));
+        assert!(render_out_name("{name.so", &vars).is_err());
+        assert!(render_out_name("{}.so", &vars).is_err());
+        assert!(render_out_name("../{name}.so", &vars).is_err());
+
+        // Values are not scanned for placeholders
+        let vars = [("name", "{arch}")];
+        assert_eq!(render_out_name("{name}.so", &vars).unwrap(), "{arch}.so");
+    }
+
+    #[test]
+    fn test_optimization_precedence() {
+        let resolve = |config: &BuildConfig, preset, lto, panic| {
+            OptimizationSettings::resolve(config, preset, lto, panic).unwrap()
+        };
+
+        // Defaults
+        let defaults = resolve(&BuildConfig::default(), None, None, None);
+        assert_eq!(
+            defaults.summary(),
+            "opt-level=z, lto=fat, codegen-units=1, panic=abort"
+        );
+
+        // Tako.toml over defaults
+        let config: BuildConfig =
+            toml::from_str("opt_level = \"s\"\nlto = \"thin\"\npanic = \"unwind\"\n").unwrap();
+        let settings = resolve(&config, None, None, None);
+        assert_eq!(
+            settings.summary(),
+            "opt-level=s, lto=thin, codegen-units=1, panic=unwind"
+        );
+
+        // Command line over Tako.toml
+        let settings = resolve(&config, Some("speed"), Some("off"), Some("abort"));
+        assert_eq!(
+            settings.summary(),
+            "opt-level=3, lto=off, codegen-units=1, panic=abort"
+        );
+
+        // Invalid Tako.toml values are rejected unless overridden
+        let config: BuildConfig = toml::from_str("lto = \"full\"\npanic = \"halt\"").unwrap();
+        let err = OptimizationSettings::resolve(&config, None, None, Some("abort")).unwrap_err();
+        assert!(err.to_string().contains("Invalid lto 'full'"));
+        let err = OptimizationSettings::resolve(&config, None, Some("fat"), None).unwrap_err();
+        assert!(err.to_string().contains("Invalid panic 'halt'"));
+        assert!(resolve(&config, None, Some("fat"), Some("abort")).lto == "fat");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_strip_release_artifact() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("tako-strip-{}", std::process::id()));
+        let symbols = dir.join("debug-symbols");
+        ensure_dir(&dir).unwrap();
+        // Test binaries carry DWARF, like a release build with debug = true
+        let artifact = dir.join("contract.so");
+        fs::copy(std::env::current_exe().unwrap(), &artifact).unwrap();
+
+        let err = strip_release_artifact(&artifact, None, &symbols).unwrap_err();
+        assert!(err.to_string().contains("cannot strip debug info"));
+
+        // Stand-in objcopy writing a "stripped" binary to its output path
+        let objcopy = dir.join("llvm-objcopy");
+        fs::write(&objcopy, "#!/bin/sh\nprintf stripped > \"$3\"\n").unwrap();
+        fs::set_permissions(&objcopy, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let (hash, debug_copy) = strip_release_artifact(&artifact, Some(&objcopy), &symbols)
+            .unwrap()
+            .unwrap();
+        assert_eq!(fs::read(&artifact).unwrap(), b"stripped");
+        assert_eq!(hash, sha256_hex(b"stripped"));
+        assert_eq!(
+            debug_copy,
+            symbols.join(format!("contract-{}.so", &hash[..16]))
+        );
+        assert!(elf::has_debug_info(
+            &elf::parse(&fs::read(&debug_copy).unwrap()).unwrap()
+        ));
+        assert!(!artifact.with_extension("debug.so").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
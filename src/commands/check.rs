@@ -0,0 +1,800 @@
+//! Check command implementation
+//!
+//! Pre-flight checks over the contract sources that catch configuration
+//! problems before they surface as linker errors in a TBPF build, and custom
+//! error codes used by more than one error.
+
+use crate::arch_compat;
+use crate::commands::build;
+use crate::compat::{self, CompatTable};
+use crate::error::{Error, Result};
+use crate::error_codes;
+use crate::logs;
+use crate::metadata;
+use crate::std_audit::{self, Probe};
+use crate::style::Styled;
+use crate::target_spec::TargetSpec;
+use crate::toolchain::{
+    self, find_platform_tools, PlatformTools, RustcVersion, DEFAULT_PLATFORM_TOOLS_VERSION,
+    DEFAULT_RUST_VERSION,
+};
+use colored::Colorize;
+use quote::ToTokens;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use syn::visit::Visit;
+use walkdir::WalkDir;
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// Result of a single check, with a suggested fix for warnings and failures
+#[derive(Debug)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+impl CheckResult {
+    fn pass(name: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            message: message.into(),
+            suggestion: None,
+        }
+    }
+
+    fn warn(name: &'static str, message: impl Into<String>, suggestion: &str) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Warn,
+            message: message.into(),
+            suggestion: Some(suggestion.to_string()),
+        }
+    }
+
+    fn fail(name: &'static str, message: impl Into<String>, suggestion: &str) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            message: message.into(),
+            suggestion: Some(suggestion.to_string()),
+        }
+    }
+
+    /// Print the result, including the suggestion if there is one
+    pub fn print(&self) {
+        let status = match self.status {
+            CheckStatus::Pass => "✓".success().bold(),
+            CheckStatus::Warn => "!".warning().bold(),
+            CheckStatus::Fail => "✗".failure().bold(),
+        };
+        println!("{status} {}: {}", self.name, self.message);
+        if let Some(suggestion) = &self.suggestion {
+            for line in suggestion.lines() {
+                if line.is_empty() {
+                    println!();
+                } else {
+                    println!("    {line}");
+                }
+            }
+        }
+    }
+}
+
+const NO_STD_SNIPPET: &str = "TBPF builds only provide core and alloc. Add to src/lib.rs:
+
+#![cfg_attr(not(test), no_std)]";
+
+const PANIC_HANDLER_SNIPPET: &str =
+    "no_std contracts must define a panic handler. Add to src/lib.rs:
+
+#[cfg(not(test))]
+#[panic_handler]
+fn panic(_info: &core::panic::PanicInfo) -> ! {
+    unsafe extern \"C\" {
+        fn abort() -> !;
+    }
+    unsafe { abort() }
+}";
+
+const PANIC_HANDLER_CFG_SNIPPET: &str =
+    "std provides a panic handler in `cargo tako test`. Gate yours:
+
+#[cfg(not(test))]
+#[panic_handler]";
+
+const ALLOCATOR_SNIPPET: &str =
+    "The crate uses `alloc` but defines no global allocator. Add a bump \
+allocator over the TBPF heap region:
+
+#[cfg(not(test))]
+#[global_allocator]
+static ALLOCATOR: BumpAllocator = BumpAllocator;
+
+struct BumpAllocator;
+
+const HEAP_START: usize = 0x3_0000_0000;
+const HEAP_LENGTH: usize = 32 * 1024;
+
+unsafe impl core::alloc::GlobalAlloc for BumpAllocator {
+    unsafe fn alloc(&self, layout: core::alloc::Layout) -> *mut u8 {
+        // The first word of the heap holds the current (downward) position
+        let pos_ptr = HEAP_START as *mut usize;
+        let mut pos = *pos_ptr;
+        if pos == 0 {
+            pos = HEAP_START + HEAP_LENGTH;
+        }
+        pos = pos.saturating_sub(layout.size()) & !(layout.align() - 1);
+        if pos < HEAP_START + core::mem::size_of::<usize>() {
+            return core::ptr::null_mut();
+        }
+        *pos_ptr = pos;
+        pos as *mut u8
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: core::alloc::Layout) {}
+}";
+
+/// Facts about the contract sources relevant to pre-flight checks
+#[derive(Debug, Default)]
+struct SourceFacts {
+    /// Crate root declares `no_std` (directly or via `cfg_attr`)
+    no_std: bool,
+    /// `extern crate alloc` is declared
+    uses_alloc: bool,
+    /// One entry per `#[panic_handler]`, true if it is cfg-gated
+    panic_handlers: Vec<bool>,
+    global_allocator: bool,
+    /// Names of invoked macros (last path segment)
+    macros: Vec<String>,
+}
+
+impl SourceFacts {
+    /// A macro that likely expands to the given item (e.g. an SDK entrypoint macro)
+    fn macro_providing(&self, fragments: &[&str]) -> Option<&str> {
+        self.macros
+            .iter()
+            .find(|m| fragments.iter().any(|f| m.contains(f)))
+            .map(String::as_str)
+    }
+}
+
+impl<'ast> Visit<'ast> for SourceFacts {
+    fn visit_item_extern_crate(&mut self, item: &'ast syn::ItemExternCrate) {
+        if item.ident == "alloc" {
+            self.uses_alloc = true;
+        }
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        if has_attr(&item.attrs, "panic_handler") {
+            self.panic_handlers.push(has_attr(&item.attrs, "cfg"));
+        }
+        syn::visit::visit_item_fn(self, item);
+    }
+
+    fn visit_item_static(&mut self, item: &'ast syn::ItemStatic) {
+        if has_attr(&item.attrs, "global_allocator") {
+            self.global_allocator = true;
+        }
+        syn::visit::visit_item_static(self, item);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if let Some(segment) = mac.path.segments.last() {
+            self.macros.push(segment.ident.to_string());
+        }
+    }
+}
+
+fn has_attr(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs.iter().any(|a| a.path().is_ident(name))
+}
+
+/// Check whether crate-level attributes declare `no_std`
+fn declares_no_std(file: &syn::File) -> bool {
+    file.attrs.iter().any(|a| {
+        a.path().is_ident("no_std")
+            || (a.path().is_ident("cfg_attr")
+                && a.meta.to_token_stream().to_string().contains("no_std"))
+    })
+}
+
+/// Collect source facts from the crate root and all files under `src_dir`
+fn scan_sources(src_dir: &Path) -> Result<SourceFacts> {
+    let mut facts = SourceFacts::default();
+
+    for entry in WalkDir::new(src_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
+    {
+        let content = fs::read_to_string(entry.path())?;
+        let file = syn::parse_file(&content).map_err(|e| {
+            Error::Other(format!("Failed to parse {}: {e}", entry.path().display()))
+        })?;
+
+        if entry.path() == src_dir.join("lib.rs") {
+            facts.no_std = declares_no_std(&file);
+        }
+        facts.visit_file(&file);
+    }
+
+    Ok(facts)
+}
+
+/// Evaluate the allocator and panic handler configuration
+fn evaluate(facts: &SourceFacts) -> Vec<CheckResult> {
+    let mut results = Vec::new();
+
+    if !facts.no_std {
+        results.push(CheckResult::warn(
+            "no_std",
+            "crate root does not declare no_std",
+            NO_STD_SNIPPET,
+        ));
+        // Without no_std the build fails on std itself; the remaining checks
+        // would only add noise
+        return results;
+    }
+    results.push(CheckResult::pass("no_std", "crate is no_std"));
+
+    match facts.panic_handlers.as_slice() {
+        [] => match facts.macro_providing(&["entrypoint", "panic"]) {
+            Some(m) => results.push(CheckResult::pass(
+                "panic handler",
+                format!("assumed to be provided by `{m}!`"),
+            )),
+            None => results.push(CheckResult::fail(
+                "panic handler",
+                "no #[panic_handler] found",
+                PANIC_HANDLER_SNIPPET,
+            )),
+        },
+        [gated] => {
+            if *gated {
+                results.push(CheckResult::pass("panic handler", "defined"));
+            } else {
+                results.push(CheckResult::warn(
+                    "panic handler",
+                    "#[panic_handler] is not cfg-gated and will conflict with std in tests",
+                    PANIC_HANDLER_CFG_SNIPPET,
+                ));
+            }
+        }
+        handlers => results.push(CheckResult::fail(
+            "panic handler",
+            format!("{} #[panic_handler] functions defined", handlers.len()),
+            "Keep exactly one panic handler in the contract crate.",
+        )),
+    }
+
+    if !facts.uses_alloc {
+        results.push(CheckResult::pass(
+            "global allocator",
+            "not needed (crate does not use alloc)",
+        ));
+    } else if facts.global_allocator {
+        results.push(CheckResult::pass("global allocator", "defined"));
+    } else if let Some(m) = facts.macro_providing(&["entrypoint", "heap", "allocator"]) {
+        results.push(CheckResult::pass(
+            "global allocator",
+            format!("assumed to be provided by `{m}!`"),
+        ));
+    } else {
+        results.push(CheckResult::fail(
+            "global allocator",
+            "crate uses alloc but no #[global_allocator] found",
+            ALLOCATOR_SNIPPET,
+        ));
+    }
+
+    results
+}
+
+/// Run pre-flight checks on the sources under `src_dir`
+///
+/// Returns no results for packages without a `lib.rs` crate root.
+pub fn preflight(src_dir: &Path) -> Result<Vec<CheckResult>> {
+    if !src_dir.join("lib.rs").exists() {
+        return Ok(Vec::new());
+    }
+    let mut results = evaluate(&scan_sources(src_dir)?);
+    results.extend(error_codes(src_dir)?);
+    Ok(results)
+}
+
+/// Check that no two custom errors share a numeric code
+pub fn error_codes(src_dir: &Path) -> Result<Vec<CheckResult>> {
+    let catalog = error_codes::extract_catalog(src_dir)?;
+    if catalog.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let collisions = error_codes::collisions(&catalog);
+    if collisions.is_empty() {
+        return Ok(vec![CheckResult::pass(
+            "error codes",
+            format!("{} codes, all distinct", catalog.len()),
+        )]);
+    }
+    Ok(collisions
+        .iter()
+        .map(|(code, errors)| {
+            let names: Vec<String> = errors.iter().map(|e| e.path()).collect();
+            CheckResult::fail(
+                "error codes",
+                format!("code {code} is used by {}", names.join(", ")),
+                "Clients cannot tell these errors apart. Give each one its own code.",
+            )
+        })
+        .collect())
+}
+
+/// First rustc release supporting each edition
+const EDITION_RUSTC: &[(&str, &str)] = &[("2018", "1.31"), ("2021", "1.56"), ("2024", "1.85")];
+
+/// Check that the compiler used for TBPF builds can build the project
+///
+/// Compares `rustc --version --verbose` against the edition and
+/// `rust-version` of each workspace member, and checks that the compiler
+/// accepts `-Zbuild-std`.
+pub fn rustc_version(platform_tools: &Option<PlatformTools>) -> Vec<CheckResult> {
+    let rustc = build::get_cargo_and_rustc(platform_tools)
+        .1
+        .unwrap_or_else(|| PathBuf::from("rustc"));
+    let Some(version) = RustcVersion::query(&rustc) else {
+        return vec![CheckResult::warn(
+            "rustc version",
+            format!("could not run {} --version", rustc.display()),
+            &format!(
+                "Install platform-tools {DEFAULT_PLATFORM_TOOLS_VERSION}:\n\n{}",
+                toolchain::install_instructions(DEFAULT_PLATFORM_TOOLS_VERSION)
+            ),
+        )];
+    };
+
+    let mut requirements = Vec::new();
+    for package in metadata::load().map(|m| m.packages).unwrap_or_default() {
+        if let Some((_, release)) = EDITION_RUSTC.iter().find(|(e, _)| *e == package.edition) {
+            requirements.push((
+                release.to_string(),
+                format!("edition {} of {}", package.edition, package.name),
+            ));
+        }
+        if let Some(msrv) = package.rust_version {
+            requirements.push((msrv, format!("rust-version of {}", package.name)));
+        }
+    }
+    evaluate_rustc(&version, &requirements)
+}
+
+/// Check a compiler against (minimum release, reason) requirements
+fn evaluate_rustc(version: &RustcVersion, requirements: &[(String, String)]) -> Vec<CheckResult> {
+    let install = format!(
+        "Install platform-tools {DEFAULT_PLATFORM_TOOLS_VERSION} (rustc {DEFAULT_RUST_VERSION}):\n\n{}",
+        toolchain::install_instructions(DEFAULT_PLATFORM_TOOLS_VERSION)
+    );
+    let mut results = Vec::new();
+
+    if !version.allows_unstable() {
+        results.push(CheckResult::fail(
+            "rustc version",
+            format!(
+                "rustc {} is a stable release, which rejects -Zbuild-std",
+                version.release
+            ),
+            &format!("TBPF builds compile core and alloc from source. {install}"),
+        ));
+    }
+
+    let too_old = requirements
+        .iter()
+        .filter(|(required, _)| compat::version_less(version.version(), required))
+        .fold(None, |newest: Option<&(String, String)>, r| match newest {
+            Some(n) if !compat::version_less(&n.0, &r.0) => Some(n),
+            _ => Some(r),
+        });
+    match too_old {
+        Some((required, reason)) => {
+            let suggestion = if compat::version_less(DEFAULT_RUST_VERSION, required) {
+                format!(
+                    "No platform-tools release known to this cargo-tako bundles rustc {required}.\nLower the {reason} to {DEFAULT_RUST_VERSION} or below, or update cargo-tako:\n\ncargo install --force cargo-tako"
+                )
+            } else {
+                install
+            };
+            results.push(CheckResult::fail(
+                "rustc version",
+                format!(
+                    "rustc {} is older than {required}, required by the {reason}",
+                    version.release
+                ),
+                &suggestion,
+            ));
+        }
+        None if results.is_empty() => results.push(CheckResult::pass(
+            "rustc version",
+            format!("rustc {} ({})", version.release, version.host),
+        )),
+        None => {}
+    }
+    results
+}
+
+/// Audit dependencies for `std` usage with a compile probe
+pub fn no_std_audit() -> Result<Vec<CheckResult>> {
+    let (probe, leaks) = std_audit::audit()?;
+
+    let mut results = Vec::new();
+    let confirmed = match &probe {
+        Probe::Completed(_) => true,
+        Probe::Unavailable(reason) => {
+            results.push(CheckResult::warn(
+                "no_std probe",
+                format!("could not compile for a target without std ({reason})"),
+                "Install rust-src for -Zbuild-std:\n\nrustup component add rust-src\n\nUntil then, std features are reported from the dependency graph only.",
+            ));
+            false
+        }
+    };
+
+    for leak in &leaks {
+        let message = match &leak.feature {
+            Some(feature) => format!(
+                "{} v{} has feature \"{feature}\" enabled{}",
+                leak.krate,
+                leak.version,
+                if leak.confirmed {
+                    " and does not build without std"
+                } else {
+                    ""
+                }
+            ),
+            None => format!(
+                "{} v{} does not build without std and has no std feature",
+                leak.krate, leak.version
+            ),
+        };
+
+        let mut suggestion = String::new();
+        if !leak.chain.is_empty() {
+            suggestion.push_str(&format!("Enabled by: {}\n\n", leak.chain.join(" <- ")));
+        }
+        match (leak.fix(), &leak.feature) {
+            (Some(fix), _) if fix.feature == "default" => suggestion.push_str(&format!(
+                "Add `default-features = false` to {} in the Cargo.toml of {}:\n\n[dependencies]\n{} = {{ ..., default-features = false }}",
+                fix.dependency, fix.package, fix.dependency
+            )),
+            (Some(fix), _) => suggestion.push_str(&format!(
+                "Remove feature \"{}\" of {} in the Cargo.toml of {}",
+                fix.feature, fix.dependency, fix.package
+            )),
+            (None, None) => suggestion.push_str(&format!(
+                "Replace {} with a no_std alternative, or ask upstream for a std feature",
+                leak.krate
+            )),
+            (None, Some(feature)) => suggestion.push_str(&format!(
+                "Find the dependency that enables {}/{feature} with `cargo tree -e features -i {}`",
+                leak.krate, leak.krate
+            )),
+        }
+
+        if leak.confirmed || !confirmed {
+            results.push(CheckResult::fail("no_std audit", message, &suggestion));
+        } else {
+            // The feature is on but the crate still builds without std
+            results.push(CheckResult::warn("no_std audit", message, &suggestion));
+        }
+    }
+
+    if results.is_empty() {
+        results.push(CheckResult::pass(
+            "no_std audit",
+            "no dependency pulls in std",
+        ));
+    }
+    Ok(results)
+}
+
+/// Scan dependencies for code and assets that only target Solana/SBF
+pub fn arch_compat() -> Result<Vec<CheckResult>> {
+    let metadata = metadata::load_with_deps()?;
+
+    let mut results = Vec::new();
+    for package in metadata.dependencies().filter(|p| !p.is_proc_macro()) {
+        let findings = arch_compat::scan_package(package);
+        if findings.is_empty() {
+            continue;
+        }
+
+        let mut problems = Vec::new();
+        if let Some(first) = findings.cfg_gates.first() {
+            problems.push(format!(
+                "{} cfg gate(s) for solana/sbf ({first})",
+                findings.cfg_gates.len()
+            ));
+        }
+        if let Some(first) = findings.build_script.first() {
+            problems.push(format!("build script matches solana/sbf targets ({first})"));
+        }
+        if let Some(first) = findings.blobs.first() {
+            problems.push(format!(
+                "{} prebuilt blob(s) ({})",
+                findings.blobs.len(),
+                first.display()
+            ));
+        }
+
+        let suggestion = format!(
+            "These do not apply to tbpf*-tos-tos targets. Disable the Solana-specific code if a feature allows it:
+
+[dependencies]
+{name} = {{ version = \"{version}\", default-features = false }}
+
+or patch in a version with TBPF support:
+
+[patch.crates-io]
+{name} = {{ git = \"<fork with TBPF support>\" }}",
+            name = findings.name,
+            version = findings.version,
+        );
+        results.push(CheckResult::warn(
+            "arch compat",
+            format!(
+                "{} v{}: {}",
+                findings.name,
+                findings.version,
+                problems.join(", ")
+            ),
+            &suggestion,
+        ));
+    }
+    Ok(results)
+}
+
+/// Type-check the contract for the TBPF target, printing cargo's JSON messages
+///
+/// Used as the check-on-save command of rust-analyzer (see `cargo tako ide
+/// setup`), so editor diagnostics come from the same compiler and target as
+/// real builds.
+///
+/// # Arguments
+/// * `arch` - TBPF architecture version to check for
+/// * `target_spec` - Custom target spec used instead of the arch's triple
+pub fn check_json(arch: &str, target_spec: Option<&Path>) -> Result<()> {
+    let platform_tools = find_platform_tools(Some(DEFAULT_PLATFORM_TOOLS_VERSION));
+    let (cargo_bin, rustc) = build::get_cargo_and_rustc(&platform_tools);
+    let target = match target_spec {
+        Some(path) => TargetSpec::load(path)?.path.to_string_lossy().to_string(),
+        None => build::get_target_triple(arch),
+    };
+
+    let mut cmd = Command::new(&cargo_bin);
+    cmd.args(["check", "--message-format", "json", "--target"])
+        .arg(target)
+        .arg("-Zbuild-std=core,alloc");
+    if let Some(rustc) = rustc {
+        cmd.env("RUSTC", rustc);
+    }
+
+    let status =
+        logs::status(&mut cmd).map_err(|e| Error::spawn("cargo", e, Error::BuildFailed))?;
+    if !status.success() {
+        return Err(Error::BuildFailed("cargo check failed".to_string()));
+    }
+    Ok(())
+}
+
+/// Check the project's TAKO SDK version against the compatibility table
+///
+/// Warns when the SDK needs a newer platform-tools or cargo-tako, or does
+/// not support `arch`. Projects without a git dependency on the SDK are
+/// not checked.
+pub fn sdk_compat(arch: &str) -> Vec<CheckResult> {
+    let Some(sdk_ref) = compat::project_sdk_ref(Path::new("Cargo.toml")) else {
+        return Vec::new();
+    };
+    let table = CompatTable::load();
+    let Some(entry) = table.find(&sdk_ref) else {
+        return vec![CheckResult::warn(
+            "sdk compatibility",
+            format!("SDK {sdk_ref} is not in the compatibility table"),
+            "Run `cargo tako compat --refresh` to download the latest table",
+        )];
+    };
+
+    let tools = find_platform_tools(Some(DEFAULT_PLATFORM_TOOLS_VERSION));
+    let problems = entry.check(tools.as_ref(), arch, env!("CARGO_PKG_VERSION"));
+    if problems.is_empty() {
+        return vec![CheckResult::pass(
+            "sdk compatibility",
+            format!("SDK {sdk_ref} supports {arch} with the selected platform-tools"),
+        )];
+    }
+    problems
+        .into_iter()
+        .map(|p| CheckResult::warn("sdk compatibility", p.message, &p.suggestion))
+        .collect()
+}
+
+/// Print the compatibility table and check the current project against it
+///
+/// # Arguments
+/// * `arch` - TBPF architecture version to check
+/// * `refresh` - Download the latest table first
+pub fn compat_table(arch: &str, refresh: bool) -> Result<()> {
+    if refresh {
+        let path = CompatTable::refresh()?;
+        println!("  Updated {}", path.display());
+    }
+
+    let table = CompatTable::load();
+    println!();
+    println!(
+        "{:<16} {:<16} {:<22} cargo-tako",
+        "SDK", "platform-tools", "archs"
+    );
+    for entry in &table.sdk {
+        println!(
+            "{:<16} {:<16} {:<22} {}",
+            entry.version,
+            format!(">= {}", entry.platform_tools),
+            entry.archs.join(","),
+            entry
+                .cargo_tako
+                .as_deref()
+                .map(|v| format!(">= {v}"))
+                .unwrap_or_default()
+        );
+    }
+
+    let results = sdk_compat(arch);
+    if !results.is_empty() {
+        println!();
+    }
+    for result in &results {
+        result.print();
+    }
+    Ok(())
+}
+
+/// Validate a custom target spec passed with `--target-spec`
+pub fn target_spec(path: &Path) -> Vec<CheckResult> {
+    match TargetSpec::load(path) {
+        Ok(spec) => vec![CheckResult::pass(
+            "target spec",
+            format!("{} ({})", spec.path.display(), &spec.sha256[..16]),
+        )],
+        Err(e) => vec![CheckResult::fail(
+            "target spec",
+            e.to_string(),
+            "Target specs need llvm-target, arch, data-layout and a 64-bit target-pointer-width",
+        )],
+    }
+}
+
+/// Run all checks for the current project and print the results
+///
+/// # Arguments
+/// * `arch` - TBPF architecture version the project is built for
+/// * `no_std_audit` - Also audit dependencies for `std` usage with a compile probe
+/// * `target_spec` - Custom target spec the project is built with, if any
+pub fn run_checks(arch: &str, no_std_audit: bool, target_spec: Option<&Path>) -> Result<()> {
+    let mut results = preflight(Path::new("src"))?;
+    if let Some(path) = target_spec {
+        results.extend(self::target_spec(path));
+    }
+    results.extend(arch_compat()?);
+    results.extend(sdk_compat(arch));
+    results.extend(rustc_version(&find_platform_tools(Some(
+        DEFAULT_PLATFORM_TOOLS_VERSION,
+    ))));
+    if no_std_audit {
+        results.extend(self::no_std_audit()?);
+    }
+    for result in &results {
+        result.print();
+    }
+
+    let failures = results
+        .iter()
+        .filter(|r| r.status == CheckStatus::Fail)
+        .count();
+    if failures > 0 {
+        return Err(Error::BuildFailed(format!("{failures} check(s) failed")));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facts(source: &str) -> SourceFacts {
+        let file = syn::parse_file(source).unwrap();
+        let mut facts = SourceFacts {
+            no_std: declares_no_std(&file),
+            ..Default::default()
+        };
+        facts.visit_file(&file);
+        facts
+    }
+
+    fn status(results: &[CheckResult], name: &str) -> CheckStatus {
+        results.iter().find(|r| r.name == name).unwrap().status
+    }
+
+    #[test]
+    fn test_missing_allocator_and_panic_handler() {
+        let results = evaluate(&facts("#![no_std]\nextern crate alloc;\n"));
+        assert_eq!(status(&results, "panic handler"), CheckStatus::Fail);
+        assert_eq!(status(&results, "global allocator"), CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_configured_contract() {
+        let results = evaluate(&facts(
+            "#![cfg_attr(not(test), no_std)]
+            extern crate alloc;
+            #[cfg(not(test))]
+            #[panic_handler]
+            fn panic(_: &core::panic::PanicInfo) -> ! { loop {} }
+            #[global_allocator]
+            static A: Bump = Bump;",
+        ));
+        assert!(results.iter().all(|r| r.status == CheckStatus::Pass));
+    }
+
+    #[test]
+    fn test_ungated_panic_handler() {
+        let results = evaluate(&facts(
+            "#![no_std]
+            #[panic_handler]
+            fn panic(_: &core::panic::PanicInfo) -> ! { loop {} }",
+        ));
+        assert_eq!(status(&results, "panic handler"), CheckStatus::Warn);
+        assert_eq!(status(&results, "global allocator"), CheckStatus::Pass);
+    }
+
+    #[test]
+    fn test_evaluate_rustc() {
+        let version = |release: &str| RustcVersion {
+            release: release.to_string(),
+            host: "x86_64-unknown-linux-gnu".to_string(),
+            commit_hash: None,
+        };
+        let requirements = [
+            ("1.85".to_string(), "edition 2024 of vault".to_string()),
+            ("1.80".to_string(), "rust-version of vault".to_string()),
+        ];
+
+        let results = evaluate_rustc(&version("1.89.0-dev"), &requirements);
+        assert_eq!(status(&results, "rustc version"), CheckStatus::Pass);
+
+        let results = evaluate_rustc(&version("1.84.1-dev"), &requirements);
+        assert_eq!(results.len(), 1);
+        assert!(results[0].message.contains("edition 2024 of vault"));
+        assert!(results[0]
+            .suggestion
+            .as_ref()
+            .unwrap()
+            .contains(DEFAULT_PLATFORM_TOOLS_VERSION));
+
+        if std::env::var_os("RUSTC_BOOTSTRAP").is_none() {
+            let results = evaluate_rustc(&version("1.89.0"), &requirements);
+            assert_eq!(status(&results, "rustc version"), CheckStatus::Fail);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! CI command implementation
+//!
+//! Runs the checks a contract repository gates merges on, in a fixed order,
+//! and writes the outcome of each step to target/tako/ci-report.json. All
+//! steps run even if an earlier one fails, so one CI run reports every problem.
+
+use colored::Colorize;
+use serde::Serialize;
+use std::fs;
+use std::process::Command;
+use std::time::Instant;
+
+use crate::commands::{build, check, test};
+use crate::config::TakoConfig;
+use crate::error::{Error, Result};
+use crate::logs;
+use crate::schema::SCHEMA_VERSION;
+use crate::style::Styled;
+use crate::toolchain::{find_platform_tools, DEFAULT_PLATFORM_TOOLS_VERSION};
+use crate::util::{ensure_dir, tako_dir};
+
+/// CI steps in the order they run
+pub const STEPS: &[&str] = &["fmt", "clippy", "lint", "build", "test"];
+
+#[derive(Debug, Serialize)]
+struct StepResult {
+    name: &'static str,
+    /// "passed", "failed" or "skipped"
+    status: &'static str,
+    /// Duration in seconds
+    duration: f64,
+    error: Option<String>,
+}
+
+/// CI report document written under target/tako/
+#[derive(Serialize)]
+struct CiReport<'a> {
+    schema_version: u32,
+    passed: bool,
+    arch: &'a str,
+    steps: &'a [StepResult],
+}
+
+/// Run all CI steps
+///
+/// # Arguments
+/// * `arch` - TBPF architecture version to lint, build and verify for
+/// * `skip` - Steps to skip
+pub fn run(arch: &str, skip: &[String]) -> Result<()> {
+    let mut results = Vec::new();
+
+    for &name in STEPS {
+        println!();
+        if skip.iter().any(|s| s == name) {
+            println!("{} {name} (skipped)", "==>".dimmed());
+            results.push(StepResult {
+                name,
+                status: "skipped",
+                duration: 0.0,
+                error: None,
+            });
+            continue;
+        }
+
+        println!("{} {name}", "==>".accent().bold());
+        let start = Instant::now();
+        let result = match name {
+            "fmt" => fmt(),
+            "clippy" => clippy(arch),
+            "lint" => check::run_checks(arch, false, None),
+            "build" => build_and_verify(arch),
+            _ => test::run_tests(None, false, false),
+        };
+        if let Err(e) = &result {
+            eprintln!("{} {e}", "error:".failure().bold());
+        }
+        results.push(StepResult {
+            name,
+            status: if result.is_ok() { "passed" } else { "failed" },
+            duration: start.elapsed().as_secs_f64(),
+            error: result.err().map(|e| e.to_string()),
+        });
+    }
+
+    let failed = results.iter().filter(|r| r.status == "failed").count();
+
+    println!();
+    for result in &results {
+        let status = match result.status {
+            "passed" => "ok".success().bold(),
+            "failed" => "failed".failure().bold(),
+            _ => "skipped".dimmed(),
+        };
+        println!(
+            "  {:<8} {:<8} {:>8.1}s",
+            result.name, status, result.duration
+        );
+    }
+
+    let report = CiReport {
+        schema_version: SCHEMA_VERSION,
+        passed: failed == 0,
+        arch,
+        steps: &results,
+    };
+    ensure_dir(tako_dir())?;
+    let path = tako_dir().join("ci-report.json");
+    let content = serde_json::to_string_pretty(&report)
+        .map_err(|e| Error::Other(format!("Failed to serialize CI report: {e}")))?;
+    fs::write(&path, content)?;
+    println!();
+    println!("Report: {}", path.display());
+
+    if failed > 0 {
+        return Err(Error::BuildFailed(format!(
+            "{failed} of {} CI step(s) failed",
+            results.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Check formatting with rustfmt
+fn fmt() -> Result<()> {
+    let status = logs::status(Command::new("cargo").args(["fmt", "--all", "--check"]))
+        .map_err(|e| Error::spawn("cargo fmt", e, Error::Other))?;
+    if !status.success() {
+        return Err(Error::BuildFailed(
+            "code is not formatted (run `cargo fmt`)".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Run clippy for the TBPF target with warnings denied
+fn clippy(arch: &str) -> Result<()> {
+    let platform_tools = find_platform_tools(Some(DEFAULT_PLATFORM_TOOLS_VERSION));
+    let (cargo_bin, _) = build::get_cargo_and_rustc(&platform_tools);
+
+    let mut cmd = Command::new(&cargo_bin);
+    cmd.args(["clippy", "--target"])
+        .arg(build::get_target_triple(arch))
+        .args(["-Zbuild-std=core,alloc", "--", "-D", "warnings"])
+        .envs(build::toolchain_env(&platform_tools));
+
+    let status =
+        logs::status(&mut cmd).map_err(|e| Error::spawn("cargo clippy", e, Error::Other))?;
+    if !status.success() {
+        return Err(Error::BuildFailed("clippy reported problems".to_string()));
+    }
+    Ok(())
+}
+
+/// Build a release artifact and verify it
+fn build_and_verify(arch: &str) -> Result<()> {
+    let config = TakoConfig::load_or_default()?;
+    let optimization = build::OptimizationSettings::resolve(&config.build, None, None, None)?;
+    let output = build::build_contract(true, arch, None, &optimization, false)?;
+    build::verify_contract(&output, arch, false, true)
+}
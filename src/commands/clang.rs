@@ -0,0 +1,227 @@
+//! C and C++ contracts built with the platform-tools clang
+//!
+//! A project with `src/contract.c` (or `cargo tako build --lang c`) is built
+//! without cargo: every C and C++ file under src/ is compiled for TBPF by
+//! clang and the objects are linked into a shared object by lld. The
+//! artifact then goes through the same manifest, verify and info steps as a
+//! Rust build, and lands where a Rust build would put it.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+use crate::commands::build::{self, OptimizationSettings};
+use crate::config::TakoConfig;
+use crate::error::{Error, Result};
+use crate::logs;
+use crate::template::to_snake_case;
+use crate::toolchain::{
+    self, find_platform_tools, touch_last_used, DEFAULT_PLATFORM_TOOLS_VERSION,
+};
+use crate::util::{ensure_dir, tako_dir};
+
+/// Source file whose presence marks a C contract
+pub const C_ENTRY: &str = "src/contract.c";
+
+/// Header directory added to the include path when present
+const INCLUDE_DIR: &str = "include";
+
+/// Flags for every translation unit: position-independent, freestanding
+/// code with debug info for the source map (stripped from release artifacts)
+const COMMON_FLAGS: &[&str] = &[
+    "-fPIC",
+    "-ffreestanding",
+    "-fno-builtin",
+    "-nostdlib",
+    "-g",
+    "-Wall",
+    "-Werror=implicit-function-declaration",
+];
+
+/// Extra flags for C++: no runtime support for exceptions or RTTI on TBPF
+const CXX_FLAGS: &[&str] = &["-std=c++17", "-fno-exceptions", "-fno-rtti"];
+
+/// Whether the project in the current directory is a C contract
+pub fn is_c_project() -> bool {
+    Path::new(C_ENTRY).exists()
+}
+
+/// Build a C/C++ contract
+///
+/// # Arguments
+/// * `release` - Build with the release optimization level
+/// * `arch` - Architecture version (v0, v1, v2, v3, v4)
+/// * `optimization` - Release profile settings; only the opt-level applies
+///
+/// # Returns
+/// Path to the built contract binary (.so file)
+pub fn build_c_contract(
+    release: bool,
+    arch: &str,
+    optimization: &OptimizationSettings,
+) -> Result<PathBuf> {
+    let target = build::get_target_triple(arch);
+    let profile = if release { "release" } else { "debug" };
+
+    println!("  Lang: C/C++");
+    println!("  Arch: {arch}");
+    println!("  Target: {target}");
+    println!("  Profile: {profile}");
+
+    // The system clang has no TBPF backend, so the bundled one is required
+    let tools = find_platform_tools(Some(DEFAULT_PLATFORM_TOOLS_VERSION));
+    // ld.lld selects the GNU flavor that plain `lld` needs -flavor for
+    let (clang, lld) = match &tools {
+        Some(tools) => (tools.clang(), tools.llvm_bin.join("ld.lld")),
+        None => (PathBuf::new(), PathBuf::new()),
+    };
+    let Some(tools) = tools.filter(|_| clang.exists() && lld.exists()) else {
+        return Err(Error::ToolchainMissing(format!(
+            "C contracts are compiled with the clang and lld of platform-tools.\nInstall platform-tools {DEFAULT_PLATFORM_TOOLS_VERSION}:\n\n{}",
+            toolchain::install_instructions(DEFAULT_PLATFORM_TOOLS_VERSION)
+        )));
+    };
+    println!("  Toolchain: {} ({})", tools.display_path(), tools.version);
+    touch_last_used(&tools);
+
+    let sources = find_sources(Path::new("src"));
+    if sources.is_empty() {
+        return Err(Error::BuildFailed(
+            "no C or C++ sources (.c, .cc, .cpp, .cxx) found in src/".to_string(),
+        ));
+    }
+
+    let out_dir = Path::new("target").join(&target).join(profile);
+    let obj_dir = out_dir.join("obj");
+    ensure_dir(&obj_dir)?;
+
+    let opt_level = if release {
+        format!("-O{}", optimization.opt_level)
+    } else {
+        "-O0".to_string()
+    };
+    let mut objects = Vec::new();
+    for source in &sources {
+        let object = obj_dir.join(object_name(source));
+        println!("  Compiling {}", source.display());
+        let mut cmd = Command::new(&clang);
+        cmd.args(compile_flags(arch, is_cxx(source)))
+            .arg(&opt_level)
+            .arg("-c")
+            .arg(source)
+            .arg("-o")
+            .arg(&object);
+        if Path::new(INCLUDE_DIR).is_dir() {
+            cmd.arg("-I").arg(INCLUDE_DIR);
+        }
+        run(&mut cmd, "clang")?;
+        objects.push(object);
+    }
+
+    let binary_path = out_dir.join(format!("{}.so", project_name()?));
+    let maps_dir = env::current_dir()?.join(tako_dir()).join("maps");
+    ensure_dir(&maps_dir)?;
+    let link_map = maps_dir.join(format!("{target}-{profile}.map"));
+    let entry = TakoConfig::load_or_default()?.contract.entry;
+
+    let mut cmd = Command::new(&lld);
+    cmd.args(["-z", "notext", "-shared", "--Bdynamic"])
+        .arg(format!("--entry={entry}"))
+        .arg(format!("--Map={}", link_map.display()))
+        .arg("-o")
+        .arg(&binary_path)
+        .args(&objects);
+    println!("  Linking {}", binary_path.display());
+    run(&mut cmd, "ld.lld")?;
+
+    build::record_artifact(&binary_path, release, arch, &target, Some(link_map), None)?;
+
+    if TakoConfig::load_or_default()?.build.target_deploy {
+        let deployed = build::place_in_target_deploy(&binary_path)?;
+        println!("  Deploy copy: {}", deployed.display());
+    }
+
+    println!("✓ Build successful");
+
+    Ok(binary_path)
+}
+
+/// Flags selecting the TBPF target and CPU for a translation unit
+fn compile_flags(arch: &str, cxx: bool) -> Vec<String> {
+    // v0 is the generic CPU; later versions are CPUs of the same name
+    let cpu = if arch == "v0" { "generic" } else { arch };
+    let mut flags = vec!["--target=tbpf".to_string(), format!("-mcpu={cpu}")];
+    flags.extend(COMMON_FLAGS.iter().map(|f| f.to_string()));
+    if cxx {
+        flags.extend(CXX_FLAGS.iter().map(|f| f.to_string()));
+    } else {
+        flags.push("-std=c17".to_string());
+    }
+    flags
+}
+
+/// C and C++ sources under `dir`, sorted by path
+fn find_sources(dir: &Path) -> Vec<PathBuf> {
+    WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .map(|e| e.into_path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "c") || is_cxx(p))
+        .collect()
+}
+
+fn is_cxx(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "cc" || ext == "cpp" || ext == "cxx")
+}
+
+/// Object file name for a source, unique across subdirectories of src/
+fn object_name(source: &Path) -> String {
+    let relative = source.strip_prefix("src").unwrap_or(source);
+    format!("{}.o", relative.to_string_lossy().replace(['/', '\\'], "_"))
+}
+
+/// Artifact name: the project directory name in snake_case
+fn project_name() -> Result<String> {
+    let dir = env::current_dir()?;
+    Ok(dir
+        .file_name()
+        .map(|name| to_snake_case(&name.to_string_lossy()))
+        .unwrap_or_else(|| "contract".to_string()))
+}
+
+/// Run clang or lld, passing their warnings through
+fn run(cmd: &mut Command, program: &str) -> Result<()> {
+    let output = logs::output(cmd).map_err(|e| Error::spawn(program, e, Error::BuildFailed))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(Error::BuildFailed(format!("{program} failed:\n{stderr}")));
+    }
+    eprint!("{stderr}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_flags() {
+        let flags = compile_flags("v0", false);
+        assert_eq!(flags[..2], ["--target=tbpf", "-mcpu=generic"]);
+        assert!(flags.contains(&"-std=c17".to_string()));
+
+        let flags = compile_flags("v3", true);
+        assert_eq!(flags[1], "-mcpu=v3");
+        assert!(flags.contains(&"-fno-exceptions".to_string()));
+
+        assert!(is_cxx(Path::new("src/math.cpp")));
+        assert!(!is_cxx(Path::new("src/contract.c")));
+        assert_eq!(
+            object_name(Path::new("src/util/math.cpp")),
+            "util_math.cpp.o"
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Debug command implementation
+//!
+//! Builds the native test binaries with debug info, finds the one containing
+//! the requested test and runs it under gdb or lldb. The Rust wrappers
+//! (`rust-gdb`, `rust-lldb`) are preferred so standard library types print
+//! readably, and a command script adds breakpoints on the test and on
+//! contract calls made through the test support context, plus a `tako-addr`
+//! command printing a 32-byte address as hex.
+
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::{Error, Result};
+use crate::interrupt;
+use crate::logs;
+use crate::util::{ensure_dir, tako_dir};
+
+/// Debuggers in order of preference, with the flavor of their command scripts
+const DEBUGGERS: &[(&str, Flavor)] = &[
+    ("rust-gdb", Flavor::Gdb),
+    ("rust-lldb", Flavor::Lldb),
+    ("gdb", Flavor::Gdb),
+    ("lldb", Flavor::Lldb),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Flavor {
+    Gdb,
+    Lldb,
+}
+
+/// A cargo message; only test executables are of interest
+#[derive(Deserialize)]
+struct ArtifactMessage {
+    reason: String,
+    target: Option<ArtifactTarget>,
+    #[serde(default)]
+    executable: Option<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct ArtifactTarget {
+    name: String,
+}
+
+/// Debug a single test under gdb or lldb
+///
+/// # Arguments
+/// * `test` - Test name or unique part of it (e.g. `test_transfer`)
+/// * `debugger` - gdb, lldb, or a debugger executable (defaults to the first one found)
+/// * `release` - Debug the release build of the tests (still with debug info)
+pub fn debug(test: &str, debugger: Option<&str>, release: bool) -> Result<()> {
+    let (debugger, flavor) = find_debugger(debugger)?;
+    let binaries = build_test_binaries(release)?;
+
+    let mut matches = Vec::new();
+    for (crate_name, executable) in &binaries {
+        for name in list_tests(executable)? {
+            if name == test || name.ends_with(&format!("::{test}")) || name.contains(test) {
+                matches.push((crate_name.clone(), executable.clone(), name));
+            }
+        }
+    }
+    // An exact match wins over tests that merely contain the name
+    if let Some(exact) = matches
+        .iter()
+        .position(|(_, _, name)| name == test || name.ends_with(&format!("::{test}")))
+    {
+        let exact = matches.swap_remove(exact);
+        matches = vec![exact];
+    }
+
+    let (crate_name, executable, name) = match matches.len() {
+        0 => return Err(Error::TestFailed(format!("No test matching '{test}'"))),
+        1 => matches.remove(0),
+        _ => {
+            let names: Vec<&str> = matches.iter().map(|(_, _, n)| n.as_str()).collect();
+            return Err(Error::TestFailed(format!(
+                "'{test}' matches several tests, use one of:\n  {}",
+                names.join("\n  ")
+            )));
+        }
+    };
+
+    let script = write_script(flavor, &crate_name, &name)?;
+    println!("  Test: {name}");
+    println!("  Binary: {}", executable.display());
+    println!("  Debugger: {}", debugger.display());
+    println!("  Breakpoints: {crate_name}::{name}, contract calls (TestContext::call_as)");
+    println!("  Print an address with: tako-addr <expr>");
+    println!();
+
+    let mut cmd = Command::new(&debugger);
+    let test_args = [name.as_str(), "--exact", "--nocapture", "--test-threads=1"];
+    match flavor {
+        Flavor::Gdb => {
+            cmd.arg("-q")
+                .arg("-x")
+                .arg(&script)
+                .arg("--args")
+                .arg(&executable)
+                .args(test_args);
+        }
+        Flavor::Lldb => {
+            cmd.arg("-s")
+                .arg(&script)
+                .arg("--")
+                .arg(&executable)
+                .args(test_args);
+        }
+    }
+
+    interrupt::set_interactive(true);
+    let status = cmd.status();
+    interrupt::set_interactive(false);
+    status.map_err(|e| Error::spawn(&debugger.to_string_lossy(), e, Error::Other))?;
+    Ok(())
+}
+
+/// Resolve the requested debugger, or the first available one
+fn find_debugger(requested: Option<&str>) -> Result<(PathBuf, Flavor)> {
+    let candidates: Vec<(&str, Flavor)> = match requested {
+        None => DEBUGGERS.to_vec(),
+        Some("gdb") => vec![("rust-gdb", Flavor::Gdb), ("gdb", Flavor::Gdb)],
+        Some("lldb") => vec![("rust-lldb", Flavor::Lldb), ("lldb", Flavor::Lldb)],
+        Some(program) => {
+            let flavor = if program.contains("lldb") {
+                Flavor::Lldb
+            } else {
+                Flavor::Gdb
+            };
+            vec![(program, flavor)]
+        }
+    };
+
+    candidates
+        .iter()
+        .filter(|(program, _)| {
+            // The rust-* wrappers only add pretty-printers to an installed debugger
+            program
+                .strip_prefix("rust-")
+                .is_none_or(|backend| which::which(backend).is_ok())
+        })
+        .find_map(|(program, flavor)| which::which(program).ok().map(|path| (path, *flavor)))
+        .ok_or_else(|| {
+            Error::ToolchainMissing(
+                "no debugger found (install gdb or lldb, or pass --debugger)".to_string(),
+            )
+        })
+}
+
+/// Build the test binaries with debug info, returning (crate name, executable)
+fn build_test_binaries(release: bool) -> Result<Vec<(String, PathBuf)>> {
+    let mut cmd = Command::new("cargo");
+    cmd.args(["test", "--no-run", "--message-format", "json"])
+        .env("CARGO_PROFILE_DEV_DEBUG", "true")
+        .env("CARGO_PROFILE_TEST_DEBUG", "true")
+        .env("CARGO_PROFILE_RELEASE_DEBUG", "true")
+        .env("CARGO_PROFILE_BENCH_DEBUG", "true");
+    if release {
+        cmd.arg("--release");
+    }
+
+    let output =
+        interrupt::output(&mut cmd).map_err(|e| Error::spawn("cargo", e, Error::TestFailed))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::BuildFailed(format!(
+            "Failed to build tests:\n{stderr}"
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<ArtifactMessage>(line).ok())
+        .filter(|m| m.reason == "compiler-artifact")
+        .filter_map(|m| Some((m.target?.name.replace('-', "_"), m.executable?)))
+        .collect())
+}
+
+/// Names of the tests in a test binary
+fn list_tests(executable: &Path) -> Result<Vec<String>> {
+    let output = logs::output(Command::new(executable).args(["--list", "--format", "terse"]))
+        .map_err(|e| {
+            Error::Other(format!(
+                "Failed to list tests of {}: {e}",
+                executable.display()
+            ))
+        })?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_suffix(": test"))
+        .map(String::from)
+        .collect())
+}
+
+/// Write the debugger command script to target/tako/debug/
+fn write_script(flavor: Flavor, crate_name: &str, test: &str) -> Result<PathBuf> {
+    let dir = tako_dir().join("debug");
+    ensure_dir(&dir)?;
+    let path = dir.join(match flavor {
+        Flavor::Gdb => "commands.gdb",
+        Flavor::Lldb => "commands.lldb",
+    });
+    fs::write(&path, script(flavor, crate_name, test))?;
+    Ok(path)
+}
+
+fn script(flavor: Flavor, crate_name: &str, test: &str) -> String {
+    let test_fn = format!("{crate_name}::{test}");
+    match flavor {
+        Flavor::Gdb => format!(
+            "set breakpoint pending on\n\
+             break {test_fn}\n\
+             rbreak ^{crate_name}::test_support::TestContext::call_as\n\
+             define tako-addr\n  x/32xb &($arg0)\nend\n\
+             document tako-addr\nPrint a 32-byte address as hex bytes.\nend\n"
+        ),
+        Flavor::Lldb => format!(
+            "breakpoint set --name {test_fn}\n\
+             breakpoint set --func-regex ^{crate_name}::test_support::TestContext::call_as\n\
+             command alias tako-addr memory read --size 1 --format x --count 32\n"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script() {
+        let gdb = script(Flavor::Gdb, "my_token", "tests::test_mint");
+        assert!(gdb.contains("break my_token::tests::test_mint\n"));
+        assert!(gdb.contains("rbreak ^my_token::test_support::TestContext::call_as\n"));
+
+        let lldb = script(Flavor::Lldb, "my_token", "tests::test_mint");
+        assert!(lldb.contains("breakpoint set --name my_token::tests::test_mint\n"));
+    }
+}
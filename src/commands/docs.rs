@@ -0,0 +1,497 @@
+//! Docs command implementation
+//!
+//! Builds rustdoc for the contract crate and an instruction reference listing
+//! the public methods of each contract type (the structs holding storage
+//! containers), with their arguments, return types and doc comments, followed
+//! by the catalog of custom error codes. Everything is written to
+//! target/tako/docs/: the reference as index.html (and instructions.md with
+//! `--markdown`), the error catalog as errors.json, rustdoc under api/.
+
+use quote::ToTokens;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use syn::visit::Visit;
+use walkdir::WalkDir;
+
+use crate::commands::storage;
+use crate::error::{Error, Result};
+use crate::error_codes::{self, ErrorCode};
+use crate::logs;
+use crate::style::Styled;
+use crate::util::{ensure_dir, tako_dir};
+
+#[derive(Debug, PartialEq)]
+pub struct ContractDoc {
+    pub name: String,
+    pub doc: String,
+    pub methods: Vec<MethodDoc>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MethodDoc {
+    pub name: String,
+    pub kind: MethodKind,
+    pub doc: String,
+    /// (name, type) of each argument after the receiver
+    pub args: Vec<(String, String)>,
+    pub returns: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MethodKind {
+    /// No receiver (`new`)
+    Constructor,
+    /// `&self`
+    View,
+    /// `&mut self`
+    Call,
+}
+
+impl MethodKind {
+    fn label(self) -> &'static str {
+        match self {
+            MethodKind::Constructor => "constructor",
+            MethodKind::View => "view",
+            MethodKind::Call => "call",
+        }
+    }
+}
+
+/// Build the contract documentation
+///
+/// # Arguments
+/// * `open` - Open the instruction reference in a browser afterwards
+/// * `markdown` - Also write the reference as markdown, e.g. for a docs site
+pub fn docs(open: bool, markdown: bool) -> Result<()> {
+    let out_dir = tako_dir().join("docs");
+    ensure_dir(&out_dir)?;
+
+    let contracts = extract_contracts(Path::new("src"))?;
+    if contracts.is_empty() {
+        println!(
+            "  {} no contract types (structs with storage containers) found in src/",
+            "warning:".warning()
+        );
+    }
+
+    let status = logs::status(Command::new("cargo").args(["doc", "--no-deps"]))
+        .map_err(|e| Error::spawn("cargo doc", e, Error::BuildFailed))?;
+    if !status.success() {
+        return Err(Error::BuildFailed("cargo doc failed".to_string()));
+    }
+    let api_dir = out_dir.join("api");
+    if api_dir.exists() {
+        fs::remove_dir_all(&api_dir)?;
+    }
+    copy_dir(Path::new("target/doc"), &api_dir)?;
+
+    let errors = error_codes::extract_catalog(Path::new("src"))?;
+    let catalog = out_dir.join("errors.json");
+    let content = serde_json::to_string_pretty(&errors)
+        .map_err(|e| Error::Other(format!("Failed to serialize error catalog: {e}")))?;
+    fs::write(&catalog, content)?;
+
+    let crate_name = crate_name()?;
+    let index = out_dir.join("index.html");
+    fs::write(&index, render_html(&crate_name, &contracts, &errors))?;
+    println!("  Instruction reference: {}", index.display());
+    println!(
+        "  Error catalog: {} ({} codes)",
+        catalog.display(),
+        errors.len()
+    );
+    println!(
+        "  API docs: {}",
+        api_dir.join(&crate_name).join("index.html").display()
+    );
+    if markdown {
+        let path = out_dir.join("instructions.md");
+        fs::write(&path, render_markdown(&crate_name, &contracts, &errors))?;
+        println!("  Markdown: {}", path.display());
+    }
+
+    println!(
+        "{} Documentation written to {}",
+        "✓".success(),
+        out_dir.display()
+    );
+
+    if open {
+        open_in_browser(&index)?;
+    }
+    Ok(())
+}
+
+/// Public methods of the contract types defined under `src_dir`
+pub fn extract_contracts(src_dir: &Path) -> Result<Vec<ContractDoc>> {
+    let layout = storage::extract_layout(src_dir)?;
+    let mut collector = ContractCollector {
+        contracts: layout
+            .structs
+            .iter()
+            .map(|s| ContractDoc {
+                name: s.name.clone(),
+                doc: String::new(),
+                methods: Vec::new(),
+            })
+            .collect(),
+    };
+
+    for entry in WalkDir::new(src_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
+    {
+        let content = fs::read_to_string(entry.path())?;
+        let file = syn::parse_file(&content).map_err(|e| {
+            Error::Other(format!("Failed to parse {}: {e}", entry.path().display()))
+        })?;
+        collector.visit_file(&file);
+    }
+    Ok(collector.contracts)
+}
+
+struct ContractCollector {
+    contracts: Vec<ContractDoc>,
+}
+
+impl ContractCollector {
+    fn contract(&mut self, name: &str) -> Option<&mut ContractDoc> {
+        self.contracts.iter_mut().find(|c| c.name == name)
+    }
+}
+
+impl<'ast> Visit<'ast> for ContractCollector {
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        let doc = doc_comment(&item.attrs);
+        if let Some(contract) = self.contract(&item.ident.to_string()) {
+            contract.doc = doc;
+        }
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        // Trait impls are not part of the contract's own interface
+        if item.trait_.is_some() {
+            return;
+        }
+        let syn::Type::Path(p) = item.self_ty.as_ref() else {
+            return;
+        };
+        let Some(name) = p.path.segments.last().map(|s| s.ident.to_string()) else {
+            return;
+        };
+        let Some(contract) = self.contract(&name) else {
+            return;
+        };
+
+        for impl_item in &item.items {
+            let syn::ImplItem::Fn(f) = impl_item else {
+                continue;
+            };
+            if !matches!(f.vis, syn::Visibility::Public(_)) {
+                continue;
+            }
+            let mut kind = MethodKind::Constructor;
+            let mut args = Vec::new();
+            for input in &f.sig.inputs {
+                match input {
+                    syn::FnArg::Receiver(r) => {
+                        kind = if r.mutability.is_some() {
+                            MethodKind::Call
+                        } else {
+                            MethodKind::View
+                        };
+                    }
+                    syn::FnArg::Typed(arg) => {
+                        args.push((tokens(&*arg.pat), tokens(&*arg.ty)));
+                    }
+                }
+            }
+            let returns = match &f.sig.output {
+                syn::ReturnType::Default => None,
+                syn::ReturnType::Type(_, ty) => Some(tokens(&**ty)),
+            };
+            contract.methods.push(MethodDoc {
+                name: f.sig.ident.to_string(),
+                kind,
+                doc: doc_comment(&f.attrs),
+                args,
+                returns,
+            });
+        }
+    }
+}
+
+/// Tokens of a type or pattern without the spaces `quote` puts between them
+fn tokens(node: &dyn ToTokens) -> String {
+    node.to_token_stream()
+        .to_string()
+        .replace(" < ", "<")
+        .replace(" >", ">")
+        .replace("& ", "&")
+        .replace(" ,", ",")
+        .replace(" :: ", "::")
+        .replace("( ", "(")
+        .replace(" )", ")")
+        .replace(" ;", ";")
+        .replace("[ ", "[")
+        .replace(" ]", "]")
+}
+
+/// Joined `///` lines of an item
+fn doc_comment(attrs: &[syn::Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            syn::Meta::NameValue(nv) => match &nv.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) => Some(s.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+fn signature(method: &MethodDoc) -> String {
+    let args: Vec<String> = method
+        .args
+        .iter()
+        .map(|(name, ty)| format!("{name}: {ty}"))
+        .collect();
+    let mut signature = format!("{}({})", method.name, args.join(", "));
+    if let Some(returns) = &method.returns {
+        signature.push_str(&format!(" -> {returns}"));
+    }
+    signature
+}
+
+/// Instruction reference as markdown
+pub fn render_markdown(
+    crate_name: &str,
+    contracts: &[ContractDoc],
+    errors: &[ErrorCode],
+) -> String {
+    let mut out = format!("# {crate_name} instruction reference\n");
+    for contract in contracts {
+        out.push_str(&format!("\n## {}\n\n", contract.name));
+        if !contract.doc.is_empty() {
+            out.push_str(&format!("{}\n\n", contract.doc));
+        }
+        out.push_str("| Method | Kind | Arguments | Returns |\n");
+        out.push_str("|--------|------|-----------|---------|\n");
+        for method in &contract.methods {
+            let args: Vec<String> = method
+                .args
+                .iter()
+                .map(|(name, ty)| format!("`{name}: {ty}`"))
+                .collect();
+            out.push_str(&format!(
+                "| [`{}`](#{}) | {} | {} | {} |\n",
+                method.name,
+                anchor(&contract.name, &method.name),
+                method.kind.label(),
+                args.join(", "),
+                method
+                    .returns
+                    .as_ref()
+                    .map(|r| format!("`{r}`"))
+                    .unwrap_or_default()
+            ));
+        }
+        for method in &contract.methods {
+            out.push_str(&format!(
+                "\n### {}\n\n```rust\n{}\n```\n",
+                method.name,
+                signature(method)
+            ));
+            if !method.doc.is_empty() {
+                out.push_str(&format!("\n{}\n", method.doc));
+            }
+        }
+    }
+    if !errors.is_empty() {
+        out.push_str(
+            "\n## Errors\n\n| Code | Error | Description |\n|------|-------|-------------|\n",
+        );
+        for error in errors {
+            out.push_str(&format!(
+                "| {} | `{}` | {} |\n",
+                error.code,
+                error.path(),
+                error.doc
+            ));
+        }
+    }
+    out
+}
+
+/// Instruction reference as a standalone HTML page
+fn render_html(crate_name: &str, contracts: &[ContractDoc], errors: &[ErrorCode]) -> String {
+    let mut body = format!(
+        "<h1>{} instruction reference</h1>\n<p><a href=\"api/{}/index.html\">API documentation</a></p>\n",
+        escape(crate_name),
+        escape(crate_name)
+    );
+    for contract in contracts {
+        body.push_str(&format!("<h2>{}</h2>\n", escape(&contract.name)));
+        if !contract.doc.is_empty() {
+            body.push_str(&format!("<p>{}</p>\n", escape(&contract.doc)));
+        }
+        for method in &contract.methods {
+            body.push_str(&format!(
+                "<h3 id=\"{}\">{} <small>{}</small></h3>\n<pre><code>{}</code></pre>\n",
+                anchor(&contract.name, &method.name),
+                escape(&method.name),
+                method.kind.label(),
+                escape(&signature(method))
+            ));
+            if !method.doc.is_empty() {
+                body.push_str(&format!("<p>{}</p>\n", escape(&method.doc)));
+            }
+        }
+    }
+    if !errors.is_empty() {
+        body.push_str(
+            "<h2>Errors</h2>\n<table>\n<tr><th>Code</th><th>Error</th><th>Description</th></tr>\n",
+        );
+        for error in errors {
+            body.push_str(&format!(
+                "<tr><td>{}</td><td><code>{}</code></td><td>{}</td></tr>\n",
+                error.code,
+                escape(&error.path()),
+                escape(&error.doc)
+            ));
+        }
+        body.push_str("</table>\n");
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{} instruction reference</title>\n\
+         <style>body {{ font-family: sans-serif; max-width: 56em; margin: 2em auto; }} \
+         pre {{ background: #f4f4f4; padding: 0.5em; }} small {{ color: #777; }}</style>\n\
+         </head>\n<body>\n{body}</body>\n</html>\n",
+        escape(crate_name)
+    )
+}
+
+fn anchor(contract: &str, method: &str) -> String {
+    format!("{}-{}", contract, method).to_lowercase()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Name of the crate as rustdoc spells it
+fn crate_name() -> Result<String> {
+    let manifest = fs::read_to_string("Cargo.toml")?;
+    let doc: toml_edit::DocumentMut = manifest
+        .parse()
+        .map_err(|e| Error::Config(format!("Cargo.toml: {e}")))?;
+    let name = doc
+        .get("lib")
+        .and_then(|lib| lib.get("name"))
+        .or_else(|| doc.get("package").and_then(|p| p.get("name")))
+        .and_then(|n| n.as_str())
+        .ok_or_else(|| Error::Config("Cargo.toml has no package name".to_string()))?;
+    Ok(name.replace('-', "_"))
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    for entry in WalkDir::new(from).into_iter().filter_map(|e| e.ok()) {
+        let Ok(relative) = entry.path().strip_prefix(from) else {
+            continue;
+        };
+        let target = to.join(relative);
+        if entry.file_type().is_dir() {
+            ensure_dir(&target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn open_in_browser(path: &Path) -> Result<()> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    Command::new(opener)
+        .arg(path)
+        .status()
+        .map_err(|e| Error::spawn(opener, e, Error::Other))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_contracts() {
+        let dir = std::env::temp_dir().join(format!("tako-docs-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("lib.rs"),
+            r#"
+/// A counter
+pub struct Counter {
+    count: Value<u64>,
+}
+
+impl Counter {
+    /// Create the counter
+    pub fn new() -> Self { todo!() }
+    /// Current count
+    pub fn get(&self) -> u64 { 0 }
+    /// Add to the count
+    pub fn add(&mut self, by: u64, caller: &Address) -> StorageResult<()> { Ok(()) }
+    fn helper(&self) {}
+}
+"#,
+        )
+        .unwrap();
+
+        let contracts = extract_contracts(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(contracts.len(), 1);
+        let counter = &contracts[0];
+        assert_eq!(counter.doc, "A counter");
+        let kinds: Vec<MethodKind> = counter.methods.iter().map(|m| m.kind).collect();
+        assert_eq!(
+            kinds,
+            [MethodKind::Constructor, MethodKind::View, MethodKind::Call]
+        );
+        assert_eq!(
+            signature(&counter.methods[2]),
+            "add(by: u64, caller: &Address) -> StorageResult<()>"
+        );
+
+        let errors = [ErrorCode {
+            code: 6000,
+            enum_name: "CounterError".to_string(),
+            variant: "Overflow".to_string(),
+            doc: "Count would overflow".to_string(),
+        }];
+        let markdown = render_markdown("counter", &contracts, &errors);
+        assert!(
+            markdown.contains("| [`add`](#counter-add) | call | `by: u64`, `caller: &Address` |")
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Env command implementation
+//!
+//! Prints the environment `cargo tako build` runs cargo with, as shell
+//! statements, so builds can be reproduced by hand and other tools (editors,
+//! debuggers) can use the same toolchain:
+//!
+//! ```text
+//! eval "$(cargo tako env)"
+//! cargo build --release
+//! ```
+
+use std::env;
+use std::path::PathBuf;
+
+use crate::commands::build::{self, OptimizationSettings};
+use crate::config::TakoConfig;
+use crate::error::{Error, Result};
+use crate::toolchain::{find_llvm_tool, find_platform_tools, DEFAULT_PLATFORM_TOOLS_VERSION};
+
+/// Print the build environment as shell statements
+///
+/// # Arguments
+/// * `arch` - TBPF architecture version to build for
+/// * `shell` - Output syntax: bash (also zsh/sh), fish or powershell
+pub fn print_env(arch: &str, shell: &str) -> Result<()> {
+    let config = TakoConfig::load_or_default()?;
+    let optimization = OptimizationSettings::resolve(&config.build, None, None, None)?;
+    let target = build::get_target_triple(arch);
+    let platform_tools = find_platform_tools(Some(DEFAULT_PLATFORM_TOOLS_VERSION));
+
+    // Release builds only carry debug info when it can be stripped afterwards,
+    // as in `cargo tako build`
+    let objcopy = find_llvm_tool("llvm-objcopy");
+
+    println!("# cargo-tako build environment ({arch})");
+    println!("# Build with: cargo build --release");
+    if let Some(objcopy) = &objcopy {
+        println!(
+            "# Strip before deploying: {} --strip-all target/{target}/release/<contract>.so",
+            objcopy.display()
+        );
+    }
+
+    match &platform_tools {
+        Some(tools) => {
+            println!("# Toolchain: {} ({})", tools.display_path(), tools.version);
+            if let Some(sysroot) = tools.rust_bin.parent() {
+                println!("# Sysroot: {}", sysroot.display());
+            }
+            let paths = [&tools.rust_bin, &tools.llvm_bin]
+                .into_iter()
+                .filter(|path| path.exists())
+                .cloned()
+                .collect::<Vec<_>>();
+            if !paths.is_empty() {
+                println!("{}", prepend_path(shell, &paths)?);
+            }
+        }
+        None => {
+            println!("# Toolchain: system (TOS platform-tools not found)");
+        }
+    }
+
+    let mut vars: Vec<(&str, String)> = build::toolchain_env(&platform_tools)
+        .into_iter()
+        .map(|(name, path)| (name, path.to_string_lossy().to_string()))
+        .collect();
+    vars.push(("CARGO_BUILD_TARGET", target));
+    vars.push(("CARGO_UNSTABLE_BUILD_STD", "core,alloc".to_string()));
+    vars.extend(optimization.env());
+    if objcopy.is_some() {
+        vars.push(("CARGO_PROFILE_RELEASE_DEBUG", "true".to_string()));
+    }
+    for name in ["RUSTFLAGS", "CARGO_ENCODED_RUSTFLAGS"] {
+        if let Ok(value) = env::var(name) {
+            vars.push((name, value));
+        }
+    }
+
+    for (name, value) in vars {
+        println!("{}", export(shell, name, &value)?);
+    }
+    Ok(())
+}
+
+/// A statement setting an environment variable
+fn export(shell: &str, name: &str, value: &str) -> Result<String> {
+    match shell {
+        "bash" => Ok(format!("export {name}={}", quote(shell, value))),
+        "fish" => Ok(format!("set -gx {name} {}", quote(shell, value))),
+        "powershell" => Ok(format!("$env:{name} = {}", quote(shell, value))),
+        _ => Err(Error::Other(format!("Unsupported shell: {shell}"))),
+    }
+}
+
+/// A statement prepending directories to PATH
+fn prepend_path(shell: &str, paths: &[PathBuf]) -> Result<String> {
+    let quoted = |separator: &str| {
+        let joined = paths
+            .iter()
+            .map(|p| p.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(separator);
+        quote(shell, &joined)
+    };
+    match shell {
+        "bash" => Ok(format!("export PATH={}:\"$PATH\"", quoted(":"))),
+        "fish" => {
+            let list = paths
+                .iter()
+                .map(|p| quote(shell, &p.to_string_lossy()))
+                .collect::<Vec<_>>()
+                .join(" ");
+            Ok(format!("set -gx PATH {list} $PATH"))
+        }
+        "powershell" => Ok(format!(
+            "$env:PATH = {} + [IO.Path]::PathSeparator + $env:PATH",
+            quoted(if cfg!(windows) { ";" } else { ":" })
+        )),
+        _ => Err(Error::Other(format!("Unsupported shell: {shell}"))),
+    }
+}
+
+/// Single-quote a value for the shell
+fn quote(shell: &str, value: &str) -> String {
+    match shell {
+        "fish" => format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'")),
+        "powershell" => format!("'{}'", value.replace('\'', "''")),
+        _ => format!("'{}'", value.replace('\'', "'\\''")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export() {
+        let value = "-C link-arg=it's";
+        assert_eq!(
+            export("bash", "RUSTFLAGS", value).unwrap(),
+            r"export RUSTFLAGS='-C link-arg=it'\''s'"
+        );
+        assert_eq!(
+            export("fish", "RUSTFLAGS", value).unwrap(),
+            r"set -gx RUSTFLAGS '-C link-arg=it\'s'"
+        );
+        assert_eq!(
+            export("powershell", "RUSTFLAGS", value).unwrap(),
+            "$env:RUSTFLAGS = '-C link-arg=it''s'"
+        );
+
+        let paths = [
+            PathBuf::from("/opt/rust/bin"),
+            PathBuf::from("/opt/llvm/bin"),
+        ];
+        assert_eq!(
+            prepend_path("bash", &paths).unwrap(),
+            "export PATH='/opt/rust/bin:/opt/llvm/bin':\"$PATH\""
+        );
+        assert_eq!(
+            prepend_path("fish", &paths).unwrap(),
+            "set -gx PATH '/opt/rust/bin' '/opt/llvm/bin' $PATH"
+        );
+    }
+}
@@ -0,0 +1,473 @@
+//! Explain command implementation
+//!
+//! Maps TBPF loader, verifier and runtime errors (as they appear in node logs)
+//! to human explanations and likely fixes.
+
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::error_codes::{self, ErrorCode};
+use crate::util::parse_address;
+
+/// Stage of the program lifecycle an error is reported from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorStage {
+    /// ELF parsing and relocation at deploy time
+    Loader,
+    /// Bytecode verification at deploy time
+    Verifier,
+    /// Execution of the program
+    Runtime,
+}
+
+impl ErrorStage {
+    fn as_str(self) -> &'static str {
+        match self {
+            ErrorStage::Loader => "loader",
+            ErrorStage::Verifier => "verifier",
+            ErrorStage::Runtime => "runtime",
+        }
+    }
+}
+
+/// A known error with its explanation
+pub struct ErrorExplanation {
+    /// Error variant name as printed by the node (e.g. "UnresolvedSymbol")
+    pub name: &'static str,
+    /// Numeric code reported for the error: the variant's index in the VM's
+    /// error enum for its stage (ElfError, VerifierError or EbpfError)
+    pub code: u32,
+    /// Fragment of the human-readable message that identifies the error
+    pub message: &'static str,
+    pub stage: ErrorStage,
+    pub explanation: &'static str,
+    pub fix: &'static str,
+}
+
+/// Maintained table of known errors
+///
+/// Keep entries grouped by stage. `message` must be a lowercase fragment of
+/// the error's display string so pasted log lines can be matched.
+pub const ERRORS: &[ErrorExplanation] = &[
+    // ===== Loader =====
+    ErrorExplanation {
+        name: "FailedToParse",
+        code: 0,
+        message: "failed to parse elf",
+        stage: ErrorStage::Loader,
+        explanation: "The uploaded file is not a well-formed ELF shared object.",
+        fix: "Deploy the .so produced by `cargo tako build --release` and check it with `cargo tako build --verify`.",
+    },
+    ErrorExplanation {
+        name: "EntrypointOutOfBounds",
+        code: 1,
+        message: "entrypoint out of bounds",
+        stage: ErrorStage::Loader,
+        explanation: "The ELF entry address does not point into the text section.",
+        fix: "Make sure the contract exports its entrypoint symbol and is linked as a cdylib.",
+    },
+    ErrorExplanation {
+        name: "InvalidEntrypoint",
+        code: 2,
+        message: "invalid entrypoint",
+        stage: ErrorStage::Loader,
+        explanation: "The entrypoint is missing or is not a valid function start.",
+        fix: "Check that the entrypoint function is `#[no_mangle] pub extern \"C\"` and not removed by LTO.",
+    },
+    ErrorExplanation {
+        name: "UnresolvedSymbol",
+        code: 4,
+        message: "unresolved symbol",
+        stage: ErrorStage::Loader,
+        explanation: "The program calls a function that is neither defined in the binary nor a registered syscall.",
+        fix: "Remove the dependency using the missing symbol (often std or libc), or update the node if the syscall is new.",
+    },
+    ErrorExplanation {
+        name: "SectionNotFound",
+        code: 5,
+        message: "section not found",
+        stage: ErrorStage::Loader,
+        explanation: "A section required by the loader (usually .text) is missing.",
+        fix: "Rebuild with `cargo tako build --release`; do not strip sections manually.",
+    },
+    ErrorExplanation {
+        name: "RelativeJumpOutOfBounds",
+        code: 6,
+        message: "relative jump out of bounds",
+        stage: ErrorStage::Loader,
+        explanation: "A relocated call or jump target falls outside the text section.",
+        fix: "Rebuild from clean (`cargo tako clean`) with the matching platform-tools version.",
+    },
+    ErrorExplanation {
+        name: "ValueOutOfBounds",
+        code: 20,
+        message: "relocation out of range",
+        stage: ErrorStage::Loader,
+        explanation: "A relocation points outside its section, usually because rodata is too large for the addressing mode.",
+        fix: "Reduce read-only data (large constant tables, long strings) and rebuild with --arch v3, which places rodata in its own region.",
+    },
+    ErrorExplanation {
+        name: "UnknownRelocation",
+        code: 16,
+        message: "unknown relocation",
+        stage: ErrorStage::Loader,
+        explanation: "The binary contains a relocation type the loader does not support.",
+        fix: "Build with the TOS platform-tools linker instead of a system linker.",
+    },
+    ErrorExplanation {
+        name: "SymbolHashCollision",
+        code: 7,
+        message: "symbol hash collision",
+        stage: ErrorStage::Loader,
+        explanation: "Two functions hash to the same call identifier.",
+        fix: "Rename one of the colliding functions.",
+    },
+    ErrorExplanation {
+        name: "WrongMachine",
+        code: 10,
+        message: "incompatible elf: wrong machine",
+        stage: ErrorStage::Loader,
+        explanation: "The ELF was built for a different CPU architecture than TBPF.",
+        fix: "Build with `cargo tako build` rather than plain `cargo build`.",
+    },
+    ErrorExplanation {
+        name: "WrongClass",
+        code: 11,
+        message: "incompatible elf: wrong class",
+        stage: ErrorStage::Loader,
+        explanation: "The ELF is not 64-bit.",
+        fix: "Build with `cargo tako build` so the tbpf target is used.",
+    },
+    ErrorExplanation {
+        name: "WritableSectionNotSupported",
+        code: 13,
+        message: "writable section not supported",
+        stage: ErrorStage::Loader,
+        explanation: "The program has mutable global data (.data/.bss), which contracts cannot use.",
+        fix: "Remove `static mut` and interior-mutable statics; keep state in contract storage instead.",
+    },
+    ErrorExplanation {
+        name: "UnsupportedSBPFVersion",
+        code: 21,
+        message: "unsupported sbpf version",
+        stage: ErrorStage::Loader,
+        explanation: "The e_flags of the binary select a TBPF version the network does not accept.",
+        fix: "Rebuild with an arch the network supports, e.g. `cargo tako build --arch v3`.",
+    },
+    ErrorExplanation {
+        name: "InvalidProgramHeader",
+        code: 22,
+        message: "invalid program header",
+        stage: ErrorStage::Loader,
+        explanation: "The program headers do not match the strict layout required by v3+.",
+        fix: "Build with the target matching the arch (`--arch v3` implies tbpfv3-tos-tos); do not override --target.",
+    },
+    // ===== Verifier =====
+    ErrorExplanation {
+        name: "ProgramLengthNotMultiple",
+        code: 0,
+        message: "program length must be a multiple",
+        stage: ErrorStage::Verifier,
+        explanation: "The text section is not a whole number of 8-byte instructions.",
+        fix: "The binary is corrupt or was post-processed; rebuild it.",
+    },
+    ErrorExplanation {
+        name: "NoProgram",
+        code: 2,
+        message: "no program set",
+        stage: ErrorStage::Verifier,
+        explanation: "The text section is empty.",
+        fix: "Check that the entrypoint and contract code are not compiled out by features or LTO.",
+    },
+    ErrorExplanation {
+        name: "DivisionByZero",
+        code: 3,
+        message: "division by 0",
+        stage: ErrorStage::Verifier,
+        explanation: "The program divides by a constant zero.",
+        fix: "Fix the division in the source; the compiler emits it only for literal zero divisors.",
+    },
+    ErrorExplanation {
+        name: "InfiniteLoop",
+        code: 7,
+        message: "infinite loop",
+        stage: ErrorStage::Verifier,
+        explanation: "A jump targets itself.",
+        fix: "Remove unconditional `loop {}` bodies (e.g. in a custom panic handler); abort instead.",
+    },
+    ErrorExplanation {
+        name: "JumpOutOfCode",
+        code: 8,
+        message: "jump out of code",
+        stage: ErrorStage::Verifier,
+        explanation: "A jump target lies outside the program.",
+        fix: "Rebuild from clean with the matching platform-tools version.",
+    },
+    ErrorExplanation {
+        name: "CannotWriteR10",
+        code: 11,
+        message: "cannot write into register r10",
+        stage: ErrorStage::Verifier,
+        explanation: "The frame pointer register was written to.",
+        fix: "Usually caused by hand-written assembly; remove or fix it.",
+    },
+    ErrorExplanation {
+        name: "UnknownOpCode",
+        code: 13,
+        message: "unknown ebpf opcode",
+        stage: ErrorStage::Verifier,
+        explanation: "An instruction is not valid for the binary's TBPF version.",
+        fix: "The arch used to build is newer than the network supports; rebuild with a lower --arch.",
+    },
+    ErrorExplanation {
+        name: "InvalidSyscall",
+        code: 17,
+        message: "invalid syscall",
+        stage: ErrorStage::Verifier,
+        explanation: "A static syscall refers to a syscall the node does not register.",
+        fix: "Update the node, or avoid the SDK function that uses the new syscall.",
+    },
+    ErrorExplanation {
+        name: "InvalidFunction",
+        code: 16,
+        message: "invalid function",
+        stage: ErrorStage::Verifier,
+        explanation: "A call targets an address that is not a registered function.",
+        fix: "Rebuild from clean; check for function pointers cast from integers.",
+    },
+    // ===== Runtime =====
+    ErrorExplanation {
+        name: "CallDepthExceeded",
+        code: 2,
+        message: "exceeded max bpf to bpf call depth",
+        stage: ErrorStage::Runtime,
+        explanation: "Too many nested function calls (often recursion).",
+        fix: "Replace recursion with iteration or reduce call nesting.",
+    },
+    ErrorExplanation {
+        name: "ExceededMaxInstructions",
+        code: 8,
+        message: "exceeded cus meter",
+        stage: ErrorStage::Runtime,
+        explanation: "The program ran out of compute units.",
+        fix: "Build with `--optimize speed`, reduce work per call, or request a larger compute budget.",
+    },
+    ErrorExplanation {
+        name: "AccessViolation",
+        code: 11,
+        message: "access violation",
+        stage: ErrorStage::Runtime,
+        explanation: "The program read or wrote memory outside a mapped region.",
+        fix: "Check slice indexing and unsafe pointer arithmetic near the reported address.",
+    },
+    ErrorExplanation {
+        name: "StackAccessViolation",
+        code: 12,
+        message: "access violation in stack frame",
+        stage: ErrorStage::Runtime,
+        explanation: "A stack frame grew past its fixed size (4KB per frame).",
+        fix: "Move large locals to the heap (Box/Vec) or build with --arch v1+ for dynamic stack frames.",
+    },
+    ErrorExplanation {
+        name: "DivideByZero",
+        code: 4,
+        message: "divide by zero",
+        stage: ErrorStage::Runtime,
+        explanation: "An integer division or remainder by zero at runtime.",
+        fix: "Validate divisors or use checked_div.",
+    },
+    ErrorExplanation {
+        name: "DivideOverflow",
+        code: 5,
+        message: "divide overflow",
+        stage: ErrorStage::Runtime,
+        explanation: "Signed division of the minimum value by -1.",
+        fix: "Use checked_div for signed arithmetic.",
+    },
+    ErrorExplanation {
+        name: "ExecutionOverrun",
+        code: 6,
+        message: "attempted to execute past the end",
+        stage: ErrorStage::Runtime,
+        explanation: "Execution ran off the end of the text section.",
+        fix: "Make sure the entrypoint returns; rebuild with the matching toolchain.",
+    },
+    ErrorExplanation {
+        name: "InvalidInstruction",
+        code: 13,
+        message: "invalid instruction",
+        stage: ErrorStage::Runtime,
+        explanation: "The VM reached an instruction it cannot execute.",
+        fix: "Rebuild with an arch supported by the network.",
+    },
+];
+
+/// Look up an error by name or message fragment
+///
+/// Accepts the variant name in any case (`unresolvedsymbol`, `UnresolvedSymbol`)
+/// or a pasted log line containing the error message.
+pub fn lookup(query: &str) -> Option<&'static ErrorExplanation> {
+    let normalized: String = query
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    let lowered = query.to_lowercase();
+
+    ERRORS
+        .iter()
+        .find(|e| e.name.to_lowercase() == normalized)
+        // Prefer the longest matching message so specific errors win over generic ones
+        .or_else(|| {
+            ERRORS
+                .iter()
+                .filter(|e| lowered.contains(e.message))
+                .max_by_key(|e| e.message.len())
+        })
+}
+
+/// Errors reported with a numeric code: the contract's own errors from its
+/// catalog (see [`error_codes`]) and built-in errors of any stage
+///
+/// # Arguments
+/// * `code` - Numeric error code
+/// * `catalog` - Custom error codes of the current project
+pub fn lookup_code(
+    code: u64,
+    catalog: &[ErrorCode],
+) -> (Vec<&ErrorCode>, Vec<&'static ErrorExplanation>) {
+    let custom = catalog
+        .iter()
+        .filter(|e| e.code == i128::from(code))
+        .collect();
+    let builtin = ERRORS
+        .iter()
+        .filter(|e| u64::from(e.code) == code)
+        .collect();
+    (custom, builtin)
+}
+
+/// Explain an error, or list all known errors when `query` is None
+pub fn explain(query: Option<&str>) -> Result<()> {
+    let Some(query) = query else {
+        for stage in [
+            ErrorStage::Loader,
+            ErrorStage::Verifier,
+            ErrorStage::Runtime,
+        ] {
+            println!("{}:", stage.as_str());
+            for e in ERRORS.iter().filter(|e| e.stage == stage) {
+                println!("  {}", e.name);
+            }
+        }
+        return Ok(());
+    };
+
+    // Decimal, or hex as in `custom program error: 0x1771`
+    if let Ok(code) = parse_address(query.trim()) {
+        return explain_code(code);
+    }
+
+    let entry = lookup(query).ok_or_else(|| {
+        Error::Other(format!(
+            "Unknown error '{query}'. Run `cargo tako explain` to list known errors."
+        ))
+    })?;
+    print_explanation(entry);
+
+    Ok(())
+}
+
+fn explain_code(code: u64) -> Result<()> {
+    let catalog = if Path::new("src").is_dir() {
+        error_codes::extract_catalog(Path::new("src"))?
+    } else {
+        Vec::new()
+    };
+    let (custom, builtin) = lookup_code(code, &catalog);
+    if custom.is_empty() && builtin.is_empty() {
+        return Err(Error::Other(format!(
+            "Unknown error code {code}. Run `cargo tako explain` to list known errors."
+        )));
+    }
+
+    for e in &custom {
+        println!("{} (error {code} of this contract)", e.path());
+        if !e.doc.is_empty() {
+            println!();
+            println!("  {}", e.doc);
+        }
+        println!();
+    }
+    // Built-in codes are only unique within a stage
+    if !builtin.is_empty() && !custom.is_empty() {
+        println!("Built-in errors with code {code}:");
+        println!();
+    }
+    for entry in builtin {
+        print_explanation(entry);
+        println!();
+    }
+    Ok(())
+}
+
+fn print_explanation(entry: &ErrorExplanation) {
+    println!(
+        "{} ({} error, code {})",
+        entry.name,
+        entry.stage.as_str(),
+        entry.code
+    );
+    println!();
+    println!("  {}", entry.explanation);
+    println!();
+    println!("Likely fix:");
+    println!("  {}", entry.fix);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_by_name() {
+        let entry = lookup("unresolved_symbol").unwrap();
+        assert_eq!(entry.name, "UnresolvedSymbol");
+    }
+
+    #[test]
+    fn test_lookup_by_log_line() {
+        let entry =
+            lookup("Program failed: Access violation in stack frame 3 at address 0x200001000")
+                .unwrap();
+        assert_eq!(entry.name, "StackAccessViolation");
+    }
+
+    #[test]
+    fn test_lookup_code() {
+        let catalog = vec![ErrorCode {
+            code: 6001,
+            enum_name: "VaultError".to_string(),
+            variant: "Locked".to_string(),
+            doc: "Vault is locked".to_string(),
+        }];
+
+        let (custom, builtin) = lookup_code(6001, &catalog);
+        assert_eq!(custom[0].path(), "VaultError::Locked");
+        assert!(builtin.is_empty());
+
+        // Built-in codes repeat across stages
+        let (custom, builtin) = lookup_code(4, &catalog);
+        assert!(custom.is_empty());
+        let names: Vec<&str> = builtin.iter().map(|e| e.name).collect();
+        assert_eq!(names, ["UnresolvedSymbol", "DivideByZero"]);
+
+        let (custom, builtin) = lookup_code(9999, &catalog);
+        assert!(custom.is_empty() && builtin.is_empty());
+    }
+
+    #[test]
+    fn test_lookup_unknown() {
+        assert!(lookup("definitely not an error").is_none());
+    }
+}
@@ -0,0 +1,316 @@
+//! Hexdump command implementation
+//!
+//! Renders the contract binary as hex and ASCII, annotated with ELF header
+//! fields, program and section header tables, section boundaries and the
+//! entrypoint, so low-level inspection does not need readelf and xxd side by
+//! side. Rows of .text are also annotated with the Rust lines they were
+//! compiled from when the artifact has a source map.
+
+use colored::Colorize;
+use goblin::elf::header::{et_to_str, machine_to_str};
+use goblin::elf::program_header::pt_to_str;
+use goblin::elf::section_header::SHT_NOBITS;
+use goblin::elf::Elf;
+use std::path::PathBuf;
+
+use crate::commands::build;
+use crate::elf;
+use crate::error::{Error, Result};
+use crate::source_map::SourceMap;
+use crate::style::Styled;
+use crate::util::{parse_address, sha256_hex};
+
+const ROW_SIZE: u64 = 16;
+
+/// An annotation at a file offset
+#[derive(Debug, Clone, PartialEq)]
+enum Mark {
+    /// Start of a region, printed on its own line above the row
+    Region(String),
+    /// A field, printed after the row that contains it
+    Field(String),
+}
+
+/// Dump the contract binary with annotations
+///
+/// # Arguments
+/// * `contract` - Path to a contract binary (defaults to the latest build)
+/// * `arch` - TBPF architecture version used to locate the latest build
+/// * `section` - Only dump this section
+/// * `start` - First byte offset to dump (hex with 0x prefix, or decimal)
+/// * `length` - Number of bytes to dump
+pub fn hexdump(
+    contract: Option<&str>,
+    arch: &str,
+    section: Option<&str>,
+    start: Option<&str>,
+    length: Option<&str>,
+) -> Result<()> {
+    let path = match contract {
+        Some(p) => PathBuf::from(p),
+        None => build::latest_artifact(arch)?,
+    };
+    let bytes = elf::read(&path)?;
+    let parsed = elf::parse(&bytes)?;
+
+    let (mut from, mut to) = (0, bytes.len() as u64);
+    if let Some(name) = section {
+        let header = parsed
+            .section_headers
+            .iter()
+            .find(|sh| parsed.shdr_strtab.get_at(sh.sh_name) == Some(name))
+            .ok_or_else(|| Error::Other(format!("Section {name} not found")))?;
+        if header.sh_type == SHT_NOBITS {
+            return Err(Error::Other(format!(
+                "Section {name} occupies no space in the file"
+            )));
+        }
+        from = header.sh_offset;
+        to = header.sh_offset + header.sh_size;
+    }
+    if let Some(start) = start {
+        from = parse_address(start)?;
+    }
+    if let Some(length) = length {
+        to = from.saturating_add(parse_address(length)?);
+    }
+    let to = to.min(bytes.len() as u64);
+    if from >= to {
+        return Err(Error::Other(format!(
+            "Range 0x{from:x}..0x{to:x} is outside the file ({} bytes)",
+            bytes.len()
+        )));
+    }
+
+    println!("Binary: {}", path.display());
+    println!("Range: 0x{from:x}..0x{to:x} ({} bytes)", to - from);
+    println!();
+
+    let mut marks = annotations(&parsed);
+    if let Some(map) = SourceMap::load_for(&path, &sha256_hex(&bytes)) {
+        marks.extend(source_lines(&parsed, &map));
+        marks.sort_by_key(|(offset, _)| *offset);
+    }
+
+    for line in render(&bytes, from, to, &marks) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Collect annotations for the ELF header, header tables, sections and
+/// entrypoint, sorted by file offset
+fn annotations(elf: &Elf) -> Vec<(u64, Mark)> {
+    let header = &elf.header;
+    let mut marks = vec![(0, Mark::Region("ELF header".to_string()))];
+
+    if elf.is_64 {
+        let fields = [
+            (0x00, "magic".to_string()),
+            (0x04, format!("class={}", header.e_ident[4])),
+            (0x05, format!("data={}", header.e_ident[5])),
+            (0x07, format!("osabi={}", header.e_ident[7])),
+            (0x10, format!("e_type={}", et_to_str(header.e_type))),
+            (
+                0x12,
+                format!("e_machine={}", machine_to_str(header.e_machine)),
+            ),
+            (0x14, format!("e_version={}", header.e_version)),
+            (0x18, format!("e_entry=0x{:x}", header.e_entry)),
+            (0x20, format!("e_phoff=0x{:x}", header.e_phoff)),
+            (0x28, format!("e_shoff=0x{:x}", header.e_shoff)),
+            (0x30, format!("e_flags=0x{:x}", header.e_flags)),
+            (0x34, format!("e_ehsize={}", header.e_ehsize)),
+            (0x36, format!("e_phentsize={}", header.e_phentsize)),
+            (0x38, format!("e_phnum={}", header.e_phnum)),
+            (0x3a, format!("e_shentsize={}", header.e_shentsize)),
+            (0x3c, format!("e_shnum={}", header.e_shnum)),
+            (0x3e, format!("e_shstrndx={}", header.e_shstrndx)),
+        ];
+        marks.extend(
+            fields
+                .into_iter()
+                .map(|(offset, f)| (offset, Mark::Field(f))),
+        );
+    }
+
+    if !elf.program_headers.is_empty() {
+        marks.push((header.e_phoff, Mark::Region("program headers".to_string())));
+        for (i, ph) in elf.program_headers.iter().enumerate() {
+            let offset = header.e_phoff + i as u64 * header.e_phentsize as u64;
+            marks.push((
+                offset,
+                Mark::Field(format!("{}[{i}]", pt_to_str(ph.p_type))),
+            ));
+        }
+    }
+
+    for sh in &elf.section_headers {
+        if sh.sh_type == SHT_NOBITS || sh.sh_size == 0 {
+            continue;
+        }
+        let name = elf.shdr_strtab.get_at(sh.sh_name).unwrap_or("?");
+        marks.push((
+            sh.sh_offset,
+            Mark::Region(format!("{name} ({} bytes)", sh.sh_size)),
+        ));
+    }
+
+    if !elf.section_headers.is_empty() {
+        marks.push((header.e_shoff, Mark::Region("section headers".to_string())));
+    }
+
+    let entry = elf.section_headers.iter().find(|sh| {
+        sh.sh_type != SHT_NOBITS
+            && sh.sh_addr <= header.e_entry
+            && header.e_entry < sh.sh_addr + sh.sh_size
+    });
+    if let Some(sh) = entry {
+        let offset = sh.sh_offset + (header.e_entry - sh.sh_addr);
+        marks.push((offset, Mark::Region("entrypoint".to_string())));
+    }
+
+    // Stable sort keeps header fields in table order at equal offsets
+    marks.sort_by_key(|(offset, _)| *offset);
+    marks
+}
+
+/// Source lines from the source map, at the file offset of the first
+/// instruction compiled from each line
+fn source_lines(elf: &Elf, map: &SourceMap) -> Vec<(u64, Mark)> {
+    let Some(text) = elf
+        .section_headers
+        .iter()
+        .find(|sh| elf.shdr_strtab.get_at(sh.sh_name) == Some(".text"))
+    else {
+        return Vec::new();
+    };
+    map.ranges
+        .iter()
+        .filter(|r| text.sh_addr <= r.start && r.start < text.sh_addr + text.sh_size)
+        .map(|r| {
+            let offset = text.sh_offset + (r.start - text.sh_addr);
+            (offset, Mark::Field(r.location()))
+        })
+        .collect()
+}
+
+/// Render `bytes[from..to]` as annotated hexdump lines
+///
+/// Rows identical to the previous one and without annotations are collapsed
+/// into a single `*` line.
+fn render(bytes: &[u8], from: u64, to: u64, marks: &[(u64, Mark)]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut previous: Option<&[u8]> = None;
+    let mut squeezed = false;
+
+    let mut row = from - from % ROW_SIZE;
+    while row < to {
+        let start = row.max(from);
+        let end = (row + ROW_SIZE).min(to);
+        let in_row: Vec<&Mark> = marks
+            .iter()
+            .filter(|(offset, _)| (start..end).contains(offset))
+            .map(|(_, mark)| mark)
+            .collect();
+
+        let chunk = &bytes[start as usize..end as usize];
+        if in_row.is_empty() && start == row && previous == Some(chunk) {
+            if !squeezed {
+                lines.push("*".to_string());
+                squeezed = true;
+            }
+            row += ROW_SIZE;
+            continue;
+        }
+        squeezed = false;
+        previous = Some(chunk);
+
+        for (offset, mark) in marks {
+            if let Mark::Region(name) = mark {
+                if (start..end).contains(offset) {
+                    lines.push(format!(
+                        "{:>8}  {} @ 0x{offset:x}",
+                        "",
+                        name.accent().bold()
+                    ));
+                }
+            }
+        }
+
+        let mut hex = String::new();
+        let mut ascii = String::new();
+        for i in 0..ROW_SIZE {
+            let offset = row + i;
+            if i == 8 {
+                hex.push(' ');
+            }
+            if (start..end).contains(&offset) {
+                let byte = bytes[offset as usize];
+                hex.push_str(&format!("{byte:02x} "));
+                ascii.push(if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                });
+            } else {
+                hex.push_str("   ");
+                ascii.push(' ');
+            }
+        }
+
+        let fields: Vec<&str> = in_row
+            .iter()
+            .filter_map(|mark| match mark {
+                Mark::Field(name) => Some(name.as_str()),
+                Mark::Region(_) => None,
+            })
+            .collect();
+        let mut line = format!("{row:08x}  {hex} |{ascii}|");
+        if !fields.is_empty() {
+            line.push_str(&format!("  {}", fields.join(", ").dimmed()));
+        }
+        lines.push(line);
+
+        row += ROW_SIZE;
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        colored::control::set_override(false);
+        let mut bytes = vec![0u8; 64];
+        bytes[..4].copy_from_slice(b"\x7fELF");
+        let marks = vec![
+            (0, Mark::Region("ELF header".to_string())),
+            (0, Mark::Field("magic".to_string())),
+            (0x24, Mark::Region(".text (8 bytes)".to_string())),
+        ];
+
+        let lines = render(&bytes, 0, 64, &marks);
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[0], "          ELF header @ 0x0");
+        assert!(lines[1].starts_with("00000000  7f 45 4c 46 00"));
+        assert!(lines[1].ends_with("|.ELF............|  magic"));
+        assert!(lines[2].starts_with("00000010  00"));
+        assert_eq!(lines[3], "          .text (8 bytes) @ 0x24");
+        assert!(lines[4].starts_with("00000020  00"));
+        assert_eq!(lines[5], "*");
+
+        // A partial range leaves the bytes outside it blank
+        let lines = render(&bytes, 2, 6, &[]);
+        assert_eq!(
+            lines,
+            vec![format!(
+                "00000000        4c 46 00 00{:33}|  LF..          |",
+                ""
+            )]
+        );
+    }
+}
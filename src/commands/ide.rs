@@ -0,0 +1,91 @@
+//! IDE command implementation
+//!
+//! Configures rust-analyzer to analyze the contract for the TBPF target with
+//! the platform-tools compiler, so editor diagnostics match real builds.
+
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::Path;
+
+use crate::commands::build;
+use crate::error::{Error, Result};
+use crate::toolchain::{find_platform_tools, DEFAULT_PLATFORM_TOOLS_VERSION};
+use crate::util::ensure_dir;
+
+const SETTINGS_PATH: &str = ".vscode/settings.json";
+
+/// Write rust-analyzer settings for the contract to .vscode/settings.json
+///
+/// Existing settings are kept; only the rust-analyzer keys below are replaced.
+///
+/// # Arguments
+/// * `arch` - TBPF architecture version to analyze for
+pub fn setup(arch: &str) -> Result<()> {
+    let target = build::get_target_triple(arch);
+    let mut settings = Map::new();
+    settings.insert("rust-analyzer.cargo.target".to_string(), json!(target));
+    settings.insert(
+        "rust-analyzer.cargo.extraArgs".to_string(),
+        json!(["-Zbuild-std=core,alloc"]),
+    );
+    settings.insert(
+        "rust-analyzer.check.overrideCommand".to_string(),
+        json!([
+            "cargo",
+            "tako",
+            "check",
+            "--message-format",
+            "json",
+            "--arch",
+            arch
+        ]),
+    );
+
+    match find_platform_tools(Some(DEFAULT_PLATFORM_TOOLS_VERSION)) {
+        Some(tools) => {
+            println!("  Toolchain: {} ({})", tools.display_path(), tools.version);
+            if let Some(sysroot) = tools.rust_bin.parent() {
+                settings.insert(
+                    "rust-analyzer.cargo.sysroot".to_string(),
+                    json!(sysroot.to_string_lossy()),
+                );
+            }
+            settings.insert(
+                "rust-analyzer.cargo.extraEnv".to_string(),
+                json!({ "RUSTC": tools.rustc().to_string_lossy() }),
+            );
+        }
+        None => {
+            eprintln!("Warning: TOS platform-tools not found; rust-analyzer will use the default toolchain");
+        }
+    }
+
+    let path = Path::new(SETTINGS_PATH);
+    let mut existing = if path.exists() {
+        let content = fs::read_to_string(path)?;
+        match serde_json::from_str::<Value>(&content) {
+            Ok(Value::Object(map)) => map,
+            _ => {
+                return Err(Error::Config(format!(
+                    "{SETTINGS_PATH} is not plain JSON (comments are not supported); add these settings manually:\n{}",
+                    serde_json::to_string_pretty(&settings).unwrap_or_default()
+                )))
+            }
+        }
+    } else {
+        Map::new()
+    };
+
+    for (key, value) in settings {
+        println!("  {key}");
+        existing.insert(key, value);
+    }
+
+    ensure_dir(".vscode")?;
+    let content = serde_json::to_string_pretty(&Value::Object(existing))
+        .map_err(|e| Error::Other(format!("Failed to serialize settings: {e}")))?;
+    fs::write(path, content + "\n")?;
+
+    println!("✓ Wrote {SETTINGS_PATH}");
+    Ok(())
+}
@@ -1,11 +1,33 @@
 //! Project initialization command
 
 use crate::error::{Error, Result};
-use crate::template::{get_template, process_template};
+use crate::interrupt;
+use crate::logs;
+use crate::style::Styled;
+use crate::template::{get_template, process_template, Template, TemplateContext};
 use crate::util::{ensure_dir, write_file};
-use std::path::PathBuf;
+use colored::{ColoredString, Colorize};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// .cargo/config.toml written into new projects
+const CARGO_CONFIG: &str = r#"# TAKO Contract Build Configuration
+#
+# For development and testing:
+#   cargo test                    # Run tests with native target
+#
+# For TBPF deployment build:
+#   cargo tako build --release    # Build with tbpf-tos-tos target
+#   cargo build --target tbpf-tos-tos --release
+
+[target.tbpf-tos-tos]
+linker = "rust-lld"
+"#;
+
+/// Project name templates are instantiated with during validation
+const VALIDATION_PROJECT: &str = "template-check";
+
 /// Create a new TAKO project
 ///
 /// Creates a new directory with the specified name and initializes it with
@@ -14,8 +36,14 @@ use std::process::Command;
 /// # Arguments
 /// * `name` - Project name (will be used as directory name)
 /// * `path` - Optional parent directory path (defaults to current directory)
-/// * `template` - Template name (e.g., "default", "erc20", "erc721")
-pub fn create_new_project(name: &str, path: Option<&str>, template: &str) -> Result<()> {
+/// * `template` - Template name (e.g., "default", "erc20", "erc721") or template directory
+/// * `vars` - Extra template variables as `key=value`
+pub fn create_new_project(
+    name: &str,
+    path: Option<&str>,
+    template: &str,
+    vars: &[String],
+) -> Result<()> {
     // Determine project root directory
     let project_root = if let Some(parent) = path {
         PathBuf::from(parent).join(name)
@@ -28,52 +56,44 @@ pub fn create_new_project(name: &str, path: Option<&str>, template: &str) -> Res
         return Err(Error::ProjectExists(name.to_string()));
     }
 
+    // Get template and resolve variables before touching the filesystem
+    let tmpl = get_template(template)?;
+    let mut ctx = TemplateContext::new(name);
+    ctx.apply_vars(vars)?;
+
     // Create project directory
     ensure_dir(&project_root)?;
 
-    // Create src directory
-    let src_dir = project_root.join("src");
-    ensure_dir(&src_dir)?;
+    // C contracts are built by clang, without cargo
+    if tmpl.is_c() {
+        write_template_files(&tmpl, &ctx, &project_root, |_| true)?;
+        let _ = logs::output(
+            Command::new("git")
+                .args(["init"])
+                .current_dir(&project_root),
+        );
+        write_file(project_root.join(".gitignore"), "target/\n*.log\n*.so\n")?;
+        return Ok(());
+    }
 
     // Create .cargo directory
     let cargo_dir = project_root.join(".cargo");
     ensure_dir(&cargo_dir)?;
 
-    // Get template
-    let tmpl = get_template(template)?;
-
-    // Process template placeholders
-    let cargo_toml = process_template(&tmpl.cargo_toml, name);
-    let lib_rs = process_template(&tmpl.lib_rs, name);
-    let readme = process_template(&tmpl.readme, name);
-
-    // Write files
-    write_file(project_root.join("Cargo.toml"), &cargo_toml)?;
-    write_file(src_dir.join("lib.rs"), &lib_rs)?;
-    write_file(project_root.join("README.md"), &readme)?;
+    // Process template placeholders and write files
+    write_template_files(&tmpl, &ctx, &project_root, |_| true)?;
 
     // Create .cargo/config.toml for TBPF target
     // Note: We don't set a default target to allow native tests
     // Use `cargo tako build` or `cargo build --target tbpf-tos-tos` for TBPF builds
-    let cargo_config = r#"# TAKO Contract Build Configuration
-#
-# For development and testing:
-#   cargo test                    # Run tests with native target
-#
-# For TBPF deployment build:
-#   cargo tako build --release    # Build with tbpf-tos-tos target
-#   cargo build --target tbpf-tos-tos --release
-
-[target.tbpf-tos-tos]
-linker = "rust-lld"
-"#;
-    write_file(cargo_dir.join("config.toml"), cargo_config)?;
+    write_file(cargo_dir.join("config.toml"), CARGO_CONFIG)?;
 
     // Initialize git repository
-    let _ = Command::new("git")
-        .args(["init"])
-        .current_dir(&project_root)
-        .output();
+    let _ = logs::output(
+        Command::new("git")
+            .args(["init"])
+            .current_dir(&project_root),
+    );
 
     // Create .gitignore
     let gitignore = "target/\n*.log\n*.so\nCargo.lock\n";
@@ -81,10 +101,11 @@ linker = "rust-lld"
 
     // Run cargo check to verify project
     println!("Verifying project...");
-    let check_result = Command::new("cargo")
-        .args(["check"])
-        .current_dir(&project_root)
-        .output();
+    let check_result = logs::output(
+        Command::new("cargo")
+            .args(["check"])
+            .current_dir(&project_root),
+    );
 
     match check_result {
         Ok(output) if output.status.success() => {
@@ -102,81 +123,518 @@ linker = "rust-lld"
     Ok(())
 }
 
+/// Whether `init` writes a template file into an existing project: contract
+/// sources and the golden files their tests compare against
+fn is_source_file(path: &str) -> bool {
+    path.starts_with("src/") || path.starts_with("tests/golden/")
+}
+
 /// Initialize TAKO in an existing Rust project
 ///
-/// Adds TAKO dependencies and creates a template contract in the current directory.
+/// Adds the template's dependencies, `[lib]` and profile settings to
+/// Cargo.toml (keeping existing values and formatting), merges the TBPF
+/// target into .cargo/config.toml and creates a template contract (with its
+/// golden files) in the current directory.
 ///
 /// # Arguments
-/// * `template` - Template name (e.g., "default", "erc20", "erc721")
-pub fn init_current_project(template: &str) -> Result<()> {
+/// * `template` - Template name (e.g., "default", "erc20", "erc721") or template directory
+/// * `vars` - Extra template variables as `key=value`
+/// * `force` - Replace an existing .cargo/config.toml instead of merging into it
+/// * `dry_run` - Show the changes without writing anything
+pub fn init_current_project(
+    template: &str,
+    vars: &[String],
+    force: bool,
+    dry_run: bool,
+) -> Result<()> {
     let current_dir = std::env::current_dir()?;
 
-    // Check if Cargo.toml exists
-    let cargo_toml_path = current_dir.join("Cargo.toml");
-    if !cargo_toml_path.exists() {
+    // Get template
+    let tmpl = get_template(template)?;
+    if tmpl.is_c() {
         return Err(Error::Other(
-            "No Cargo.toml found in current directory. Use 'cargo tako new' instead.".to_string(),
+            "The c template is not merged into a cargo project. Use 'cargo tako new --template c' instead.".to_string(),
         ));
     }
 
-    // Check if src/lib.rs already exists
-    let lib_rs_path = current_dir.join("src/lib.rs");
-    if lib_rs_path.exists() {
+    // Check if Cargo.toml exists
+    let cargo_toml_path = current_dir.join("Cargo.toml");
+    if !cargo_toml_path.exists() {
         return Err(Error::Other(
-            "src/lib.rs already exists. Remove it or use a fresh project.".to_string(),
+            "No Cargo.toml found in current directory. Use 'cargo tako new' instead.".to_string(),
         ));
     }
 
-    // Get template
-    let tmpl = get_template(template)?;
-
     // Get project name from directory
     let project_name = current_dir
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("my-contract");
 
-    // Process template
-    let lib_rs = process_template(&tmpl.lib_rs, project_name);
+    let mut ctx = TemplateContext::new(project_name);
+    ctx.apply_vars(vars)?;
 
-    // Create src directory if it doesn't exist
-    let src_dir = current_dir.join("src");
-    ensure_dir(&src_dir)?;
+    // Plan all edits before writing, so a bad file leaves the project untouched
+    let manifest = fs::read_to_string(&cargo_toml_path)?;
+    let mut new_manifest = manifest.clone();
+    for file in &tmpl.files {
+        if process_template(&file.path, &ctx)? == "Cargo.toml" {
+            new_manifest = merge_manifest(&manifest, &process_template(&file.content, &ctx)?)?;
+        }
+    }
 
-    // Create .cargo directory and config
     let cargo_dir = current_dir.join(".cargo");
-    ensure_dir(&cargo_dir)?;
+    let config_path = cargo_dir.join("config.toml");
+    let config = fs::read_to_string(&config_path).ok();
+    let new_config = match &config {
+        Some(existing) if !force => merge_cargo_config(existing)?,
+        _ => CARGO_CONFIG.to_string(),
+    };
 
-    let cargo_config = r#"# TAKO Contract Build Configuration
-#
-# For development and testing:
-#   cargo test                    # Run tests with native target
-#
-# For TBPF deployment build:
-#   cargo tako build --release    # Build with tbpf-tos-tos target
-#   cargo build --target tbpf-tos-tos --release
+    // Contract sources and golden files are only created when missing, unless --force
+    let mut sources = Vec::new();
+    for file in &tmpl.files {
+        let path = process_template(&file.path, &ctx)?;
+        let content = process_template(&file.content, &ctx)?;
+        if !is_source_file(&path) || content.trim().is_empty() {
+            continue;
+        }
+        let existing = fs::read_to_string(current_dir.join(&path)).ok();
+        let action = match &existing {
+            None => Action::Create,
+            Some(existing) if *existing == content => Action::Unchanged,
+            Some(_) if force => Action::Replace,
+            Some(_) => Action::Keep,
+        };
+        sources.push((path, content, action));
+    }
 
-[target.tbpf-tos-tos]
-linker = "rust-lld"
-"#;
-    write_file(cargo_dir.join("config.toml"), cargo_config)?;
+    if dry_run {
+        print_diff("Cargo.toml", &manifest, &new_manifest);
+        print_diff(
+            ".cargo/config.toml",
+            config.as_deref().unwrap_or_default(),
+            &new_config,
+        );
+        for (path, _, action) in &sources {
+            println!("{} {path}", action.label());
+        }
+        return Ok(());
+    }
 
-    // Write lib.rs
-    write_file(&lib_rs_path, &lib_rs)?;
+    let mut summary: Vec<(Action, String)> = Vec::new();
+    if new_manifest != manifest {
+        write_file(&cargo_toml_path, &new_manifest)?;
+        summary.push((Action::Update, "Cargo.toml".to_string()));
+    } else {
+        summary.push((Action::Unchanged, "Cargo.toml".to_string()));
+    }
 
-    println!("✓ TAKO contract initialized");
+    ensure_dir(&cargo_dir)?;
+    match &config {
+        None => {
+            write_file(&config_path, &new_config)?;
+            summary.push((Action::Create, ".cargo/config.toml".to_string()));
+        }
+        Some(existing) if *existing == new_config => {
+            summary.push((Action::Unchanged, ".cargo/config.toml".to_string()));
+        }
+        Some(_) => {
+            let backup = cargo_dir.join("config.toml.bak");
+            fs::copy(&config_path, &backup)?;
+            write_file(&config_path, &new_config)?;
+            let action = if force {
+                Action::Replace
+            } else {
+                Action::Update
+            };
+            summary.push((
+                action,
+                ".cargo/config.toml (backup: config.toml.bak)".to_string(),
+            ));
+        }
+    }
+
+    // Write the contract sources and golden files; the existing README is kept
+    for (path, content, action) in sources {
+        let target = current_dir.join(&path);
+        match action {
+            Action::Create => {
+                if let Some(parent) = target.parent() {
+                    ensure_dir(parent)?;
+                }
+                write_file(&target, &content)?;
+                summary.push((action, path));
+            }
+            Action::Replace => {
+                let backup = format!("{path}.bak");
+                fs::copy(&target, current_dir.join(&backup))?;
+                write_file(&target, &content)?;
+                summary.push((action, format!("{path} (backup: {backup})")));
+            }
+            Action::Keep => summary.push((action, format!("{path} (use --force to replace)"))),
+            _ => summary.push((action, path)),
+        }
+    }
+
+    for (action, path) in &summary {
+        println!("  {} {path}", action.label());
+    }
     println!();
-    println!("Next steps:");
-    println!("  1. Add TAKO dependencies to Cargo.toml:");
-    println!("     [dependencies]");
-    println!("     tako-macros = {{ git = \"https://github.com/tos-network/tako\" }}");
-    println!("     tako-storage = {{ git = \"https://github.com/tos-network/tako\" }}");
+    println!("✓ TAKO contract initialized");
     println!();
-    println!("  2. Set crate type to cdylib:");
-    println!("     [lib]");
-    println!("     crate-type = [\"cdylib\"]");
+    println!("Next step: cargo tako build");
+
+    Ok(())
+}
+
+/// What `init` does with a file
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Action {
+    Create,
+    Update,
+    Replace,
+    /// Exists with different content and is left alone
+    Keep,
+    Unchanged,
+}
+
+impl Action {
+    fn label(self) -> ColoredString {
+        match self {
+            Action::Create => format!("{:<9}", "create").success(),
+            Action::Update => format!("{:<9}", "update").accent(),
+            Action::Replace => format!("{:<9}", "replace").warning(),
+            Action::Keep => format!("{:<9}", "skip").normal(),
+            Action::Unchanged => format!("{:<9}", "unchanged").dimmed(),
+        }
+    }
+}
+
+/// Validate a template by instantiating it into a temporary project
+///
+/// Placeholders are checked statically first (all branches), then the
+/// project is written with a dummy name and checked with `cargo check` and
+/// `cargo tako check`, optionally followed by a full `cargo tako build`.
+///
+/// # Arguments
+/// * `template` - Template name or template directory
+/// * `vars` - Extra template variables as `key=value`
+/// * `build` - Also build the instantiated project for TBPF
+/// * `keep` - Keep the temporary project instead of removing it
+pub fn validate_template(template: &str, vars: &[String], build: bool, keep: bool) -> Result<()> {
+    let tmpl = get_template(template)?;
+    let mut ctx = TemplateContext::new(VALIDATION_PROJECT);
+    ctx.apply_vars(vars)?;
+
+    let problems = crate::template::validate(&tmpl, &ctx);
+    if !problems.is_empty() {
+        for problem in &problems {
+            println!("  {} {problem}", "✗".failure());
+        }
+        return Err(Error::InvalidTemplate(format!(
+            "{template}: {} problem(s)",
+            problems.len()
+        )));
+    }
+    println!("  {} placeholders", "✓".success());
+
+    let root = std::env::temp_dir().join(format!("tako-template-{}", std::process::id()));
+    if root.exists() {
+        fs::remove_dir_all(&root)?;
+    }
+    interrupt::remove_on_interrupt(&root);
+    let project = root.join(VALIDATION_PROJECT);
+    ensure_dir(&project)?;
+    write_template_files(&tmpl, &ctx, &project, |_| true)?;
+
+    let tako = std::env::current_exe()?;
+    let mut steps = Vec::new();
+    // C templates have nothing for cargo to check; only --build applies
+    if !tmpl.is_c() {
+        ensure_dir(project.join(".cargo"))?;
+        write_file(project.join(".cargo/config.toml"), CARGO_CONFIG)?;
+        let mut cargo_check = Command::new("cargo");
+        cargo_check.arg("check");
+        let mut tako_check = Command::new(&tako);
+        tako_check.args(["tako", "check"]);
+        steps.push(("cargo check", cargo_check));
+        steps.push(("cargo tako check", tako_check));
+    }
+    if build {
+        let mut tako_build = Command::new(&tako);
+        tako_build.args(["tako", "build"]);
+        steps.push(("cargo tako build", tako_build));
+    }
+
+    let mut failed = 0;
+    for (name, mut cmd) in steps {
+        match interrupt::output(cmd.current_dir(&project)) {
+            Ok(output) if output.status.success() => println!("  {} {name}", "✓".success()),
+            Ok(output) => {
+                failed += 1;
+                println!("  {} {name}", "✗".failure());
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                for line in stderr.lines().filter(|l| !l.trim().is_empty()) {
+                    println!("      {line}");
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                println!("  {} {name}: {e}", "✗".failure());
+            }
+        }
+    }
+
+    if keep {
+        interrupt::keep(&root);
+        println!();
+        println!("Project kept at {}", project.display());
+    } else {
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    if failed > 0 {
+        return Err(Error::InvalidTemplate(format!(
+            "{template}: {failed} check(s) failed on the instantiated project"
+        )));
+    }
     println!();
-    println!("  3. Run: cargo tako build");
+    println!("✓ Template {template} is valid");
+    Ok(())
+}
+
+/// Add the tables and keys of the default cargo config that `existing` lacks
+fn merge_cargo_config(existing: &str) -> Result<String> {
+    let mut doc: toml_edit::DocumentMut = existing
+        .parse()
+        .map_err(|e| Error::Config(format!(".cargo/config.toml: {e}")))?;
+    let defaults: toml_edit::DocumentMut = CARGO_CONFIG
+        .parse()
+        .map_err(|e| Error::Config(format!("default cargo config: {e}")))?;
+    let mut position = last_position(doc.as_table()) + 1;
+    merge_missing(doc.as_table_mut(), defaults.as_table(), &mut position);
+    Ok(doc.to_string())
+}
+
+/// Add the template's manifest tables (all but `[package]`) that `existing`
+/// lacks, and make sure the library is built as a cdylib
+fn merge_manifest(existing: &str, template: &str) -> Result<String> {
+    let mut doc: toml_edit::DocumentMut = existing
+        .parse()
+        .map_err(|e| Error::Config(format!("Cargo.toml: {e}")))?;
+    let mut defaults: toml_edit::DocumentMut = template
+        .parse()
+        .map_err(|e| Error::InvalidTemplate(format!("Cargo.toml: {e}")))?;
+    defaults.remove("package");
+
+    let mut position = last_position(doc.as_table()) + 1;
+    merge_missing(doc.as_table_mut(), defaults.as_table(), &mut position);
+
+    // Other crate types the user builds (e.g. rlib for tests) are kept
+    let crate_types = doc
+        .get_mut("lib")
+        .and_then(|lib| lib.get_mut("crate-type"))
+        .and_then(|types| types.as_array_mut());
+    if let Some(types) = crate_types {
+        if !types.iter().any(|t| t.as_str() == Some("cdylib")) {
+            types.push("cdylib");
+        }
+    }
+    Ok(doc.to_string())
+}
+
+/// Print a line diff of a planned file change
+pub fn print_diff(name: &str, old: &str, new: &str) {
+    if old == new {
+        println!("{} {name} (unchanged)", "keep".dimmed());
+        return;
+    }
+    println!(
+        "{} {name}",
+        if old.is_empty() { "create" } else { "edit" }.success()
+    );
+    for line in line_diff(old, new) {
+        match line.chars().next() {
+            Some('+') => println!("  {}", line.success()),
+            Some('-') => println!("  {}", line.failure()),
+            _ => println!("  {}", line.dimmed()),
+        }
+    }
+}
+
+/// Lines of `old` and `new` prefixed with ' ', '-' or '+' (longest common
+/// subsequence; manifests are small enough for the quadratic table)
+fn line_diff(old: &str, new: &str) -> Vec<String> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(format!(" {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] >= common[i + 1][j]) {
+            lines.push(format!("+{}", new[j]));
+            j += 1;
+        } else {
+            lines.push(format!("-{}", old[i]));
+            i += 1;
+        }
+    }
+    lines
+}
+
+/// Highest position of any table in the document
+fn last_position(table: &toml_edit::Table) -> usize {
+    table
+        .iter()
+        .filter_map(|(_, item)| item.as_table())
+        .map(last_position)
+        .chain(table.position())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Insert what `table` lacks from `defaults`; added tables are positioned
+/// after the user's tables, starting at `position`
+fn merge_missing(table: &mut toml_edit::Table, defaults: &toml_edit::Table, position: &mut usize) {
+    for (key, item) in defaults.iter() {
+        match (table.get_mut(key), item) {
+            (None, _) => {
+                let mut item = item.clone();
+                if let Some(added) = item.as_table_mut() {
+                    append_tables(added, position);
+                }
+                table.insert(key, item);
+            }
+            (Some(toml_edit::Item::Table(existing)), toml_edit::Item::Table(defaults)) => {
+                merge_missing(existing, defaults, position);
+            }
+            // Values the user already set win
+            (Some(_), _) => {}
+        }
+    }
+}
+
+fn append_tables(table: &mut toml_edit::Table, position: &mut usize) {
+    table.set_position(*position);
+    *position += 1;
+    if !table.is_implicit() {
+        // Separate from the user's last table
+        let prefix = table
+            .decor()
+            .prefix()
+            .and_then(|p| p.as_str())
+            .unwrap_or("");
+        if !prefix.starts_with('\n') {
+            let prefix = format!("\n{prefix}");
+            table.decor_mut().set_prefix(prefix);
+        }
+    }
+    for (_, item) in table.iter_mut() {
+        if let Some(child) = item.as_table_mut() {
+            append_tables(child, position);
+        }
+    }
+}
+
+/// Render template files into `root`
+///
+/// Both file paths and contents are processed. Files rendering to empty
+/// content are skipped, as are files rejected by `include`.
+fn write_template_files(
+    tmpl: &Template,
+    ctx: &TemplateContext,
+    root: &Path,
+    include: impl Fn(&str) -> bool,
+) -> Result<()> {
+    for file in &tmpl.files {
+        let path = process_template(&file.path, ctx)?;
+        if !include(&path) {
+            continue;
+        }
 
+        let content = process_template(&file.content, ctx)?;
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        let target = root.join(&path);
+        if let Some(parent) = target.parent() {
+            ensure_dir(parent)?;
+        }
+        write_file(target, &content)?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_cargo_config() {
+        let existing =
+            "# my settings\n[build]\njobs = 4\n\n[target.tbpf-tos-tos]\nrunner = \"tos-run\"\n";
+        let merged = merge_cargo_config(existing).unwrap();
+        assert!(merged.starts_with("# my settings\n[build]\njobs = 4\n"));
+        let doc: toml::Value = toml::from_str(&merged).unwrap();
+        let target = &doc["target"]["tbpf-tos-tos"];
+        assert_eq!(target["runner"].as_str(), Some("tos-run"));
+        assert_eq!(target["linker"].as_str(), Some("rust-lld"));
+
+        // Already merged configs are left alone
+        assert_eq!(merge_cargo_config(&merged).unwrap(), merged);
+        assert_eq!(merge_cargo_config(CARGO_CONFIG).unwrap(), CARGO_CONFIG);
+    }
+
+    #[test]
+    fn test_merge_manifest() {
+        let existing = "[package]\nname = \"token\" # mine\n\n[lib]\ncrate-type = [\"rlib\"]\n\n[profile.release]\nopt-level = 3\n";
+        let template = "[package]\nname = \"other\"\n\n[lib]\ncrate-type = [\"cdylib\"]\n\n[dependencies]\ntako-macros = { git = \"https://github.com/tos-network/tako\" }\n\n[profile.release]\nopt-level = \"z\"\nlto = true\n";
+        let merged = merge_manifest(existing, template).unwrap();
+        assert!(merged.starts_with("[package]\nname = \"token\" # mine\n"));
+
+        let doc: toml::Value = toml::from_str(&merged).unwrap();
+        let crate_types = doc["lib"]["crate-type"].as_array().unwrap();
+        assert_eq!(crate_types.len(), 2);
+        assert!(doc["dependencies"].get("tako-macros").is_some());
+        assert_eq!(doc["profile"]["release"]["opt-level"].as_integer(), Some(3));
+        assert_eq!(doc["profile"]["release"]["lto"].as_bool(), Some(true));
+
+        assert_eq!(line_diff("a\nb\n", "a\nc\nb\n"), vec![" a", "+c", " b"]);
+    }
+
+    #[test]
+    fn test_is_source_file() {
+        let erc20 = get_template("erc20").unwrap();
+        let written: Vec<&str> = erc20
+            .files
+            .iter()
+            .map(|f| f.path.as_str())
+            .filter(|p| is_source_file(p))
+            .collect();
+        assert_eq!(
+            written,
+            [
+                "src/lib.rs",
+                "src/test_support.rs",
+                "tests/golden/transfer_instruction.hex"
+            ]
+        );
+    }
+}
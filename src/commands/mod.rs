@@ -1,5 +1,24 @@
 //! Command implementations for cargo-tako
 
+pub mod addresses;
+pub mod asm;
 pub mod build;
+pub mod check;
+pub mod ci;
+pub mod clang;
+pub mod debug;
+pub mod docs;
+pub mod env;
+pub mod explain;
+pub mod hexdump;
+pub mod ide;
 pub mod init;
+pub mod rename;
+pub mod sdk;
+pub mod solana;
+pub mod storage;
+pub mod symbolize;
 pub mod test;
+pub mod tree;
+pub mod vendor;
+pub mod version;
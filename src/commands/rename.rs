@@ -0,0 +1,284 @@
+//! Rename command implementation
+//!
+//! Renames a contract project: the package and lib names in Cargo.toml, the
+//! package name in Tako.toml, identifiers derived from the name by templates
+//! (`MyToken`, `my_token`, `my-token`) in sources and READMEs, and the
+//! contract's entries in the address book.
+
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::DocumentMut;
+use walkdir::WalkDir;
+
+use crate::address_book::AddressBook;
+use crate::commands::init;
+use crate::config::find_config_file;
+use crate::error::{Error, Result};
+use crate::style::Styled;
+use crate::template::{to_kebab_case, to_pascal_case, to_snake_case};
+
+/// Directories searched for sources and documentation
+const SOURCE_DIRS: &[&str] = &["src", "tests", "examples", "benches"];
+
+/// The spellings of a project name used by templates
+struct Names {
+    pascal: String,
+    snake: String,
+    kebab: String,
+}
+
+impl Names {
+    fn new(name: &str) -> Self {
+        Self {
+            pascal: to_pascal_case(name),
+            snake: to_snake_case(name),
+            kebab: to_kebab_case(name),
+        }
+    }
+
+    /// Whether the name has several words, making its snake and kebab forms
+    /// distinctive enough to replace outside of paths
+    fn is_compound(&self) -> bool {
+        self.snake.contains('_')
+    }
+}
+
+/// Rename the contract project in the current directory
+///
+/// # Arguments
+/// * `new_name` - New project name (any of PascalCase, snake_case or kebab-case)
+/// * `dry_run` - Show the changes without writing them
+pub fn rename(new_name: &str, dry_run: bool) -> Result<()> {
+    if new_name.is_empty()
+        || !new_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(Error::Other(format!("Invalid project name '{new_name}'")));
+    }
+
+    let manifest_path = Path::new("Cargo.toml");
+    let manifest = fs::read_to_string(manifest_path)?;
+    let mut doc: DocumentMut = manifest
+        .parse()
+        .map_err(|e| Error::Config(format!("Cargo.toml: {e}")))?;
+    let old_name = doc
+        .get("package")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .ok_or_else(|| Error::Config("Cargo.toml has no package name".to_string()))?
+        .to_string();
+
+    let old = Names::new(&old_name);
+    let new = Names::new(new_name);
+    if old.kebab == new.kebab {
+        return Err(Error::Other(format!("Project is already named {old_name}")));
+    }
+
+    let current_dir = std::env::current_dir()?;
+    let mut edits: Vec<(PathBuf, String, String)> = Vec::new();
+
+    doc["package"]["name"] = toml_edit::value(&new.kebab);
+    if let Some(lib) = doc.get_mut("lib").and_then(|l| l.as_table_like_mut()) {
+        if lib.contains_key("name") {
+            lib.insert("name", toml_edit::value(&new.snake));
+        }
+    }
+    edits.push((manifest_path.to_path_buf(), manifest, doc.to_string()));
+
+    if let Some(config_path) = find_config_file(&current_dir) {
+        let config = fs::read_to_string(&config_path)?;
+        let mut doc: DocumentMut = config
+            .parse()
+            .map_err(|e| Error::Config(format!("{}: {e}", config_path.display())))?;
+        let package = doc.get_mut("package").and_then(|p| p.as_table_like_mut());
+        if let Some(package) = package {
+            if let Some(renamed) = package
+                .get("name")
+                .and_then(|n| n.as_str())
+                .and_then(|name| rename_variant(name, &old, &new))
+            {
+                package.insert("name", toml_edit::value(renamed));
+                edits.push((config_path, config, doc.to_string()));
+            }
+        }
+    }
+
+    for path in source_files() {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let renamed = rename_identifiers(&content, &old, &new, is_markdown(&path));
+        if renamed != content {
+            edits.push((path, content, renamed));
+        }
+    }
+
+    let mut book = AddressBook::load()?;
+    let mut renamed_entries = Vec::new();
+    for (network, contracts) in book.networks.iter_mut() {
+        let names: Vec<String> = contracts.keys().cloned().collect();
+        for name in names {
+            if let Some(renamed) = rename_variant(&name, &old, &new) {
+                if let Some(address) = contracts.remove(&name) {
+                    contracts.insert(renamed.clone(), address);
+                    renamed_entries.push(format!("{network}: {name} -> {renamed}"));
+                }
+            }
+        }
+    }
+
+    println!("  {} -> {}", old_name, new.kebab.bold());
+    println!();
+    for (path, old_content, new_content) in &edits {
+        let name = path
+            .strip_prefix(&current_dir)
+            .or_else(|_| path.strip_prefix("."))
+            .unwrap_or(path)
+            .display()
+            .to_string();
+        if dry_run {
+            init::print_diff(&name, old_content, new_content);
+        } else {
+            fs::write(path, new_content)?;
+            println!("  {} {name}", "update".accent());
+        }
+    }
+    for entry in &renamed_entries {
+        println!("  {} Tako.lock ({entry})", "update".accent());
+    }
+
+    if !dry_run {
+        if !renamed_entries.is_empty() {
+            book.save()?;
+        }
+        println!();
+        println!("{} Renamed to {}", "✓".success(), new.kebab);
+        println!("Run `cargo tako clean` to drop artifacts built under the old name");
+    }
+    Ok(())
+}
+
+/// The new name in the same spelling as `name`, if `name` is a spelling of the old one
+fn rename_variant(name: &str, old: &Names, new: &Names) -> Option<String> {
+    if name == old.kebab {
+        Some(new.kebab.clone())
+    } else if name == old.snake {
+        Some(new.snake.clone())
+    } else if name == old.pascal {
+        Some(new.pascal.clone())
+    } else {
+        None
+    }
+}
+
+/// Sources and documentation of the project
+fn source_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(".")
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.is_file() && is_markdown(path))
+        .collect();
+    for dir in SOURCE_DIRS {
+        files.extend(
+            WalkDir::new(dir)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+                .map(|e| e.into_path())
+                .filter(|path| {
+                    path.extension().is_some_and(|ext| ext == "rs") || is_markdown(path)
+                }),
+        );
+    }
+    files
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "md")
+}
+
+/// Replace the spellings of the old name in a source or markdown file
+///
+/// The PascalCase type name is always replaced. A single-word name's
+/// snake_case form is usually also an ordinary identifier (`counter`), so it
+/// is only replaced as a crate path (`counter::`); compound names are
+/// replaced everywhere, and in markdown so is their kebab-case form.
+fn rename_identifiers(text: &str, old: &Names, new: &Names, markdown: bool) -> String {
+    let mut text = replace_identifier(text, &old.pascal, &new.pascal, "");
+    if old.is_compound() {
+        text = replace_identifier(&text, &old.snake, &new.snake, "");
+        if markdown {
+            text = replace_identifier(&text, &old.kebab, &new.kebab, "");
+        }
+    } else {
+        text = replace_identifier(&text, &old.snake, &new.snake, "::");
+    }
+    text
+}
+
+/// Replace `from` where it is a whole identifier, or where it is followed by
+/// `suffix` if one is given
+fn replace_identifier(text: &str, from: &str, to: &str, suffix: &str) -> String {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for (i, _) in text.match_indices(from) {
+        let end = i + from.len();
+        let before = text[..i].chars().next_back();
+        let after = &text[end..];
+        let bounded = if suffix.is_empty() {
+            !after.chars().next().is_some_and(is_ident)
+        } else {
+            after.starts_with(suffix)
+        };
+        if before.is_some_and(is_ident) || !bounded {
+            continue;
+        }
+        out.push_str(&text[last..i]);
+        out.push_str(to);
+        last = end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_identifiers() {
+        let old = Names::new("my-token");
+        let new = Names::new("gold-coin");
+        let source = "use my_token::MyToken;\nlet my_token = MyToken::new(); // MyTokenV2\n";
+        assert_eq!(
+            rename_identifiers(source, &old, &new, false),
+            "use gold_coin::GoldCoin;\nlet gold_coin = GoldCoin::new(); // MyTokenV2\n"
+        );
+        assert_eq!(
+            rename_identifiers("# MyToken\n\nmy-token.so\n", &old, &new, true),
+            "# GoldCoin\n\ngold-coin.so\n"
+        );
+
+        let old = Names::new("counter");
+        let new = Names::new("tally");
+        assert_eq!(
+            rename_identifiers(
+                "use counter::Counter;\nlet counter = 1;\n",
+                &old,
+                &new,
+                false
+            ),
+            "use tally::Tally;\nlet counter = 1;\n"
+        );
+
+        assert_eq!(
+            rename_variant("my_token", &Names::new("my-token"), &new),
+            Some("tally".to_string())
+        );
+    }
+}
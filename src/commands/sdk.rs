@@ -0,0 +1,277 @@
+//! SDK command implementation
+//!
+//! Pins the project's TAKO SDK crates (git dependencies on the tako
+//! repository) to one release tag, so all of them move together and builds
+//! stop following the repository's default branch. Without `--tag` the
+//! newest release in the compatibility table (compat.toml) that the
+//! installed platform-tools supports is chosen.
+
+use std::fs;
+use std::path::Path;
+use toml_edit::{DocumentMut, TableLike};
+
+use crate::commands::init;
+use crate::compat::{CompatTable, SdkCompat};
+use crate::error::{Error, Result};
+use crate::style::Styled;
+use crate::template::TAKO_SDK_REPO;
+use crate::toolchain::{find_platform_tools, DEFAULT_PLATFORM_TOOLS_VERSION};
+
+/// Keys selecting the git revision of a dependency
+const REF_KEYS: &[&str] = &["tag", "rev", "branch"];
+
+/// A pinned SDK dependency: (section, name, previous `key = value` or "default branch")
+type Pinned = (String, String, String);
+
+/// Pin the TAKO SDK crates in Cargo.toml to a release tag
+///
+/// # Arguments
+/// * `tag` - Tag to pin to (defaults to the newest compatible release in the
+///   compatibility table)
+/// * `dry_run` - Show the change to Cargo.toml without writing it
+pub fn update_sdk(tag: Option<&str>, dry_run: bool) -> Result<()> {
+    let path = Path::new("Cargo.toml");
+    let content = fs::read_to_string(path)?;
+    let mut doc: DocumentMut = content
+        .parse()
+        .map_err(|e| Error::Config(format!("Cargo.toml: {e}")))?;
+
+    let table = CompatTable::load();
+    let tag = match tag {
+        Some(tag) => tag.to_string(),
+        None => newest_compatible_tag(&table)?,
+    };
+
+    let pinned = pin_sdk(&mut doc, &tag);
+    if pinned.is_empty() {
+        return Err(Error::Config(format!(
+            "No git dependencies on {TAKO_SDK_REPO} found in Cargo.toml"
+        )));
+    }
+
+    for (section, name, previous) in &pinned {
+        println!("  {name} ({section}): {previous} -> tag {tag}");
+    }
+
+    let mut previous: Vec<&str> = pinned
+        .iter()
+        .filter_map(|(_, _, p)| p.split_once(" = ").map(|(_, value)| value))
+        .filter(|value| *value != tag)
+        .collect();
+    previous.sort();
+    previous.dedup();
+    println!();
+    match table.find(&tag) {
+        Some(entry) => {
+            for line in requirement_changes(&previous, &table, entry) {
+                println!("{line}");
+            }
+        }
+        None => println!(
+            "{} {tag} is not in the compatibility table; run `cargo tako compat --refresh`",
+            "warning:".warning()
+        ),
+    }
+    println!("Release notes: {TAKO_SDK_REPO}/releases/tag/{tag}");
+    for from in previous {
+        println!("Changes since {from}: {TAKO_SDK_REPO}/compare/{from}...{tag}");
+    }
+
+    let updated = doc.to_string();
+    if dry_run {
+        println!();
+        init::print_diff("Cargo.toml", &content, &updated);
+        return Ok(());
+    }
+    if updated != content {
+        fs::write(path, updated)?;
+        println!();
+        println!("{} Cargo.toml updated", "✓".success());
+    }
+    Ok(())
+}
+
+/// Pin every SDK dependency in the manifest to `tag`
+fn pin_sdk(doc: &mut DocumentMut, tag: &str) -> Vec<Pinned> {
+    let mut pinned = Vec::new();
+    for section in ["dependencies", "dev-dependencies", "build-dependencies"] {
+        if let Some(deps) = doc.get_mut(section).and_then(|d| d.as_table_like_mut()) {
+            pin_table(deps, section, tag, &mut pinned);
+        }
+    }
+    let workspace_deps = doc
+        .get_mut("workspace")
+        .and_then(|w| w.get_mut("dependencies"))
+        .and_then(|d| d.as_table_like_mut());
+    if let Some(deps) = workspace_deps {
+        pin_table(deps, "workspace.dependencies", tag, &mut pinned);
+    }
+    pinned
+}
+
+fn pin_table(deps: &mut dyn TableLike, section: &str, tag: &str, pinned: &mut Vec<Pinned>) {
+    for (name, dep) in deps.iter_mut() {
+        let Some(dep) = dep.as_table_like_mut() else {
+            continue;
+        };
+        let is_sdk = dep
+            .get("git")
+            .and_then(|git| git.as_str())
+            .is_some_and(|git| git.trim_end_matches('/').trim_end_matches(".git") == TAKO_SDK_REPO);
+        if !is_sdk {
+            continue;
+        }
+
+        let previous = REF_KEYS
+            .iter()
+            .find_map(|key| {
+                let value = dep.get(key)?.as_str()?;
+                Some(format!("{key} = {value}"))
+            })
+            .unwrap_or_else(|| "default branch".to_string());
+        for key in REF_KEYS {
+            dep.remove(key);
+        }
+        dep.insert("tag", toml_edit::value(tag));
+        pinned.push((section.to_string(), name.get().to_string(), previous));
+    }
+}
+
+/// Newest release in the compatibility table the installed platform-tools
+/// and this cargo-tako support
+fn newest_compatible_tag(table: &CompatTable) -> Result<String> {
+    let tools = find_platform_tools(Some(DEFAULT_PLATFORM_TOOLS_VERSION));
+    let found = tools.as_ref().map(|tools| tools.version.as_str());
+    if let Some(entry) = table.newest_compatible(found, env!("CARGO_PKG_VERSION")) {
+        return Ok(entry.version.clone());
+    }
+    Err(Error::Config(format!(
+        "No SDK release in the compatibility table supports platform-tools {} and cargo-tako {}.\nRun `cargo tako compat --refresh`, install a newer platform-tools, or pass --tag to pin a specific ref",
+        found.unwrap_or("(not installed)"),
+        env!("CARGO_PKG_VERSION")
+    )))
+}
+
+/// Summary of what the new release requires, and which requirements changed
+/// since the previously pinned refs that are in the table
+fn requirement_changes(previous: &[&str], table: &CompatTable, entry: &SdkCompat) -> Vec<String> {
+    let mut lines = vec![format!(
+        "SDK {} requires platform-tools {} or newer{}, archs: {}",
+        entry.version,
+        entry.platform_tools,
+        entry
+            .cargo_tako
+            .as_ref()
+            .map(|v| format!(", cargo-tako {v} or newer"))
+            .unwrap_or_default(),
+        entry.archs.join(", ")
+    )];
+    for from in previous.iter().filter_map(|p| table.find(p)) {
+        if from.platform_tools != entry.platform_tools {
+            lines.push(format!(
+                "  platform-tools: {} ({}) -> {}",
+                from.platform_tools, from.version, entry.platform_tools
+            ));
+        }
+        if from.cargo_tako != entry.cargo_tako {
+            let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "any".to_string());
+            lines.push(format!(
+                "  cargo-tako: {} ({}) -> {}",
+                show(&from.cargo_tako),
+                from.version,
+                show(&entry.cargo_tako)
+            ));
+        }
+        let added: Vec<&str> = entry
+            .archs
+            .iter()
+            .filter(|a| !from.archs.contains(a))
+            .map(String::as_str)
+            .collect();
+        let removed: Vec<&str> = from
+            .archs
+            .iter()
+            .filter(|a| !entry.archs.contains(a))
+            .map(String::as_str)
+            .collect();
+        if !added.is_empty() {
+            lines.push(format!(
+                "  archs added since {}: {}",
+                from.version,
+                added.join(", ")
+            ));
+        }
+        if !removed.is_empty() {
+            lines.push(format!(
+                "  archs dropped since {}: {}",
+                from.version,
+                removed.join(", ")
+            ));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_sdk() {
+        let mut doc: DocumentMut = r#"[dependencies]
+tako-macros = { git = "https://github.com/tos-network/tako", branch = "main" }
+serde = "1"
+
+[dev-dependencies.tako-test]
+git = "https://github.com/tos-network/tako.git"
+"#
+        .parse()
+        .unwrap();
+
+        let pinned = pin_sdk(&mut doc, "v0.2.0");
+        assert_eq!(pinned.len(), 2);
+        assert_eq!(pinned[0].2, "branch = main");
+        assert_eq!(pinned[1].2, "default branch");
+        assert_eq!(
+            doc.to_string(),
+            r#"[dependencies]
+tako-macros = { git = "https://github.com/tos-network/tako", tag = "v0.2.0" }
+serde = "1"
+
+[dev-dependencies.tako-test]
+git = "https://github.com/tos-network/tako.git"
+tag = "v0.2.0"
+"#
+        );
+    }
+
+    #[test]
+    fn test_requirement_changes() {
+        let table: CompatTable = toml::from_str(
+            r#"
+[[sdk]]
+version = "v0.1.0"
+platform-tools = "v1.50"
+archs = ["v0", "v3"]
+
+[[sdk]]
+version = "v0.2.0"
+platform-tools = "v1.52"
+archs = ["v3", "v4"]
+cargo-tako = "0.3.1"
+"#,
+        )
+        .unwrap();
+        let entry = table.find("v0.2.0").unwrap();
+        assert_eq!(
+            requirement_changes(&["v0.1.0", "main"], &table, entry),
+            [
+                "SDK v0.2.0 requires platform-tools v1.52 or newer, cargo-tako 0.3.1 or newer, archs: v3, v4",
+                "  platform-tools: v1.50 (v0.1.0) -> v1.52",
+                "  cargo-tako: any (v0.1.0) -> 0.3.1",
+                "  archs added since v0.1.0: v4",
+                "  archs dropped since v0.1.0: v0",
+            ]
+        );
+    }
+}
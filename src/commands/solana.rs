@@ -0,0 +1,263 @@
+//! Helpers for projects migrating from Solana
+//!
+//! `cargo tako build --sbf-compat` accepts the flag spellings of
+//! cargo-build-sbf, and `cargo tako migrate-from-solana` moves a program's
+//! Cargo.toml onto the TAKO SDK crates. Neither translates program code: the
+//! account model, CPI and Solana syscalls have no TAKO counterpart, so the
+//! migration lists what has to be ported by hand.
+
+use std::fs;
+use std::path::Path;
+use toml_edit::{DocumentMut, InlineTable, Item, TableLike};
+
+use crate::commands::{build, init};
+use crate::error::{Error, Result};
+use crate::style::Styled;
+use crate::template::{TAKO_SDK_REF_KIND, TAKO_SDK_REPO, TAKO_SDK_VERSION};
+
+/// Solana crates and their TAKO replacement, with what changes for the port
+const CRATE_MAPPINGS: &[(&str, Option<&str>, &str)] = &[
+    (
+        "solana-program",
+        Some("tako-macros"),
+        "entrypoint and program macros; replace AccountInfo-based processing with contract methods",
+    ),
+    (
+        "anchor-lang",
+        Some("tako-macros"),
+        "#[program] modules become contract impls; #[account] state moves to tako-storage containers",
+    ),
+    (
+        "anchor-spl",
+        None,
+        "no SPL programs on TAKO; start from `cargo tako new --template erc20` for tokens",
+    ),
+    (
+        "spl-token",
+        None,
+        "no SPL programs on TAKO; start from `cargo tako new --template erc20` for tokens",
+    ),
+    (
+        "spl-associated-token-account",
+        None,
+        "no associated token accounts on TAKO; balances live in the token contract's storage",
+    ),
+    (
+        "solana-sdk",
+        None,
+        "client-side crate; no TAKO equivalent in this toolchain",
+    ),
+    (
+        "solana-program-test",
+        None,
+        "use the test_support TestContext generated by `cargo tako init`",
+    ),
+];
+
+/// Map a cargo-build-sbf `--arch` value to a TBPF architecture version
+///
+/// Returns the version and a note when the mapping is not one to one.
+pub fn map_arch(arch: &str) -> Result<(String, Option<&'static str>)> {
+    match arch {
+        "v0" | "v1" | "v2" | "v3" | "v4" => Ok((arch.to_string(), None)),
+        "sbfv1" => Ok(("v1".to_string(), None)),
+        "sbfv2" => Ok((
+            "v2".to_string(),
+            Some(
+                "TBPF v2 follows SBPF v2 instruction semantics, but TAKO syscalls replace Solana's",
+            ),
+        )),
+        "sbfv3" => Ok(("v3".to_string(), None)),
+        "sbf" | "bpf" => Ok((
+            "v0".to_string(),
+            Some("legacy sbf/bpf binaries map to TBPF v0; v3 is recommended for new deployments"),
+        )),
+        other => Err(Error::Config(format!(
+            "Unknown architecture '{other}' (expected v0-v4, sbfv1-sbfv3, sbf or bpf)"
+        ))),
+    }
+}
+
+/// Print how a cargo-build-sbf invocation differs from the TAKO build
+pub fn print_build_notes(arch: &str, note: Option<&str>, sbf_out_dir: bool) {
+    println!(
+        "  {} building with TBPF {arch} (target {}) in cargo-build-sbf compatibility mode",
+        "note:".accent(),
+        build::get_target_triple(arch)
+    );
+    if let Some(note) = note {
+        println!("  {} {note}", "note:".accent());
+    }
+    if sbf_out_dir {
+        println!(
+            "  {} --sbf-out-dir receives the .so only; no program keypair is generated",
+            "warning:".warning()
+        );
+    }
+}
+
+/// Rewrite Cargo.toml from Solana crates to the TAKO SDK
+///
+/// # Arguments
+/// * `dry_run` - Show the change to Cargo.toml without writing it
+pub fn migrate_from_solana(dry_run: bool) -> Result<()> {
+    let path = Path::new("Cargo.toml");
+    let content = fs::read_to_string(path)?;
+    let mut doc: DocumentMut = content
+        .parse()
+        .map_err(|e| Error::Config(format!("Cargo.toml: {e}")))?;
+
+    let findings = migrate_manifest(&mut doc);
+    if findings.is_empty() {
+        println!("No Solana dependencies found in Cargo.toml");
+        return Ok(());
+    }
+
+    for (section, name, replacement, note) in &findings {
+        match replacement {
+            Some(tako) => println!("  {name} ({section}) -> {tako}: {note}"),
+            None => println!("  {} {name} ({section}): {note}", "manual:".warning()),
+        }
+    }
+    println!();
+    println!("Program code is not rewritten; port uses of the replaced crates by hand");
+    println!("and run `cargo tako check` to find remaining Solana-only dependencies.");
+
+    let updated = doc.to_string();
+    if dry_run {
+        println!();
+        init::print_diff("Cargo.toml", &content, &updated);
+        return Ok(());
+    }
+    if updated != content {
+        fs::write(path, updated)?;
+        println!();
+        println!("{} Cargo.toml updated", "✓".success());
+    }
+    Ok(())
+}
+
+/// (section, crate, TAKO replacement, note)
+type Finding = (String, String, Option<String>, &'static str);
+
+/// Replace mapped Solana crates with TAKO SDK crates and set the crate type
+fn migrate_manifest(doc: &mut DocumentMut) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for section in ["dependencies", "dev-dependencies"] {
+        let Some(deps) = doc.get_mut(section).and_then(|d| d.as_table_like_mut()) else {
+            continue;
+        };
+        migrate_table(deps, section, &mut findings);
+    }
+
+    // State containers replace Anchor accounts
+    let uses_anchor = findings.iter().any(|(_, name, _, _)| name == "anchor-lang");
+    if uses_anchor {
+        if let Some(deps) = doc
+            .get_mut("dependencies")
+            .and_then(|d| d.as_table_like_mut())
+        {
+            if !deps.contains_key("tako-storage") {
+                deps.insert("tako-storage", sdk_dependency());
+                findings.push((
+                    "dependencies".to_string(),
+                    "anchor-lang".to_string(),
+                    Some("tako-storage".to_string()),
+                    "storage containers (Value, Mapping) for former #[account] state",
+                ));
+            }
+        }
+    }
+
+    // TBPF programs are loaded as shared objects
+    if !findings.is_empty() {
+        let lib = doc
+            .entry("lib")
+            .or_insert(toml_edit::table())
+            .as_table_like_mut();
+        if let Some(lib) = lib {
+            let has_cdylib = lib
+                .get("crate-type")
+                .and_then(|t| t.as_array())
+                .is_some_and(|types| types.iter().any(|t| t.as_str() == Some("cdylib")));
+            if !has_cdylib {
+                let mut types = toml_edit::Array::new();
+                types.push("cdylib");
+                types.push("lib");
+                lib.insert("crate-type", toml_edit::value(types));
+            }
+        }
+    }
+    findings
+}
+
+fn migrate_table(deps: &mut dyn TableLike, section: &str, findings: &mut Vec<Finding>) {
+    for (solana, tako, note) in CRATE_MAPPINGS {
+        if !deps.contains_key(solana) {
+            continue;
+        }
+        match tako {
+            Some(tako) => {
+                deps.remove(solana);
+                if !deps.contains_key(tako) {
+                    deps.insert(tako, sdk_dependency());
+                }
+                findings.push((
+                    section.to_string(),
+                    solana.to_string(),
+                    Some(tako.to_string()),
+                    note,
+                ));
+            }
+            None => findings.push((section.to_string(), solana.to_string(), None, note)),
+        }
+    }
+}
+
+/// Git dependency on the TAKO SDK at the revision the templates use
+fn sdk_dependency() -> Item {
+    let mut dep = InlineTable::new();
+    dep.insert("git", TAKO_SDK_REPO.into());
+    dep.insert(TAKO_SDK_REF_KIND, TAKO_SDK_VERSION.into());
+    toml_edit::value(dep)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_manifest() {
+        let mut doc: DocumentMut = r#"[package]
+name = "vault"
+
+[lib]
+crate-type = ["cdylib", "lib"]
+
+[dependencies]
+anchor-lang = "0.30"
+anchor-spl = "0.30"
+borsh = "1"
+"#
+        .parse()
+        .unwrap();
+
+        let findings = migrate_manifest(&mut doc);
+        let manifest = doc.to_string();
+        assert!(!manifest.contains("anchor-lang"));
+        assert!(manifest.contains("anchor-spl = \"0.30\""));
+        assert!(manifest.contains(&format!(
+            "tako-macros = {{ git = \"{TAKO_SDK_REPO}\", {TAKO_SDK_REF_KIND} = \"{TAKO_SDK_VERSION}\" }}"
+        )));
+        assert!(manifest.contains("tako-storage = "));
+        let manual: Vec<&str> = findings
+            .iter()
+            .filter(|(_, _, tako, _)| tako.is_none())
+            .map(|(_, name, _, _)| name.as_str())
+            .collect();
+        assert_eq!(manual, ["anchor-spl"]);
+
+        assert_eq!(map_arch("sbfv2").unwrap().0, "v2");
+        assert!(map_arch("sbfv9").is_err());
+    }
+}
@@ -0,0 +1,544 @@
+//! Storage layout command implementation
+//!
+//! TAKO storage containers address each field by a string key
+//! (`Value::new("count")`), so a contract's storage layout is the set of keys
+//! together with the container kind and value types stored under them. An
+//! upgrade is compatible as long as no existing key is reinterpreted with a
+//! different container or type.
+
+use crate::error::{Error, Result};
+use quote::ToTokens;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use walkdir::WalkDir;
+
+/// Storage container types provided by tako-storage
+const STORAGE_CONTAINERS: &[&str] = &["Value", "Mapping"];
+
+/// Crates whose paths to prelude types are spelled out in some sources
+/// (`core::primitive::u64`, `alloc::vec::Vec<u8>`)
+const PRELUDE_CRATES: &[&str] = &["core", "std", "alloc"];
+
+/// Types that are compared by their last path segment when named through
+/// one of [`PRELUDE_CRATES`]
+const PRELUDE_TYPES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize", "bool",
+    "char", "str", "f32", "f64", "String", "Vec", "Option", "Result", "Box",
+];
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StorageLayout {
+    pub structs: Vec<StorageStruct>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StorageStruct {
+    pub name: String,
+    pub file: String,
+    pub fields: Vec<StorageField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageField {
+    pub name: String,
+    /// Container kind (Value, Mapping)
+    pub container: String,
+    /// Generic arguments of the container, e.g. ["Address", "u64"]
+    pub type_args: Vec<String>,
+    /// Storage key passed to the container constructor, if found
+    pub key: Option<String>,
+}
+
+impl StorageField {
+    /// Key under which the field is stored (falls back to the field name)
+    fn storage_key(&self) -> &str {
+        self.key.as_deref().unwrap_or(&self.name)
+    }
+
+    fn type_string(&self) -> String {
+        format!("{}<{}>", self.container, self.type_args.join(", "))
+    }
+}
+
+/// Extract the storage layout from all Rust sources under `src_dir`
+pub fn extract_layout(src_dir: &Path) -> Result<StorageLayout> {
+    let mut layout = StorageLayout::default();
+
+    for entry in WalkDir::new(src_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
+    {
+        let content = fs::read_to_string(entry.path())?;
+        let file = syn::parse_file(&content).map_err(|e| {
+            Error::Other(format!("Failed to parse {}: {e}", entry.path().display()))
+        })?;
+
+        let mut collector = LayoutCollector::default();
+        collector.visit_file(&file);
+
+        for (name, mut fields) in collector.structs {
+            for field in &mut fields {
+                field.key = collector
+                    .keys
+                    .get(&(name.clone(), field.name.clone()))
+                    .cloned();
+            }
+            layout.structs.push(StorageStruct {
+                name,
+                file: entry.path().display().to_string(),
+                fields,
+            });
+        }
+    }
+
+    Ok(layout)
+}
+
+/// Collects storage structs and the keys their fields are constructed with
+#[derive(Default)]
+struct LayoutCollector {
+    structs: Vec<(String, Vec<StorageField>)>,
+    /// (struct name, field name) -> storage key
+    keys: BTreeMap<(String, String), String>,
+    current_impl: Option<String>,
+}
+
+impl<'ast> Visit<'ast> for LayoutCollector {
+    fn visit_item_struct(&mut self, item: &'ast syn::ItemStruct) {
+        let fields: Vec<StorageField> = item
+            .fields
+            .iter()
+            .filter_map(|f| {
+                let name = f.ident.as_ref()?.to_string();
+                let (container, type_args) = container_type(&f.ty)?;
+                Some(StorageField {
+                    name,
+                    container,
+                    type_args,
+                    key: None,
+                })
+            })
+            .collect();
+
+        if !fields.is_empty() {
+            self.structs.push((item.ident.to_string(), fields));
+        }
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        let previous = self.current_impl.take();
+        if let syn::Type::Path(p) = item.self_ty.as_ref() {
+            self.current_impl = p.path.segments.last().map(|s| s.ident.to_string());
+        }
+        syn::visit::visit_item_impl(self, item);
+        self.current_impl = previous;
+    }
+
+    fn visit_expr_struct(&mut self, expr: &'ast syn::ExprStruct) {
+        let struct_name = match expr.path.segments.last() {
+            Some(s) if s.ident == "Self" => self.current_impl.clone(),
+            Some(s) => Some(s.ident.to_string()),
+            None => None,
+        };
+
+        if let Some(struct_name) = struct_name {
+            for field in &expr.fields {
+                if let (syn::Member::Named(ident), Some(key)) =
+                    (&field.member, constructor_key(&field.expr))
+                {
+                    self.keys
+                        .insert((struct_name.clone(), ident.to_string()), key);
+                }
+            }
+        }
+
+        syn::visit::visit_expr_struct(self, expr);
+    }
+}
+
+/// Match `Value<T>` / `Mapping<K, V>` and return the container name and type arguments
+fn container_type(ty: &syn::Type) -> Option<(String, Vec<String>)> {
+    let syn::Type::Path(p) = ty else {
+        return None;
+    };
+    let segment = p.path.segments.last()?;
+    let container = segment.ident.to_string();
+    if !STORAGE_CONTAINERS.contains(&container.as_str()) {
+        return None;
+    }
+
+    let type_args = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .map(|a| normalize_type(&a.to_token_stream().to_string()))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Some((container, type_args))
+}
+
+/// Canonical spelling of a type argument, so layouts compare by type rather
+/// than by how it was written: whitespace is dropped and prelude types named
+/// by path (`core::primitive::u64`, `::std::vec::Vec<u8>`) become their
+/// plain name
+fn normalize_type(ty: &str) -> String {
+    let canonical = match syn::parse_str::<syn::Type>(ty) {
+        Ok(mut parsed) => {
+            PreludePaths.visit_type_mut(&mut parsed);
+            parsed.to_token_stream().to_string()
+        }
+        Err(_) => ty.to_string(),
+    };
+    canonical.split_whitespace().collect()
+}
+
+/// Shortens paths to prelude types wherever they appear in a type
+struct PreludePaths;
+
+impl VisitMut for PreludePaths {
+    fn visit_type_path_mut(&mut self, ty: &mut syn::TypePath) {
+        let segments = &ty.path.segments;
+        let is_prelude = ty.qself.is_none()
+            && segments.len() > 1
+            && PRELUDE_CRATES.iter().any(|c| segments[0].ident == c)
+            && PRELUDE_TYPES
+                .iter()
+                .any(|t| segments[segments.len() - 1].ident == t);
+        if is_prelude {
+            let last = ty.path.segments.pop().map(|pair| pair.into_value());
+            ty.path.segments.clear();
+            ty.path.segments.extend(last);
+            ty.path.leading_colon = None;
+        }
+        syn::visit_mut::visit_type_path_mut(self, ty);
+    }
+}
+
+/// Match `Value::new("key")` / `Mapping::new("key")` and return the key
+fn constructor_key(expr: &syn::Expr) -> Option<String> {
+    let syn::Expr::Call(call) = expr else {
+        return None;
+    };
+    let syn::Expr::Path(func) = call.func.as_ref() else {
+        return None;
+    };
+
+    let segments: Vec<String> = func
+        .path
+        .segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect();
+    match segments.as_slice() {
+        [.., container, new]
+            if new == "new" && STORAGE_CONTAINERS.contains(&container.as_str()) => {}
+        _ => return None,
+    }
+
+    match call.args.first()? {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Str(s),
+            ..
+        }) => Some(s.value()),
+        _ => None,
+    }
+}
+
+/// Print the storage layout of the current project, optionally writing it as JSON
+pub fn show_layout(output: Option<&str>) -> Result<()> {
+    let layout = extract_layout(Path::new("src"))?;
+
+    if layout.structs.is_empty() {
+        println!("No storage containers found in src/");
+    }
+
+    for s in &layout.structs {
+        println!("{} ({})", s.name, s.file);
+        for f in &s.fields {
+            let key = match &f.key {
+                Some(k) => format!("\"{k}\""),
+                None => "(key not found, assuming field name)".to_string(),
+            };
+            println!("  {:<24} {:<32} key {}", f.name, f.type_string(), key);
+        }
+    }
+
+    if let Some(path) = output {
+        let content = serde_json::to_string_pretty(&layout)
+            .map_err(|e| Error::Other(format!("Failed to serialize layout: {e}")))?;
+        fs::write(path, content)?;
+        println!();
+        println!("✓ Layout written to {path}");
+    }
+
+    Ok(())
+}
+
+/// Check that the current layout can safely read data written with `against`
+///
+/// - Same key with a different container or type: incompatible (data would be reinterpreted)
+/// - Same key used by two fields: incompatible
+/// - Key removed: warning (existing data is orphaned, not misread)
+/// - Key added: compatible
+pub fn check_layout(against: &str) -> Result<()> {
+    let content = fs::read_to_string(against)?;
+    let old: StorageLayout = serde_json::from_str(&content)
+        .map_err(|e| Error::Other(format!("Invalid layout file {against}: {e}")))?;
+    let new = extract_layout(Path::new("src"))?;
+
+    let diff = compare_layouts(&old, &new);
+    for removed in &diff.removed {
+        println!("  warning: {removed}");
+    }
+    for added in &diff.added {
+        println!("  added: {added}");
+    }
+
+    if !diff.problems.is_empty() {
+        for p in &diff.problems {
+            eprintln!("  error: {p}");
+        }
+        return Err(Error::Other(format!(
+            "Storage layout is incompatible with {against} ({} problem(s))",
+            diff.problems.len()
+        )));
+    }
+
+    println!("✓ Storage layout is compatible with {against}");
+    Ok(())
+}
+
+/// Differences between a previous and the current storage layout
+#[derive(Debug, Default)]
+struct LayoutDiff {
+    /// Keys reinterpreted or used twice; the upgrade is unsafe
+    problems: Vec<String>,
+    /// Keys no longer used
+    removed: Vec<String>,
+    /// Keys new in the current layout
+    added: Vec<String>,
+}
+
+/// Compare layouts by storage key, so renamed, reordered or moved fields are
+/// compatible as long as each key keeps its container and types
+fn compare_layouts(old: &StorageLayout, new: &StorageLayout) -> LayoutDiff {
+    let mut diff = LayoutDiff::default();
+    let old_keys = index_by_key(old, &mut Vec::new());
+    let new_keys = index_by_key(new, &mut diff.problems);
+
+    for (key, (old_struct, old_field)) in &old_keys {
+        match new_keys.get(key) {
+            None => diff.removed.push(format!(
+                "key \"{key}\" ({old_struct}.{}) removed; existing data will be orphaned",
+                old_field.name
+            )),
+            Some((new_struct, new_field)) if !same_type(old_field, new_field) => {
+                diff.problems.push(format!(
+                    "key \"{key}\" changed from {} ({old_struct}.{}) to {} ({new_struct}.{})",
+                    old_field.type_string(),
+                    old_field.name,
+                    new_field.type_string(),
+                    new_field.name
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+
+    for (key, (new_struct, new_field)) in &new_keys {
+        if !old_keys.contains_key(key) {
+            diff.added.push(format!(
+                "key \"{key}\" {} ({new_struct}.{})",
+                new_field.type_string(),
+                new_field.name
+            ));
+        }
+    }
+    diff
+}
+
+/// Whether two fields store the same container and types; layouts written
+/// by older versions may spell types differently
+fn same_type(old: &StorageField, new: &StorageField) -> bool {
+    old.container == new.container
+        && old.type_args.len() == new.type_args.len()
+        && old
+            .type_args
+            .iter()
+            .zip(&new.type_args)
+            .all(|(a, b)| normalize_type(a) == normalize_type(b))
+}
+
+/// Index fields by storage key, recording key collisions as problems
+fn index_by_key<'a>(
+    layout: &'a StorageLayout,
+    problems: &mut Vec<String>,
+) -> BTreeMap<String, (&'a str, &'a StorageField)> {
+    let mut keys = BTreeMap::new();
+    for s in &layout.structs {
+        for f in &s.fields {
+            let key = f.storage_key().to_string();
+            if let Some((other_struct, other)) = keys.insert(key.clone(), (s.name.as_str(), f)) {
+                problems.push(format!(
+                    "key \"{key}\" is used by both {other_struct}.{} and {}.{}",
+                    other.name, s.name, f.name
+                ));
+            }
+        }
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(source: &str) -> StorageLayout {
+        // Tests run in parallel; every call gets its own directory
+        static CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let call = CALLS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("tako-storage-{}-{call}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("lib.rs"), source).unwrap();
+        let layout = extract_layout(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        layout.unwrap()
+    }
+
+    const TOKEN: &str = r#"
+        pub struct Token {
+            total_supply: Value<u64>,
+            balances: Mapping<Address, u64>,
+            owner: Value<Address>,
+        }
+
+        impl Token {
+            pub fn new() -> Self {
+                Self {
+                    total_supply: Value::new("supply"),
+                    balances: Mapping::new("balances"),
+                    owner: tako_storage::Value::new("owner"),
+                }
+            }
+        }
+    "#;
+
+    #[test]
+    fn test_normalize_type() {
+        assert_eq!(normalize_type("core::primitive::u64"), "u64");
+        assert_eq!(normalize_type("::std::primitive::u64"), "u64");
+        assert_eq!(normalize_type("alloc::vec::Vec < u8 >"), "Vec<u8>");
+        assert_eq!(
+            normalize_type("Option<std::string::String>"),
+            normalize_type("Option < String >")
+        );
+        assert_eq!(normalize_type("[core::primitive::u8; 32]"), "[u8;32]");
+        // Only prelude types are shortened
+        assert_eq!(
+            normalize_type("crate::types::Address"),
+            "crate::types::Address"
+        );
+    }
+
+    #[test]
+    fn test_extract_layout_keys() {
+        let layout = layout(&format!("{TOKEN}\nstruct Config {{ fee: Value<u16> }}"));
+        assert_eq!(layout.structs.len(), 2);
+
+        let token = &layout.structs[0];
+        assert_eq!(token.name, "Token");
+        let fields: Vec<(&str, String, &str)> = token
+            .fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.type_string(), f.storage_key()))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("total_supply", "Value<u64>".to_string(), "supply"),
+                ("balances", "Mapping<Address, u64>".to_string(), "balances"),
+                ("owner", "Value<Address>".to_string(), "owner"),
+            ]
+        );
+
+        // Without a constructor the field name is the key
+        let config = &layout.structs[1];
+        assert_eq!(config.fields[0].key, None);
+        assert_eq!(config.fields[0].storage_key(), "fee");
+    }
+
+    #[test]
+    fn test_compare_layouts() {
+        let old = layout(TOKEN);
+
+        // Same source: compatible, nothing added or removed
+        let diff = compare_layouts(&old, &layout(TOKEN));
+        assert!(diff.problems.is_empty() && diff.removed.is_empty() && diff.added.is_empty());
+
+        // Reordered and renamed fields and spelled-out types keep their keys
+        let reordered = layout(
+            r#"
+            pub struct Token {
+                admin: Value<Address>,
+                holders: Mapping<Address, core::primitive::u64>,
+                supply: Value<::core::primitive::u64>,
+                paused: Value<bool>,
+            }
+
+            impl Token {
+                pub fn new() -> Self {
+                    Self {
+                        admin: Value::new("owner"),
+                        holders: Mapping::new("balances"),
+                        supply: Value::new("supply"),
+                        paused: Value::new("paused"),
+                    }
+                }
+            }
+            "#,
+        );
+        let diff = compare_layouts(&old, &reordered);
+        assert!(diff.problems.is_empty(), "{:?}", diff.problems);
+        assert_eq!(diff.added, ["key \"paused\" Value<bool> (Token.paused)"]);
+
+        // A key reinterpreted with another type is incompatible; a dropped
+        // key is only a warning
+        let changed = layout(
+            r#"
+            pub struct Token {
+                total_supply: Value<u128>,
+                balances: Mapping<Address, u64>,
+            }
+
+            impl Token {
+                pub fn new() -> Self {
+                    Self {
+                        total_supply: Value::new("supply"),
+                        balances: Mapping::new("balances"),
+                    }
+                }
+            }
+            "#,
+        );
+        let diff = compare_layouts(&old, &changed);
+        assert_eq!(
+            diff.problems,
+            ["key \"supply\" changed from Value<u64> (Token.total_supply) to Value<u128> (Token.total_supply)"]
+        );
+        assert_eq!(diff.removed.len(), 1);
+
+        // Two fields under one key
+        let mut collision = layout(TOKEN);
+        collision.structs[0].fields[2].key = Some("supply".to_string());
+        let diff = compare_layouts(&old, &collision);
+        assert!(diff.problems[0].starts_with("key \"supply\" is used by both"));
+    }
+}
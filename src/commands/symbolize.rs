@@ -0,0 +1,136 @@
+//! Symbolize command implementation
+//!
+//! Maps program counters from node logs back to Rust functions and source
+//! lines using the debug info of a non-stripped build, or the source map
+//! written next to the artifact when neither debug info nor llvm-symbolizer
+//! is available.
+
+use crate::commands::build;
+use crate::elf;
+use crate::error::{Error, Result};
+use crate::logs;
+use crate::manifest::BuildManifest;
+use crate::source_map::SourceMap;
+use crate::toolchain::find_llvm_tool;
+use crate::util::{parse_address, sha256_hex};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// TBPF instructions are 8 bytes wide
+const INSTRUCTION_SIZE: u64 = 8;
+
+/// Resolve program counters to source locations
+///
+/// # Arguments
+/// * `contract` - Path to a contract binary (defaults to the latest build). Stripped
+///   release binaries are resolved to their debug-symbols copy via the build manifest
+/// * `arch` - TBPF architecture version used to locate the latest build
+/// * `addresses` - Addresses in hex (`0x120`) or decimal
+/// * `instruction` - Treat addresses as instruction indices into .text instead of byte addresses
+pub fn symbolize(
+    contract: Option<&str>,
+    arch: &str,
+    addresses: &[String],
+    instruction: bool,
+) -> Result<()> {
+    let mut path = match contract {
+        Some(p) => PathBuf::from(p),
+        None => build::latest_artifact(arch)?,
+    };
+
+    let mut bytes = elf::read(&path)?;
+    let hash = sha256_hex(&bytes);
+    let source_map = SourceMap::load_for(&path, &hash);
+    let source_map_path = SourceMap::path_for(&path);
+
+    let mut debug_info = elf::has_debug_info(&elf::parse(&bytes)?);
+    if !debug_info {
+        let manifest = BuildManifest::load()?;
+        match manifest.debug_symbols.get(&hash) {
+            Some(debug_copy) => {
+                println!("Using debug symbols: {debug_copy}");
+                path = PathBuf::from(debug_copy);
+                bytes = elf::read(&path)?;
+                debug_info = true;
+            }
+            None if source_map.is_some() => {}
+            None => {
+                return Err(Error::Other(format!(
+                    "{} has no debug info, source map or debug-symbols copy recorded in {}",
+                    path.display(),
+                    BuildManifest::path().display()
+                )))
+            }
+        }
+    }
+
+    let parsed = elf::parse(&bytes)?;
+
+    let text_base = if instruction {
+        elf::section_address(&parsed, ".text")
+            .ok_or_else(|| Error::Other("Contract has no .text section".to_string()))?
+    } else {
+        0
+    };
+
+    let mut resolved = Vec::new();
+    for address in addresses {
+        let value = parse_address(address)?;
+        let vaddr = if instruction {
+            text_base + value * INSTRUCTION_SIZE
+        } else {
+            value
+        };
+        resolved.push(vaddr);
+    }
+
+    // Without DWARF or llvm-symbolizer, use the source map written at build time
+    let symbolizer = find_llvm_tool("llvm-symbolizer").filter(|_| debug_info);
+    if let (None, Some(map)) = (&symbolizer, &source_map) {
+        println!("Binary: {}", path.display());
+        println!("Source map: {}", source_map_path.display());
+        println!();
+        for (input, vaddr) in addresses.iter().zip(&resolved) {
+            println!("{input} (0x{vaddr:x}):");
+            match map.lookup(*vaddr) {
+                Some(range) => {
+                    println!("  {}", range.function);
+                    println!("  {}", range.location());
+                }
+                None => println!("  ??"),
+            }
+        }
+        return Ok(());
+    }
+    let symbolizer = symbolizer.ok_or_else(|| {
+        Error::ToolchainMissing("llvm-symbolizer not found in platform-tools or PATH".to_string())
+    })?;
+
+    let output = logs::output(
+        Command::new(&symbolizer)
+            .arg(format!("--obj={}", path.display()))
+            .args(["--inlines", "--pretty-print"])
+            .args(resolved.iter().map(|vaddr| format!("0x{vaddr:x}"))),
+    )
+    .map_err(|e| Error::spawn("llvm-symbolizer", e, Error::Other))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Other(format!("llvm-symbolizer failed: {stderr}")));
+    }
+
+    println!("Binary: {}", path.display());
+    println!();
+
+    // llvm-symbolizer separates the frames of each address with a blank line
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let frames = stdout.split("\n\n").filter(|f| !f.trim().is_empty());
+    for ((input, vaddr), frame) in addresses.iter().zip(&resolved).zip(frames) {
+        println!("{input} (0x{vaddr:x}):");
+        for line in frame.lines() {
+            println!("  {line}");
+        }
+    }
+
+    Ok(())
+}
@@ -1,8 +1,21 @@
 //! Test command implementation
 
+use crate::commands::build;
 use crate::error::{Error, Result};
-use std::io::{BufRead, BufReader};
+use crate::interrupt;
+use crate::logs;
+use crate::metadata::{self, Metadata};
+use crate::style::Styled;
+use crate::test_report::{TestOutcome, TestReport, TestResult};
+use crate::toolchain::{find_platform_tools, DEFAULT_PLATFORM_TOOLS_VERSION};
+use crate::watch::Watcher;
+use colored::Colorize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Component, Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Instant;
 
 /// Run tests for the TAKO smart contract
 ///
@@ -12,16 +25,176 @@ use std::process::{Command, Stdio};
 /// # Arguments
 /// * `filter` - Optional test name filter (e.g., "test_counter_increment")
 /// * `release` - Whether to run tests in release mode
+/// * `no_capture` - Show output printed by tests as they run
 ///
 /// # Examples
 /// ```bash
 /// cargo tako test                    # Run all tests
 /// cargo tako test test_increment     # Run tests matching "test_increment"
 /// cargo tako test --release          # Run tests in release mode
+/// cargo tako test --no-capture       # Show output printed by tests
+/// cargo tako test --doc              # Run doctests and compile examples for TBPF
+/// cargo tako test --changed          # Run tests affected by uncommitted changes first
+/// cargo tako test --watch --changed  # Re-run affected tests on every change
 /// ```
-pub fn run_tests(filter: Option<&str>, release: bool) -> Result<()> {
+pub fn run_tests(filter: Option<&str>, release: bool, no_capture: bool) -> Result<()> {
     println!("Running tests...");
 
+    if let Some(f) = filter {
+        println!("Filter: {f}");
+    }
+
+    run_cargo_test(
+        &[],
+        filter.into_iter().map(String::from).collect(),
+        release,
+        no_capture,
+        &mut TestReport::default(),
+    )?;
+
+    println!();
+    println!("✓ All tests passed");
+
+    Ok(())
+}
+
+/// Run doctests and compile the examples for the TBPF target
+///
+/// Doctests run natively with `cargo test --doc`. Each example under
+/// `examples/` is then built for the TBPF target with the same toolchain as
+/// contract builds, so documented snippets and example clients fail here
+/// rather than rot. Example builds are recorded in the test summary as
+/// `examples::<name>`.
+///
+/// # Arguments
+/// * `filter` - Optional doctest name filter
+/// * `release` - Whether to run doctests and build examples in release mode
+/// * `no_capture` - Show output printed by doctests as they run
+/// * `arch` - TBPF architecture version to compile examples for
+pub fn run_doc_tests(
+    filter: Option<&str>,
+    release: bool,
+    no_capture: bool,
+    arch: &str,
+) -> Result<()> {
+    let metadata = metadata::load()?;
+    let has_rlib = metadata.packages.iter().any(|p| {
+        p.targets
+            .iter()
+            .any(|t| t.kind.iter().any(|k| k == "lib" || k == "rlib"))
+    });
+    if !has_rlib {
+        println!(
+            "{} doctests need an rlib: add \"rlib\" to crate-type in Cargo.toml",
+            "Note:".warning().bold()
+        );
+    }
+
+    println!("Running doctests...");
+    let mut summary = TestReport::default();
+    let doctests = run_cargo_test(
+        &["--doc".to_string()],
+        filter.into_iter().map(String::from).collect(),
+        release,
+        no_capture,
+        &mut summary,
+    );
+
+    let examples: Vec<(&str, &str)> = metadata
+        .packages
+        .iter()
+        .flat_map(|p| {
+            p.targets
+                .iter()
+                .filter(|t| t.kind.iter().any(|k| k == "example"))
+                .map(move |t| (p.name.as_str(), t.name.as_str()))
+        })
+        .collect();
+    let examples = if examples.is_empty() {
+        Ok(())
+    } else {
+        println!();
+        println!(
+            "Compiling {} example(s) for {}...",
+            examples.len(),
+            build::get_target_triple(arch)
+        );
+        compile_examples(&examples, release, arch, &mut summary)
+    };
+
+    doctests?;
+    examples?;
+
+    println!();
+    println!("✓ All doctests passed and examples compiled");
+    Ok(())
+}
+
+/// Build each `(package, example)` for the TBPF target and record the outcome
+fn compile_examples(
+    examples: &[(&str, &str)],
+    release: bool,
+    arch: &str,
+    summary: &mut TestReport,
+) -> Result<()> {
+    let platform_tools = find_platform_tools(Some(DEFAULT_PLATFORM_TOOLS_VERSION));
+    let (cargo_bin, _) = build::get_cargo_and_rustc(&platform_tools);
+
+    let mut report = TestReport::default();
+    for (package, example) in examples {
+        let mut cmd = Command::new(&cargo_bin);
+        cmd.args(["build", "--package", package, "--example", example])
+            .arg("--target")
+            .arg(build::get_target_triple(arch))
+            .arg("-Zbuild-std=core,alloc")
+            .envs(build::toolchain_env(&platform_tools));
+        if release {
+            cmd.arg("--release");
+        }
+
+        let start = Instant::now();
+        let output =
+            interrupt::output(&mut cmd).map_err(|e| Error::spawn("cargo", e, Error::TestFailed))?;
+        let failed = !output.status.success();
+        report.push(TestResult {
+            name: format!("examples::{example}"),
+            outcome: if failed {
+                TestOutcome::Failed
+            } else {
+                TestOutcome::Passed
+            },
+            exec_time: start.elapsed().as_secs_f64(),
+            stdout: failed.then(|| String::from_utf8_lossy(&output.stderr).to_string()),
+        });
+    }
+
+    report.render();
+    summary.results.extend(report.results.iter().cloned());
+    summary.save()?;
+
+    let failed = report.count(TestOutcome::Failed);
+    if failed > 0 {
+        return Err(Error::TestFailed(format!(
+            "{failed} example(s) failed to compile"
+        )));
+    }
+    Ok(())
+}
+
+/// Run `cargo test <cargo_args> -- <filters>` and summarize its results
+///
+/// libtest's JSON output is unstable, so RUSTC_BOOTSTRAP is set to enable it
+/// on stable toolchains. Lines that are not JSON events (output of tests run
+/// with `--no-capture`) are echoed as they arrive.
+///
+/// Results are added to `summary`, which is saved to target/tako/ after each run.
+fn run_cargo_test(
+    cargo_args: &[String],
+    filters: Vec<String>,
+    release: bool,
+    no_capture: bool,
+    summary: &mut TestReport,
+) -> Result<()> {
     // Build cargo test command
     let mut cmd = Command::new("cargo");
     cmd.arg("test");
@@ -30,11 +203,20 @@ pub fn run_tests(filter: Option<&str>, release: bool) -> Result<()> {
         cmd.arg("--release");
     }
 
-    // Add filter if specified
-    if let Some(f) = filter {
-        cmd.arg(f);
-        println!("Filter: {f}");
+    cmd.args(cargo_args);
+
+    cmd.env("RUSTC_BOOTSTRAP", "1");
+    cmd.arg("--").args([
+        "-Z",
+        "unstable-options",
+        "--format",
+        "json",
+        "--report-time",
+    ]);
+    if no_capture {
+        cmd.arg("--nocapture");
     }
+    cmd.args(filters);
 
     // Configure command to capture output
     cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
@@ -42,13 +224,29 @@ pub fn run_tests(filter: Option<&str>, release: bool) -> Result<()> {
     // Execute the command
     let mut child = cmd
         .spawn()
-        .map_err(|e| Error::TestFailed(format!("Failed to execute cargo test: {e}")))?;
+        .map_err(|e| Error::spawn("cargo", e, Error::TestFailed))?;
+    interrupt::track_child(&child);
+
+    // Collect stderr (compiler output) in the background so neither pipe blocks
+    let stderr = child.stderr.take().map(|stderr| {
+        thread::spawn(move || {
+            BufReader::new(stderr)
+                .lines()
+                .map_while(|r| r.ok())
+                .collect::<Vec<_>>()
+        })
+    });
 
-    // Capture and display stdout in real-time
+    let mut report = TestReport::default();
+    let mut stdout_lines = Vec::new();
     if let Some(stdout) = child.stdout.take() {
         let reader = BufReader::new(stdout);
         for line in reader.lines().map_while(|r| r.ok()) {
-            println!("{line}");
+            stdout_lines.push(line.clone());
+            if !report.handle_line(&line) {
+                println!("{line}");
+            }
+            let _ = io::stdout().flush();
         }
     }
 
@@ -56,12 +254,25 @@ pub fn run_tests(filter: Option<&str>, release: bool) -> Result<()> {
     let status = child
         .wait()
         .map_err(|e| Error::TestFailed(format!("Failed to wait for tests: {e}")))?;
+    interrupt::untrack_child(&child);
+    let stderr = stderr
+        .and_then(|handle| handle.join().ok())
+        .unwrap_or_default();
+    logs::record(
+        &cmd,
+        &status,
+        stdout_lines.join("\n").as_bytes(),
+        stderr.join("\n").as_bytes(),
+    );
+
+    report.render();
+    summary.results.extend(report.results.iter().cloned());
+    summary.save()?;
 
     if !status.success() {
-        // Capture stderr if available
-        if let Some(stderr) = child.stderr {
-            let reader = BufReader::new(stderr);
-            for line in reader.lines().map_while(|r| r.ok()) {
+        // Without failed tests, the build itself failed; show the compiler output
+        if report.count(TestOutcome::Failed) == 0 {
+            for line in &stderr {
                 eprintln!("{line}");
             }
         }
@@ -72,8 +283,347 @@ pub fn run_tests(filter: Option<&str>, release: bool) -> Result<()> {
         )));
     }
 
+    Ok(())
+}
+
+/// Run tests affected by the changed files first, then the rest
+///
+/// Changed files are mapped to their workspace package. Affected packages are
+/// the changed ones plus every member depending on them. For a package whose
+/// changes are confined to non-root modules, only tests in those modules run
+/// in the first phase. The second phase runs everything not fully covered by
+/// the first one.
+pub fn run_changed_tests(
+    filter: Option<&str>,
+    release: bool,
+    no_capture: bool,
+    changed: &[PathBuf],
+) -> Result<()> {
+    let metadata = metadata::load()?;
+    let selection = select_tests(&metadata, changed);
+
+    if selection.is_empty() {
+        println!("No workspace packages affected by changes, running all tests");
+        return run_tests(filter, release, no_capture);
+    }
+
+    println!("{} affected tests...", "Running".success().bold());
+    let mut summary = TestReport::default();
+    let mut fully_tested = Vec::new();
+    for (package, modules) in &selection {
+        let mut cargo_args = vec!["-p".to_string(), package.clone()];
+        let filters = match (filter, modules) {
+            (Some(f), _) => {
+                println!("  {package} ({f})");
+                vec![f.to_string()]
+            }
+            (None, Some(modules)) => {
+                let modules: Vec<&str> = modules.iter().map(String::as_str).collect();
+                println!("  {package} ({})", modules.join(", "));
+                // Module paths name unit tests; integration tests run in the second phase
+                cargo_args.extend(unit_test_targets(&metadata, package));
+                let names = list_tests(&cargo_args, release)?;
+                let mut filters = tests_in_modules(&names, &modules);
+                if filters.is_empty() {
+                    println!("    no tests in the changed modules");
+                    continue;
+                }
+                // libtest filters match substrings; exact names keep the
+                // selection anchored at the crate root
+                filters.push("--exact".to_string());
+                filters
+            }
+            (None, None) => {
+                println!("  {package}");
+                Vec::new()
+            }
+        };
+        if filter.is_some() || modules.is_none() {
+            fully_tested.push(package.clone());
+        }
+
+        run_cargo_test(&cargo_args, filters, release, no_capture, &mut summary)?;
+    }
+
+    let remaining = metadata
+        .packages
+        .iter()
+        .filter(|p| !fully_tested.contains(&p.name))
+        .count();
+    if remaining > 0 {
+        println!();
+        println!("{} remaining tests...", "Running".success().bold());
+        let mut args = vec!["--workspace".to_string()];
+        for package in &fully_tested {
+            args.push("--exclude".to_string());
+            args.push(package.clone());
+        }
+        run_cargo_test(
+            &args,
+            filter.into_iter().map(String::from).collect(),
+            release,
+            no_capture,
+            &mut summary,
+        )?;
+    }
+
     println!();
     println!("✓ All tests passed");
 
     Ok(())
 }
+
+/// Map changed files to affected packages and, where possible, test module filters
+///
+/// A `None` module list means the whole package must be tested.
+fn select_tests(
+    metadata: &Metadata,
+    changed: &[PathBuf],
+) -> BTreeMap<String, Option<BTreeSet<String>>> {
+    let mut selection: BTreeMap<String, Option<BTreeSet<String>>> = BTreeMap::new();
+
+    for file in changed {
+        let file = if file.is_absolute() {
+            file.clone()
+        } else {
+            metadata.workspace_root.join(file)
+        };
+        let Some(package) = metadata.package_for_file(&file) else {
+            continue;
+        };
+
+        let module = file
+            .strip_prefix(package.root().join("src"))
+            .ok()
+            .and_then(module_path);
+        let entry = selection
+            .entry(package.name.clone())
+            .or_insert_with(|| Some(BTreeSet::new()));
+        match (entry.as_mut(), module) {
+            (Some(modules), Some(module)) => {
+                modules.insert(module);
+            }
+            _ => *entry = None,
+        }
+    }
+
+    // Dependents must be tested in full since any of their modules may use the change
+    let changed_packages: BTreeSet<String> = selection.keys().cloned().collect();
+    for dependent in metadata.with_dependents(&changed_packages) {
+        selection.entry(dependent).or_insert(None);
+    }
+
+    selection
+}
+
+/// `cargo test` flags selecting the targets that hold a package's unit tests
+fn unit_test_targets(metadata: &Metadata, package: &str) -> Vec<String> {
+    let Some(package) = metadata.packages.iter().find(|p| p.name == package) else {
+        return Vec::new();
+    };
+    let has_kind = |kinds: &[&str]| {
+        package
+            .targets
+            .iter()
+            .any(|t| t.kind.iter().any(|k| kinds.contains(&k.as_str())))
+    };
+    let mut flags = Vec::new();
+    if has_kind(&["lib", "rlib", "dylib", "cdylib", "staticlib", "proc-macro"]) {
+        flags.push("--lib".to_string());
+    }
+    if has_kind(&["bin"]) {
+        flags.push("--bins".to_string());
+    }
+    flags
+}
+
+/// Names of the tests `cargo test <cargo_args>` would run
+fn list_tests(cargo_args: &[String], release: bool) -> Result<Vec<String>> {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("test").args(cargo_args);
+    if release {
+        cmd.arg("--release");
+    }
+    cmd.args(["--", "--list", "--format", "terse"]);
+    let output = logs::output(&mut cmd).map_err(|e| Error::spawn("cargo", e, Error::TestFailed))?;
+    if !output.status.success() {
+        return Err(Error::TestFailed(format!(
+            "Failed to list tests:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_suffix(": test"))
+        .map(String::from)
+        .collect())
+}
+
+/// Tests inside the given modules: names equal to a module path or starting
+/// with it followed by `::`, so `token` selects `token::tests::x` but not
+/// `other::token::tests::x` or `token_list::tests::x`
+fn tests_in_modules(names: &[String], modules: &[&str]) -> Vec<String> {
+    let mut selected: Vec<String> = names
+        .iter()
+        .filter(|name| {
+            modules.iter().any(|module| {
+                name.strip_prefix(module)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+        })
+        .cloned()
+        .collect();
+    selected.sort();
+    selected.dedup();
+    selected
+}
+
+/// Convert a path relative to `src/` into a module path (`a/b.rs` -> `a::b`)
+///
+/// Returns None for crate roots, whose changes can affect every module.
+fn module_path(relative: &Path) -> Option<String> {
+    let mut parts: Vec<String> = relative
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => s.to_str().map(String::from),
+            _ => None,
+        })
+        .collect();
+
+    let file = parts.pop()?;
+    let stem = file.strip_suffix(".rs")?;
+    if parts.is_empty() && (stem == "lib" || stem == "main") {
+        return None;
+    }
+    if parts.first().is_some_and(|p| p == "bin") {
+        return None;
+    }
+    if stem != "mod" {
+        parts.push(stem.to_string());
+    }
+
+    Some(parts.join("::"))
+}
+
+/// Files changed in the working tree according to git (modified and untracked)
+pub fn git_changed_files() -> Result<Vec<PathBuf>> {
+    let root = logs::output(Command::new("git").args(["rev-parse", "--show-toplevel"]))
+        .map_err(|e| Error::Other(format!("Failed to run git: {e}")))?;
+    if !root.status.success() {
+        return Err(Error::Other(
+            "--changed requires a git repository".to_string(),
+        ));
+    }
+    let root = PathBuf::from(String::from_utf8_lossy(&root.stdout).trim());
+
+    let status =
+        logs::output(Command::new("git").args(["status", "--porcelain", "--untracked-files=all"]))
+            .map_err(|e| Error::Other(format!("Failed to run git: {e}")))?;
+
+    Ok(String::from_utf8_lossy(&status.stdout)
+        .lines()
+        .filter_map(|line| line.get(3..))
+        // Renames are reported as "old -> new"
+        .map(|path| path.rsplit(" -> ").next().unwrap_or(path))
+        .map(|path| root.join(path))
+        .collect())
+}
+
+/// Re-run tests whenever sources change
+///
+/// With `changed`, each run starts with the tests affected by the files that
+/// changed since the previous run (the first run uses git's working tree status).
+pub fn watch_tests(
+    filter: Option<&str>,
+    release: bool,
+    no_capture: bool,
+    changed: bool,
+) -> Result<()> {
+    let roots = match metadata::load() {
+        Ok(metadata) => vec![metadata.workspace_root],
+        Err(_) => vec![PathBuf::from(".")],
+    };
+    let mut watcher = Watcher::new(roots);
+
+    let mut changed_files = if changed {
+        git_changed_files()?
+    } else {
+        Vec::new()
+    };
+
+    loop {
+        let result = if changed && !changed_files.is_empty() {
+            run_changed_tests(filter, release, no_capture, &changed_files)
+        } else {
+            run_tests(filter, release, no_capture)
+        };
+        if let Err(e) = result {
+            eprintln!("{} {e}", "✗".failure().bold());
+        }
+
+        println!();
+        println!(
+            "{} for changes... (Ctrl-C to stop)",
+            "Watching".accent().bold()
+        );
+        changed_files = watcher.wait_for_changes();
+        for file in &changed_files {
+            println!("  changed: {}", file.display());
+        }
+        println!();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_path() {
+        assert_eq!(module_path(Path::new("token.rs")).as_deref(), Some("token"));
+        assert_eq!(
+            module_path(Path::new("token/transfer.rs")).as_deref(),
+            Some("token::transfer")
+        );
+        assert_eq!(
+            module_path(Path::new("token/mod.rs")).as_deref(),
+            Some("token")
+        );
+        assert_eq!(module_path(Path::new("lib.rs")), None);
+        assert_eq!(module_path(Path::new("main.rs")), None);
+        assert_eq!(module_path(Path::new("bin/cli.rs")), None);
+        assert_eq!(module_path(Path::new("README.md")), None);
+    }
+
+    #[test]
+    fn test_tests_in_modules() {
+        let names: Vec<String> = [
+            "token::tests::test_transfer",
+            "token::transfer::tests::test_amount",
+            "token_list::tests::test_push",
+            "other::token::tests::test_transfer",
+            "tests::token",
+            "token",
+        ]
+        .iter()
+        .map(|n| n.to_string())
+        .collect();
+
+        assert_eq!(
+            tests_in_modules(&names, &["token"]),
+            [
+                "token",
+                "token::tests::test_transfer",
+                "token::transfer::tests::test_amount",
+            ]
+        );
+        assert_eq!(
+            tests_in_modules(&names, &["token::transfer", "other"]),
+            [
+                "other::token::tests::test_transfer",
+                "token::transfer::tests::test_amount",
+            ]
+        );
+        assert!(tests_in_modules(&names, &["transfer"]).is_empty());
+    }
+}
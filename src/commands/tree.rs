@@ -0,0 +1,278 @@
+//! Tree command implementation
+//!
+//! Shows the dependency tree filtered to crates that contribute code to the
+//! contract binary, annotated with the bytes attributed to each crate. Sizes
+//! come from the linker map of the latest build when it matches the binary.
+
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::commands::build;
+use crate::elf;
+use crate::error::{Error, Result};
+use crate::logs;
+use crate::manifest::BuildManifest;
+use crate::size::{self, format_size, LINKER, SYSROOT_CRATES, UNATTRIBUTED};
+use crate::util::sha256_hex;
+
+/// A package in the output of `cargo tree`
+struct Node {
+    lib: String,
+    label: String,
+    children: Vec<usize>,
+}
+
+/// Print the dependency tree of crates linked into the contract
+///
+/// # Arguments
+/// * `contract` - Path to a contract binary (defaults to the latest build). Without a
+///   link map, stripped release binaries are resolved to their debug-symbols copy
+/// * `arch` - TBPF architecture version used to locate the latest build
+pub fn tree(contract: Option<&str>, arch: &str) -> Result<()> {
+    let path = match contract {
+        Some(p) => PathBuf::from(p),
+        None => build::latest_artifact(arch)?,
+    };
+
+    let (mut sizes, source) = match linked_map(&path)? {
+        Some(map) => (size::map_crate_sizes(&fs::read_to_string(&map)?), map),
+        None => (symbol_sizes(&path)?, path.clone()),
+    };
+    let total: u64 = sizes.values().sum();
+
+    let nodes = cargo_tree()?;
+
+    println!("Binary: {}", path.display());
+    if source != path {
+        println!("Link map: {}", source.display());
+    }
+    println!();
+    println!("{:<48} {:>12} {:>12}", "Crate", "Own", "With deps");
+    if !nodes.is_empty() {
+        let mut totals = vec![0; nodes.len()];
+        subtree_size(&nodes, 0, &sizes, &mut totals);
+        print_node(&nodes, 0, &sizes, &totals, "", "");
+    }
+
+    // Crates in the tree are removed so only toolchain and unknown code is left
+    for node in &nodes {
+        sizes.remove(&node.lib);
+    }
+
+    let sysroot: Vec<_> = SYSROOT_CRATES
+        .iter()
+        .filter_map(|name| sizes.remove(*name).map(|bytes| (*name, bytes)))
+        .collect();
+    if !sysroot.is_empty() {
+        println!();
+        println!("{}", "Toolchain".bold());
+        for (name, bytes) in sysroot {
+            println!("{:<48} {:>12}", name, format_size(bytes));
+        }
+    }
+
+    // Keep the placeholder names at the end of the list
+    let special: Vec<_> = [LINKER, UNATTRIBUTED]
+        .iter()
+        .filter_map(|name| sizes.remove(*name).map(|bytes| (*name, bytes)))
+        .collect();
+    if !sizes.is_empty() || !special.is_empty() {
+        println!();
+        println!("{}", "Other".bold());
+        for (name, bytes) in &sizes {
+            println!("{:<48} {:>12}", name, format_size(*bytes));
+        }
+        for (name, bytes) in special {
+            println!("{:<48} {:>12}", name, format_size(bytes));
+        }
+    }
+
+    println!();
+    println!("{:<48} {:>12}", "Total".bold(), format_size(total));
+    if source == path {
+        println!();
+        println!(
+            "Sizes are estimated from symbol names; build with `cargo tako build` for a link map."
+        );
+    }
+
+    Ok(())
+}
+
+/// Get the link map recorded for the binary, if it is the latest build
+fn linked_map(path: &Path) -> Result<Option<PathBuf>> {
+    let Some(artifact) = BuildManifest::load()?.artifact else {
+        return Ok(None);
+    };
+    let map = artifact.link_map.map(PathBuf::from);
+    let hash = sha256_hex(&elf::read(path)?);
+    Ok(map.filter(|map| artifact.sha256 == hash && map.exists()))
+}
+
+/// Estimate crate sizes from the symbol table of the binary or its debug-symbols copy
+fn symbol_sizes(path: &Path) -> Result<BTreeMap<String, u64>> {
+    let mut bytes = elf::read(path)?;
+    if elf::parse(&bytes)?.syms.is_empty() {
+        let manifest = BuildManifest::load()?;
+        let debug_copy = manifest
+            .debug_symbols
+            .get(&sha256_hex(&bytes))
+            .ok_or_else(|| {
+                Error::Other(format!(
+                    "{} has no symbol table and no debug-symbols copy is recorded in {}",
+                    path.display(),
+                    BuildManifest::path().display()
+                ))
+            })?;
+        bytes = elf::read(Path::new(debug_copy))?;
+    }
+    Ok(size::crate_sizes(&elf::parse(&bytes)?))
+}
+
+/// Run `cargo tree` and parse it into nodes; the root package is node 0
+fn cargo_tree() -> Result<Vec<Node>> {
+    let output = logs::output(
+        Command::new("cargo")
+            .args(["tree", "-e", "normal", "--prefix", "depth"])
+            .args(["--format", "{lib}|{p}"]),
+    )
+    .map_err(|e| Error::spawn("cargo tree", e, Error::Other))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Other(format!("cargo tree failed:\n{stderr}")));
+    }
+
+    Ok(parse_tree(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `cargo tree --prefix depth --format "{lib}|{p}"` output
+///
+/// Only the first package tree is parsed; repeated subtrees (marked `(*)`)
+/// are skipped so no crate is counted twice.
+fn parse_tree(output: &str) -> Vec<Node> {
+    let mut nodes: Vec<Node> = Vec::new();
+    // Index of the most recent node at each depth
+    let mut parents: Vec<usize> = Vec::new();
+
+    for line in output.lines() {
+        let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+        let Ok(depth) = line[..digits].parse::<usize>() else {
+            continue;
+        };
+        if depth == 0 && !nodes.is_empty() {
+            break;
+        }
+        let Some((lib, label)) = line[digits..].split_once('|') else {
+            continue;
+        };
+        if label.ends_with("(*)") || depth > parents.len() {
+            continue;
+        }
+
+        let name = label.split_whitespace().next().unwrap_or_default();
+        let lib = if lib.is_empty() {
+            name.replace('-', "_")
+        } else {
+            lib.to_string()
+        };
+
+        let index = nodes.len();
+        nodes.push(Node {
+            lib,
+            label: label.to_string(),
+            children: Vec::new(),
+        });
+        parents.truncate(depth);
+        if let Some(&parent) = parents.last() {
+            nodes[parent].children.push(index);
+        }
+        parents.push(index);
+    }
+
+    nodes
+}
+
+/// Compute the bytes of each node including its dependencies
+fn subtree_size(
+    nodes: &[Node],
+    index: usize,
+    sizes: &BTreeMap<String, u64>,
+    totals: &mut [u64],
+) -> u64 {
+    let own = sizes.get(&nodes[index].lib).copied().unwrap_or_default();
+    let deps: u64 = nodes[index]
+        .children
+        .iter()
+        .map(|&child| subtree_size(nodes, child, sizes, totals))
+        .sum();
+    totals[index] = own + deps;
+    totals[index]
+}
+
+/// Print a node and its dependencies that contribute code
+fn print_node(
+    nodes: &[Node],
+    index: usize,
+    sizes: &BTreeMap<String, u64>,
+    totals: &[u64],
+    prefix: &str,
+    child_prefix: &str,
+) {
+    let node = &nodes[index];
+    let own = sizes.get(&node.lib).copied().unwrap_or_default();
+    let label = format!("{prefix}{}", node.label);
+    println!(
+        "{:<48} {:>12} {:>12}",
+        label,
+        format_size(own),
+        format_size(totals[index])
+    );
+
+    let children: Vec<usize> = node
+        .children
+        .iter()
+        .copied()
+        .filter(|&child| totals[child] > 0)
+        .collect();
+    for (i, &child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        let (branch, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        print_node(
+            nodes,
+            child,
+            sizes,
+            totals,
+            &format!("{child_prefix}{branch}"),
+            &format!("{child_prefix}{indent}"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tree() {
+        let output = "\
+0my_token|my-token v0.1.0 (/work/my-token)
+1tako_sdk|tako-sdk v0.2.0
+2tako_macros|tako-macros v0.2.0 (proc-macro)
+1tako_sdk|tako-sdk v0.2.0 (*)
+1|helper v0.1.0
+";
+        let nodes = parse_tree(output);
+        assert_eq!(nodes.len(), 4);
+        assert_eq!(nodes[0].children, vec![1, 3]);
+        assert_eq!(nodes[1].children, vec![2]);
+        assert_eq!(nodes[3].lib, "helper");
+    }
+}
@@ -0,0 +1,139 @@
+//! Vendor command implementation
+//!
+//! Vendors the project's dependencies together with the dependencies of the
+//! Rust standard library sources used by `-Zbuild-std`, so release builds can
+//! run with `cargo tako build --offline-cargo` on air-gapped machines.
+
+use crate::commands::build::get_cargo_and_rustc;
+use crate::error::{Error, Result};
+use crate::logs;
+use crate::toolchain::{find_platform_tools, DEFAULT_PLATFORM_TOOLS_VERSION};
+use crate::util::ensure_dir;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Vendor dependencies into `dir` and configure cargo to use them
+///
+/// # Arguments
+/// * `dir` - Vendor directory, relative to the project root
+pub fn vendor(dir: &str) -> Result<()> {
+    let platform_tools = find_platform_tools(Some(DEFAULT_PLATFORM_TOOLS_VERSION));
+    let (cargo_bin, rustc) = get_cargo_and_rustc(&platform_tools);
+
+    let library = library_dir(rustc.as_deref())?;
+    if library.is_none() {
+        eprintln!("Warning: rust-src not found in the toolchain sysroot;");
+        eprintln!("  build-std sources will not be vendored and offline builds will fail.");
+    }
+
+    let mut cmd = Command::new(&cargo_bin);
+    cmd.arg("vendor").arg("--versioned-dirs");
+    if let Some(ref library) = library {
+        cmd.arg("--sync").arg(library.join("Cargo.toml"));
+    }
+    cmd.arg(dir);
+    if let Some(ref rustc) = rustc {
+        cmd.env("RUSTC", rustc);
+    }
+
+    println!("Running: cargo vendor {dir}");
+    let output = logs::output(cmd.stderr(Stdio::inherit()))
+        .map_err(|e| Error::spawn("cargo vendor", e, Error::Other))?;
+    if !output.status.success() {
+        return Err(Error::Other("cargo vendor failed".to_string()));
+    }
+
+    // cargo vendor prints the source replacement config on stdout
+    let source_config = String::from_utf8_lossy(&output.stdout);
+    write_source_config(&source_config)?;
+
+    if let Some(library) = library {
+        let missing = missing_build_std_packages(&library, Path::new(dir))?;
+        if !missing.is_empty() {
+            for package in &missing {
+                eprintln!("  missing: {package}");
+            }
+            return Err(Error::Other(format!(
+                "{dir} does not cover {} build-std package(s)",
+                missing.len()
+            )));
+        }
+        println!("✓ Vendored build-std sources from {}", library.display());
+    }
+
+    println!("✓ Dependencies vendored to {dir}");
+    Ok(())
+}
+
+/// Locate `library/` in the rust-src component of the toolchain sysroot
+fn library_dir(rustc: Option<&Path>) -> Result<Option<PathBuf>> {
+    let rustc = rustc.map(Path::as_os_str).unwrap_or("rustc".as_ref());
+    let output = logs::output(Command::new(rustc).args(["--print", "sysroot"]))
+        .map_err(|e| Error::Other(format!("Failed to query rustc sysroot: {e}")))?;
+    if !output.status.success() {
+        return Err(Error::Other("Failed to query rustc sysroot".to_string()));
+    }
+
+    let sysroot = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    let library = sysroot.join("lib/rustlib/src/rust/library");
+    Ok(library.join("Cargo.lock").exists().then_some(library))
+}
+
+/// Add the vendored source replacement to .cargo/config.toml
+fn write_source_config(source_config: &str) -> Result<()> {
+    let cargo_dir = Path::new(".cargo");
+    ensure_dir(cargo_dir)?;
+    let config_path = cargo_dir.join("config.toml");
+
+    let existing = fs::read_to_string(&config_path).unwrap_or_default();
+    if existing.contains("[source.crates-io]") {
+        println!(
+            "  {} already has a source replacement; left unchanged",
+            config_path.display()
+        );
+        return Ok(());
+    }
+
+    let mut content = existing;
+    if !content.is_empty() && !content.ends_with("\n\n") {
+        content.push('\n');
+    }
+    content.push_str("# Vendored dependencies (written by `cargo tako vendor`)\n");
+    content.push_str(source_config.trim());
+    content.push('\n');
+    fs::write(&config_path, content)?;
+
+    println!("  Updated {}", config_path.display());
+    Ok(())
+}
+
+/// Registry packages locked by the standard library that are not vendored
+fn missing_build_std_packages(library: &Path, vendor_dir: &Path) -> Result<Vec<String>> {
+    let lock: toml::Value = toml::from_str(&fs::read_to_string(library.join("Cargo.lock"))?)?;
+
+    let mut missing = Vec::new();
+    for package in lock
+        .get("package")
+        .and_then(|p| p.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let is_registry = package
+            .get("source")
+            .and_then(|s| s.as_str())
+            .is_some_and(|s| s.starts_with("registry+"));
+        let (Some(name), Some(version)) = (
+            package.get("name").and_then(|n| n.as_str()),
+            package.get("version").and_then(|v| v.as_str()),
+        ) else {
+            continue;
+        };
+
+        if is_registry && !vendor_dir.join(format!("{name}-{version}")).is_dir() {
+            missing.push(format!("{name} {version}"));
+        }
+    }
+
+    Ok(missing)
+}
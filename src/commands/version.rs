@@ -0,0 +1,89 @@
+//! Version command implementation
+//!
+//! `cargo tako --version --verbose` lists the versions of every component a
+//! build depends on, for bug reports; `--message-format json` prints the
+//! same as JSON for CI compatibility checks.
+
+use serde_json::json;
+use std::path::PathBuf;
+
+use crate::commands::build::{self, ALL_ARCHS};
+use crate::error::{Error, Result};
+use crate::schema::SCHEMA_VERSION;
+use crate::toolchain::{
+    find_platform_tools, RustcVersion, DEFAULT_PLATFORM_TOOLS_VERSION, DEFAULT_RUST_VERSION,
+};
+
+/// Print the cargo-tako version
+///
+/// # Arguments
+/// * `verbose` - Also print the git commit, platform-tools, rustc and supported architectures
+/// * `message_format` - `human` or `json` (JSON always includes every component)
+pub fn print_version(verbose: bool, message_format: &str) -> Result<()> {
+    let version = env!("CARGO_PKG_VERSION");
+    let commit = Some(env!("TAKO_GIT_COMMIT")).filter(|c| !c.is_empty());
+    if !verbose && message_format == "human" {
+        match commit {
+            Some(commit) => println!("cargo-tako {version} ({})", &commit[..commit.len().min(9)]),
+            None => println!("cargo-tako {version}"),
+        }
+        return Ok(());
+    }
+
+    let tools = find_platform_tools(Some(DEFAULT_PLATFORM_TOOLS_VERSION));
+    let rustc_path = build::get_cargo_and_rustc(&tools)
+        .1
+        .unwrap_or_else(|| PathBuf::from("rustc"));
+    let rustc = RustcVersion::query(&rustc_path);
+
+    if message_format == "json" {
+        let info = json!({
+            "cargo_tako": version,
+            "commit_hash": commit,
+            "host": env!("TAKO_HOST"),
+            "schema_version": SCHEMA_VERSION,
+            "platform_tools": tools.as_ref().map(|t| json!({
+                "version": t.version,
+                "path": t.rust_bin.parent().unwrap_or(&t.rust_bin),
+            })),
+            "default_platform_tools": DEFAULT_PLATFORM_TOOLS_VERSION,
+            "bundled_rustc": DEFAULT_RUST_VERSION,
+            "rustc": rustc.as_ref().map(|r| json!({
+                "path": rustc_path,
+                "release": r.release,
+                "host": r.host,
+                "commit_hash": r.commit_hash,
+            })),
+            "archs": ALL_ARCHS
+                .iter()
+                .map(|arch| json!({ "arch": arch, "target": build::get_target_triple(arch) }))
+                .collect::<Vec<_>>(),
+        });
+        let output = serde_json::to_string_pretty(&info)
+            .map_err(|e| Error::Other(format!("Failed to serialize version info: {e}")))?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    println!("cargo-tako {version}");
+    println!("commit-hash: {}", commit.unwrap_or("unknown"));
+    println!("host: {}", env!("TAKO_HOST"));
+    println!("schema-version: {SCHEMA_VERSION}");
+    match &tools {
+        Some(t) => println!("platform-tools: {} ({})", t.version, t.display_path()),
+        None => println!("platform-tools: not found"),
+    }
+    println!(
+        "default-platform-tools: {DEFAULT_PLATFORM_TOOLS_VERSION} (rustc {DEFAULT_RUST_VERSION})"
+    );
+    match &rustc {
+        Some(r) => println!("rustc: {} ({})", r.release, rustc_path.display()),
+        None => println!("rustc: not found ({})", rustc_path.display()),
+    }
+    let archs: Vec<String> = ALL_ARCHS
+        .iter()
+        .map(|arch| format!("{arch} ({})", build::get_target_triple(arch)))
+        .collect();
+    println!("archs: {}", archs.join(", "));
+    Ok(())
+}
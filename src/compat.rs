@@ -0,0 +1,320 @@
+//! Compatibility table of TAKO SDK versions
+//!
+//! Maps SDK git refs to the platform-tools version, TBPF architectures and
+//! cargo-tako version they need. The table in compat.toml is built in; a
+//! refreshed copy downloaded to the TOS cache directory takes precedence.
+
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::{Error, Result};
+use crate::logs;
+use crate::template::{TAKO_SDK_REPO, TAKO_SDK_VERSION};
+use crate::toolchain::{self, PlatformTools};
+
+/// Where `cargo tako compat --refresh` downloads the table from
+pub const COMPAT_URL: &str =
+    "https://raw.githubusercontent.com/tos-network/cargo-tako/main/compat.toml";
+
+const BUILTIN: &str = include_str!("../compat.toml");
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CompatTable {
+    #[serde(default)]
+    pub sdk: Vec<SdkCompat>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SdkCompat {
+    pub version: String,
+    pub platform_tools: String,
+    pub archs: Vec<String>,
+    #[serde(default)]
+    pub cargo_tako: Option<String>,
+}
+
+/// A requirement of the project's SDK that is not met
+#[derive(Debug, PartialEq)]
+pub struct Incompatibility {
+    pub message: String,
+    pub suggestion: String,
+}
+
+impl CompatTable {
+    /// Path of the refreshed table
+    pub fn cache_path() -> PathBuf {
+        toolchain::cache_dir().join("compat.toml")
+    }
+
+    /// The refreshed table if one was downloaded, otherwise the built-in one
+    pub fn load() -> Self {
+        fs::read_to_string(Self::cache_path())
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_else(|| toml::from_str(BUILTIN).unwrap_or_default())
+    }
+
+    /// Download the latest table into the cache directory
+    pub fn refresh() -> Result<PathBuf> {
+        let path = Self::cache_path();
+        let partial = path.with_extension("toml.part");
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // curl, like tar for toolchain archives, avoids an HTTP client dependency
+        let status = logs::status(
+            Command::new("curl")
+                .args(["-fsSL", "-o"])
+                .arg(&partial)
+                .arg(COMPAT_URL),
+        )
+        .map_err(|e| Error::Other(format!("Failed to run curl: {e}")))?;
+        if !status.success() {
+            let _ = fs::remove_file(&partial);
+            return Err(Error::Network(format!("Failed to download {COMPAT_URL}")));
+        }
+
+        let content = fs::read_to_string(&partial)?;
+        if let Err(e) = toml::from_str::<CompatTable>(&content) {
+            let _ = fs::remove_file(&partial);
+            return Err(Error::Config(format!("Downloaded table is invalid: {e}")));
+        }
+        fs::rename(&partial, &path)?;
+        Ok(path)
+    }
+
+    pub fn find(&self, sdk_ref: &str) -> Option<&SdkCompat> {
+        self.sdk.iter().find(|entry| entry.version == sdk_ref)
+    }
+
+    /// Newest `vX.Y.Z` release in the table that the platform-tools version
+    /// and running cargo-tako satisfy
+    ///
+    /// # Arguments
+    /// * `platform_tools` - Installed platform-tools version, `None` if not installed
+    /// * `cargo_tako` - Running cargo-tako version
+    pub fn newest_compatible(
+        &self,
+        platform_tools: Option<&str>,
+        cargo_tako: &str,
+    ) -> Option<&SdkCompat> {
+        self.sdk
+            .iter()
+            .filter(|entry| is_release(&entry.version))
+            .filter(|entry| {
+                platform_tools.is_some_and(|found| !version_less(found, &entry.platform_tools))
+            })
+            .filter(|entry| {
+                entry
+                    .cargo_tako
+                    .as_deref()
+                    .is_none_or(|required| !version_less(cargo_tako, required))
+            })
+            .max_by(|a, b| {
+                if version_less(&a.version, &b.version) {
+                    std::cmp::Ordering::Less
+                } else {
+                    std::cmp::Ordering::Greater
+                }
+            })
+    }
+}
+
+/// Whether an SDK ref is a `vX.Y.Z` release tag
+fn is_release(version: &str) -> bool {
+    let parts: Vec<&str> = version.trim_start_matches('v').split('.').collect();
+    version.starts_with('v')
+        && parts.len() == 3
+        && parts.iter().all(|part| part.parse::<u64>().is_ok())
+}
+
+impl SdkCompat {
+    /// Requirements not met by the selected toolchain, architecture and
+    /// running cargo-tako
+    pub fn check(
+        &self,
+        tools: Option<&PlatformTools>,
+        arch: &str,
+        cargo_tako: &str,
+    ) -> Vec<Incompatibility> {
+        let mut problems = Vec::new();
+
+        // Legacy installs report "unknown"; their version cannot be compared
+        let installed = tools.map(|t| t.version.as_str());
+        let outdated = match installed {
+            None => true,
+            Some(version) => version_less(version, &self.platform_tools),
+        };
+        if outdated {
+            problems.push(Incompatibility {
+                message: format!(
+                    "SDK {} needs platform-tools {} or newer (found {})",
+                    self.version,
+                    self.platform_tools,
+                    installed.unwrap_or("none")
+                ),
+                suggestion: format!(
+                    "Install platform-tools {}:\n\n{}",
+                    self.platform_tools,
+                    toolchain::install_instructions(&self.platform_tools)
+                ),
+            });
+        }
+
+        if !self.archs.iter().any(|a| a == arch) {
+            problems.push(Incompatibility {
+                message: format!("SDK {} does not support arch {arch}", self.version),
+                suggestion: format!("Build with --arch {}", self.archs.join(", --arch ")),
+            });
+        }
+
+        if let Some(required) = &self.cargo_tako {
+            if version_less(cargo_tako, required) {
+                problems.push(Incompatibility {
+                    message: format!(
+                        "SDK {} needs cargo-tako {required} or newer (running {cargo_tako})",
+                        self.version
+                    ),
+                    suggestion: "cargo install --force cargo-tako".to_string(),
+                });
+            }
+        }
+
+        problems
+    }
+}
+
+/// The SDK git ref the project depends on, from its Cargo.toml
+///
+/// Dependencies without a tag, rev or branch follow the default branch.
+pub fn project_sdk_ref(manifest_path: &Path) -> Option<String> {
+    let content = fs::read_to_string(manifest_path).ok()?;
+    let manifest: toml::Value = toml::from_str(&content).ok()?;
+    ["dependencies", "dev-dependencies"]
+        .iter()
+        .filter_map(|section| manifest.get(section)?.as_table())
+        .flat_map(|deps| deps.values())
+        .filter_map(|dep| dep.as_table())
+        .find(|dep| {
+            dep.get("git")
+                .and_then(|git| git.as_str())
+                .is_some_and(|git| {
+                    git.trim_end_matches('/').trim_end_matches(".git") == TAKO_SDK_REPO
+                })
+        })
+        .map(|dep| {
+            ["tag", "rev", "branch"]
+                .iter()
+                .find_map(|key| dep.get(*key)?.as_str())
+                .unwrap_or(TAKO_SDK_VERSION)
+                .to_string()
+        })
+}
+
+/// Compare dotted versions (`v1.52`, `0.3.1`) numerically; versions that
+/// do not parse are never considered older
+pub fn version_less(a: &str, b: &str) -> bool {
+    let parse = |v: &str| -> Option<Vec<u64>> {
+        v.trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect()
+    };
+    match (parse(a), parse(b)) {
+        (Some(a), Some(b)) => a < b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::toolchain::ToolchainSource;
+
+    #[test]
+    fn test_check() {
+        let table: CompatTable = toml::from_str(BUILTIN).unwrap();
+        assert!(table.find("main").is_some());
+        // The templates must pin a release of the tested matrix
+        assert!(is_release(TAKO_SDK_VERSION));
+        assert!(table.find(TAKO_SDK_VERSION).is_some());
+
+        let entry = SdkCompat {
+            version: "v0.4.0".to_string(),
+            platform_tools: "v1.52".to_string(),
+            archs: vec!["v3".to_string()],
+            cargo_tako: Some("0.4.0".to_string()),
+        };
+        let tools = PlatformTools {
+            version: "v1.9".to_string(),
+            rust_bin: PathBuf::from("/tools/rust/bin"),
+            llvm_bin: PathBuf::from("/tools/llvm/bin"),
+            source: ToolchainSource::VersionedCache,
+        };
+        let messages: Vec<String> = entry
+            .check(Some(&tools), "v1", "0.3.1")
+            .into_iter()
+            .map(|p| p.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "SDK v0.4.0 needs platform-tools v1.52 or newer (found v1.9)",
+                "SDK v0.4.0 does not support arch v1",
+                "SDK v0.4.0 needs cargo-tako 0.4.0 or newer (running 0.3.1)",
+            ]
+        );
+
+        let tools = PlatformTools {
+            version: "unknown".to_string(),
+            ..tools
+        };
+        assert!(entry.check(Some(&tools), "v3", "0.4.0").is_empty());
+    }
+
+    #[test]
+    fn test_newest_compatible() {
+        let table: CompatTable = toml::from_str(
+            r#"
+[[sdk]]
+version = "main"
+platform-tools = "v1.40"
+archs = ["v3"]
+
+[[sdk]]
+version = "v0.9.0"
+platform-tools = "v1.50"
+archs = ["v3"]
+
+[[sdk]]
+version = "v0.10.0"
+platform-tools = "v1.52"
+archs = ["v3"]
+cargo-tako = "0.3.0"
+
+[[sdk]]
+version = "v0.11.0"
+platform-tools = "v1.54"
+archs = ["v3"]
+"#,
+        )
+        .unwrap();
+        let newest = |tools, cargo_tako| {
+            table
+                .newest_compatible(tools, cargo_tako)
+                .map(|entry| entry.version.as_str())
+        };
+
+        assert_eq!(newest(Some("v1.54"), "0.3.1"), Some("v0.11.0"));
+        assert_eq!(newest(Some("v1.52"), "0.3.1"), Some("v0.10.0"));
+        // cargo-tako too old for v0.10.0
+        assert_eq!(newest(Some("v1.52"), "0.2.0"), Some("v0.9.0"));
+        // Branches are never picked, even when they are the only match
+        assert_eq!(newest(Some("v1.45"), "0.3.1"), None);
+        assert_eq!(newest(None, "0.3.1"), None);
+    }
+}
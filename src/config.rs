@@ -1,12 +1,14 @@
 //! Configuration management for cargo-tako
 
 use serde::{Deserialize, Serialize};
+use std::env;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use crate::error::{Error, Result};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct TakoConfig {
     #[serde(default)]
     pub package: PackageConfig,
@@ -16,6 +18,9 @@ pub struct TakoConfig {
 
     #[serde(default)]
     pub build: BuildConfig,
+
+    #[serde(default)]
+    pub ui: UiConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -49,6 +54,28 @@ pub struct BuildConfig {
 
     #[serde(default = "default_opt_level")]
     pub opt_level: String,
+
+    #[serde(default = "default_lto")]
+    pub lto: String,
+
+    #[serde(default = "default_codegen_units")]
+    pub codegen_units: u32,
+
+    #[serde(default = "default_panic")]
+    pub panic: String,
+
+    /// Directory the final artifact is copied to; `--out-dir` takes precedence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub out_dir: Option<String>,
+
+    /// File name of the copied artifact, e.g. `"{name}-{version}-{arch}.so"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub out_name: Option<String>,
+
+    /// Also place the latest artifact at target/deploy/<name>.so, where
+    /// Solana-style scripts expect it
+    #[serde(default = "default_target_deploy")]
+    pub target_deploy: bool,
 }
 
 impl Default for BuildConfig {
@@ -56,10 +83,33 @@ impl Default for BuildConfig {
         Self {
             target: default_target(),
             opt_level: default_opt_level(),
+            lto: default_lto(),
+            codegen_units: default_codegen_units(),
+            panic: default_panic(),
+            out_dir: None,
+            out_name: None,
+            target_deploy: default_target_deploy(),
         }
     }
 }
 
+/// Settings for the CLI's own output
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct UiConfig {
+    /// Language of status messages (en, zh); `TAKO_LANG` takes precedence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+
+    /// Color theme for the terminal background (dark, light); `TAKO_THEME`
+    /// takes precedence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+}
+
+fn default_target_deploy() -> bool {
+    true
+}
+
 fn default_entry() -> String {
     "entrypoint".to_string()
 }
@@ -76,11 +126,25 @@ fn default_opt_level() -> String {
     "z".to_string()
 }
 
+fn default_lto() -> String {
+    "fat".to_string()
+}
+
+fn default_codegen_units() -> u32 {
+    1
+}
+
+fn default_panic() -> String {
+    "abort".to_string()
+}
+
 impl TakoConfig {
-    #[allow(dead_code)]
+    /// Load a config file, expanding `${VAR}` references in string values
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = fs::read_to_string(path)?;
-        let config: TakoConfig = toml::from_str(&content)?;
+        let mut value: toml::Value = toml::from_str(&content)?;
+        interpolate_value(&mut value, &|name| env::var(name).ok())?;
+        let config: TakoConfig = value.try_into()?;
         Ok(config)
     }
 
@@ -89,6 +153,29 @@ impl TakoConfig {
         Self::load_from_file("Tako.toml")
     }
 
+    /// Load the nearest Tako.toml from the current directory or its parents,
+    /// falling back to defaults when there is none
+    ///
+    /// In a monorepo, a contract can share the Tako.toml of an enclosing
+    /// directory or override it with its own.
+    ///
+    /// A file selected with `--config` is used instead and must exist.
+    pub fn load_or_default() -> Result<Self> {
+        if let Some(path) = CONFIG_OVERRIDE.get() {
+            if !path.is_file() {
+                return Err(Error::Config(format!(
+                    "Config file {} not found",
+                    path.display()
+                )));
+            }
+            return Self::load_from_file(path);
+        }
+        match find_config_file(&env::current_dir()?) {
+            Some(path) => Self::load_from_file(path),
+            None => Ok(Self::default()),
+        }
+    }
+
     #[allow(dead_code)]
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         let content = toml::to_string_pretty(self).map_err(|e| Error::Config(e.to_string()))?;
@@ -96,3 +183,187 @@ impl TakoConfig {
         Ok(())
     }
 }
+
+/// Config file selected with the global `--config` option
+static CONFIG_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `path` instead of the nearest Tako.toml for the rest of the process
+///
+/// Relative paths are resolved against the current directory at the time of
+/// the call, so this must run before changing into the manifest directory.
+pub fn set_config_override(path: &Path) -> Result<()> {
+    let path = env::current_dir()?.join(path);
+    CONFIG_OVERRIDE
+        .set(path)
+        .map_err(|_| Error::Config("Config file already selected".to_string()))
+}
+
+/// Find the nearest Tako.toml in `start` or one of its ancestors
+pub fn find_config_file(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join("Tako.toml"))
+        .find(|path| path.is_file())
+}
+
+/// Load variables from a `.env` file into the process environment
+///
+/// Variables already set in the environment take precedence. Supports
+/// `KEY=value`, `export KEY=value`, `#` comments and quoted values.
+pub fn load_dotenv<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(path)?;
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line.split_once('=').ok_or_else(|| {
+            Error::Config(format!("{}:{}: expected KEY=value", path.display(), i + 1))
+        })?;
+        let key = key.trim();
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+            .unwrap_or(value);
+
+        if env::var_os(key).is_none() {
+            env::set_var(key, value);
+        }
+    }
+
+    Ok(())
+}
+
+/// Expand `${VAR}` references in every string of a TOML value
+fn interpolate_value(
+    value: &mut toml::Value,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<()> {
+    match value {
+        toml::Value::String(s) => *s = interpolate(s, lookup)?,
+        toml::Value::Array(items) => {
+            for item in items {
+                interpolate_value(item, lookup)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (_, item) in table.iter_mut() {
+                interpolate_value(item, lookup)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Expand `${VAR}` and `${VAR:-default}` in a string
+///
+/// `$${` produces a literal `${`. Referencing an unset variable without a
+/// default is an error, so missing secrets are caught before they are used.
+pub fn interpolate(input: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start..];
+
+        if let Some(escaped) = after.strip_prefix("$${") {
+            output.push_str("${");
+            rest = escaped;
+        } else if let Some(reference) = after.strip_prefix("${") {
+            let end = reference.find('}').ok_or_else(|| {
+                Error::Config(format!("Unterminated variable reference in '{input}'"))
+            })?;
+            let expr = &reference[..end];
+            let (name, default) = match expr.split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (expr, None),
+            };
+
+            let resolved = lookup(name)
+                .filter(|v| !v.is_empty() || default.is_none())
+                .or_else(|| default.map(String::from))
+                .ok_or_else(|| {
+                    Error::Config(format!("Environment variable '{name}' is not set"))
+                })?;
+            output.push_str(&resolved);
+            rest = &reference[end + 1..];
+        } else {
+            output.push('$');
+            rest = &after[1..];
+        }
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "RPC_URL" => Some("https://rpc.example".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_interpolate() {
+        assert_eq!(
+            interpolate("${RPC_URL}/v1", &lookup).unwrap(),
+            "https://rpc.example/v1"
+        );
+        assert_eq!(interpolate("cost: $5", &lookup).unwrap(), "cost: $5");
+        assert_eq!(interpolate("$${RPC_URL}", &lookup).unwrap(), "${RPC_URL}");
+    }
+
+    #[test]
+    fn test_interpolate_defaults() {
+        assert_eq!(interpolate("${MISSING:-v3}", &lookup).unwrap(), "v3");
+        assert_eq!(interpolate("${EMPTY:-v3}", &lookup).unwrap(), "v3");
+        assert!(interpolate("${MISSING}", &lookup).is_err());
+    }
+
+    #[test]
+    fn test_find_config_file() {
+        let root = std::env::temp_dir().join(format!("tako-config-{}", std::process::id()));
+        let contract = root.join("contracts").join("token");
+        let nested = contract.join("src").join("handlers");
+        fs::create_dir_all(&nested).unwrap();
+
+        // Nothing up to the temp root (the temp dir itself has no Tako.toml)
+        assert_eq!(find_config_file(&nested), None);
+
+        // Found from the directory itself and from below it
+        fs::write(root.join("Tako.toml"), "").unwrap();
+        assert_eq!(find_config_file(&root), Some(root.join("Tako.toml")));
+        assert_eq!(find_config_file(&nested), Some(root.join("Tako.toml")));
+
+        // The nearest one wins over one further up
+        fs::write(contract.join("Tako.toml"), "").unwrap();
+        assert_eq!(find_config_file(&nested), Some(contract.join("Tako.toml")));
+        assert_eq!(
+            find_config_file(&root.join("contracts")),
+            Some(root.join("Tako.toml"))
+        );
+
+        // A directory named Tako.toml is not a config file
+        fs::create_dir_all(nested.join("Tako.toml")).unwrap();
+        assert_eq!(find_config_file(&nested), Some(contract.join("Tako.toml")));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
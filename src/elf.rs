@@ -0,0 +1,256 @@
+//! ELF helpers for inspecting built contracts
+
+use colored::Colorize;
+use goblin::elf::dynamic::{tag_to_str, Dynamic, DT_NEEDED, DT_NULL, DT_SONAME};
+use goblin::elf::header::{et_to_str, machine_to_str};
+use goblin::elf::program_header::{pt_to_str, PF_R, PF_W, PF_X, PT_LOAD};
+use goblin::elf::section_header::{sht_to_str, SHF_ALLOC, SHF_EXECINSTR, SHF_WRITE};
+use goblin::elf::Elf;
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::style::Styled;
+
+/// Read a contract binary from disk
+pub fn read(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).map_err(|e| Error::Other(format!("Failed to read {}: {e}", path.display())))
+}
+
+/// Parse ELF bytes
+pub fn parse(bytes: &[u8]) -> Result<Elf<'_>> {
+    Elf::parse(bytes).map_err(|e| Error::Other(format!("Failed to parse ELF: {e}")))
+}
+
+/// Get the virtual address of a section by name
+pub fn section_address(elf: &Elf, name: &str) -> Option<u64> {
+    elf.section_headers
+        .iter()
+        .find(|sh| elf.shdr_strtab.get_at(sh.sh_name) == Some(name))
+        .map(|sh| sh.sh_addr)
+}
+
+/// Check whether the binary carries DWARF line information
+pub fn has_debug_info(elf: &Elf) -> bool {
+    section_address(elf, ".debug_line").is_some()
+}
+
+/// Address of a defined symbol, looked up in the dynamic symbol table first
+pub fn symbol_address(elf: &Elf, name: &str) -> Option<u64> {
+    elf.dynsyms
+        .iter()
+        .find(|sym| sym.st_shndx != 0 && elf.dynstrtab.get_at(sym.st_name) == Some(name))
+        .or_else(|| {
+            elf.syms
+                .iter()
+                .find(|sym| sym.st_shndx != 0 && elf.strtab.get_at(sym.st_name) == Some(name))
+        })
+        .map(|sym| sym.st_value)
+}
+
+/// Check whether a symbol is defined and exported in the dynamic symbol table
+pub fn is_exported(elf: &Elf, name: &str) -> bool {
+    elf.dynsyms
+        .iter()
+        .any(|sym| sym.st_shndx != 0 && elf.dynstrtab.get_at(sym.st_name) == Some(name))
+}
+
+/// Names of the defined function symbols at an address
+pub fn functions_at(elf: &Elf, address: u64) -> Vec<String> {
+    let dynamic = elf.dynsyms.iter().map(|sym| (sym, &elf.dynstrtab));
+    let local = elf.syms.iter().map(|sym| (sym, &elf.strtab));
+    let mut names: Vec<String> = dynamic
+        .chain(local)
+        .filter(|(sym, _)| sym.st_shndx != 0 && sym.is_function() && sym.st_value == address)
+        .filter_map(|(sym, strtab)| strtab.get_at(sym.st_name))
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Problems with the entrypoint a contract is configured with
+///
+/// The entry symbol must be exported and be the ELF entry point. When it is
+/// missing, the function at the ELF entry (or the only exported function
+/// named like an entrypoint) is suggested as `[contract] entry` instead.
+pub fn entry_problems(elf: &Elf, entry: &str) -> Vec<String> {
+    let e_entry = elf.header.e_entry;
+    let Some(address) = symbol_address(elf, entry) else {
+        let mut message = format!("Missing `{entry}` symbol");
+        if let Some(candidate) = detect_entry(elf) {
+            message.push_str(&format!(
+                " (found `{candidate}`; set `entry = \"{candidate}\"` under [contract] in Tako.toml)"
+            ));
+        }
+        return vec![message];
+    };
+
+    let mut problems = Vec::new();
+    if !is_exported(elf, entry) {
+        problems.push(format!(
+            "`{entry}` is not exported (declare it `#[no_mangle] pub extern \"C\"`)"
+        ));
+    }
+    if address != e_entry {
+        let actual = functions_at(elf, e_entry)
+            .first()
+            .map(|name| format!(" (`{name}`)"))
+            .unwrap_or_default();
+        problems.push(format!(
+            "ELF entry 0x{e_entry:x}{actual} is not `{entry}` at 0x{address:x}"
+        ));
+    }
+    problems
+}
+
+/// Guess the entrypoint of a binary built with a different entry name
+fn detect_entry(elf: &Elf) -> Option<String> {
+    if elf.header.e_entry != 0 {
+        if let Some(name) = functions_at(elf, elf.header.e_entry).into_iter().next() {
+            return Some(name);
+        }
+    }
+    let named: Vec<&str> = elf
+        .dynsyms
+        .iter()
+        .filter(|sym| sym.st_shndx != 0 && sym.is_function())
+        .filter_map(|sym| elf.dynstrtab.get_at(sym.st_name))
+        .filter(|name| name.to_lowercase().contains("entry"))
+        .collect();
+    match named.as_slice() {
+        [name] => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/// Print the ELF header, segments, sections and dynamic entries
+pub fn print_report(elf: &Elf) {
+    print_header(elf);
+    println!();
+    print_segments(elf);
+    println!();
+    print_sections(elf);
+    if let Some(dynamic) = &elf.dynamic {
+        println!();
+        print_dynamic(elf, dynamic);
+    }
+}
+
+fn print_header(elf: &Elf) {
+    let h = &elf.header;
+    println!("{}", "ELF Header".accent().bold());
+    println!(
+        "  Class:       {}",
+        if elf.is_64 { "ELF64" } else { "ELF32" }
+    );
+    println!(
+        "  Data:        {}",
+        if elf.little_endian {
+            "little endian"
+        } else {
+            "big endian"
+        }
+    );
+    println!("  Type:        {}", et_to_str(h.e_type));
+    println!(
+        "  Machine:     {} ({})",
+        machine_to_str(h.e_machine),
+        h.e_machine
+    );
+    println!("  Entry:       0x{:x}", h.e_entry);
+    println!("  Flags:       0x{:x}", h.e_flags);
+}
+
+fn print_segments(elf: &Elf) {
+    println!("{}", "Program Headers".accent().bold());
+    println!(
+        "  {:<16} {:>10} {:>18} {:>10} {:>10} {:<5}",
+        "Type", "Offset", "VirtAddr", "FileSize", "MemSize", "Flags"
+    );
+    for ph in &elf.program_headers {
+        let flags = format!(
+            "{}{}{}",
+            if ph.p_flags & PF_R != 0 { "R" } else { " " },
+            if ph.p_flags & PF_W != 0 { "W" } else { " " },
+            if ph.p_flags & PF_X != 0 { "E" } else { " " },
+        );
+        let line = format!(
+            "  {:<16} {:>10} {:>18} {:>10} {:>10} {:<5}",
+            pt_to_str(ph.p_type),
+            format!("0x{:x}", ph.p_offset),
+            format!("0x{:x}", ph.p_vaddr),
+            format!("0x{:x}", ph.p_filesz),
+            format!("0x{:x}", ph.p_memsz),
+            flags
+        );
+        if ph.p_type == PT_LOAD {
+            println!("{}", line.bold());
+        } else {
+            println!("{line}");
+        }
+    }
+}
+
+fn print_sections(elf: &Elf) {
+    println!("{}", "Section Headers".accent().bold());
+    println!(
+        "  {:<20} {:<16} {:>18} {:>10} {:>10} {:<5}",
+        "Name", "Type", "Address", "Offset", "Size", "Flags"
+    );
+    for sh in elf.section_headers.iter().skip(1) {
+        let name = elf.shdr_strtab.get_at(sh.sh_name).unwrap_or("?");
+        let flags = format!(
+            "{}{}{}",
+            if sh.sh_flags & u64::from(SHF_WRITE) != 0 {
+                "W"
+            } else {
+                ""
+            },
+            if sh.sh_flags & u64::from(SHF_ALLOC) != 0 {
+                "A"
+            } else {
+                ""
+            },
+            if sh.sh_flags & u64::from(SHF_EXECINSTR) != 0 {
+                "X"
+            } else {
+                ""
+            },
+        );
+        let line = format!(
+            "  {:<20} {:<16} {:>18} {:>10} {:>10} {:<5}",
+            name,
+            sht_to_str(sh.sh_type),
+            format!("0x{:x}", sh.sh_addr),
+            format!("0x{:x}", sh.sh_offset),
+            format!("0x{:x}", sh.sh_size),
+            flags
+        );
+        if sh.sh_flags & u64::from(SHF_EXECINSTR) != 0 {
+            println!("{}", line.success());
+        } else {
+            println!("{line}");
+        }
+    }
+}
+
+fn print_dynamic(elf: &Elf, dynamic: &Dynamic) {
+    println!("{}", "Dynamic Section".accent().bold());
+    for d in &dynamic.dyns {
+        if d.d_tag == DT_NULL {
+            continue;
+        }
+        let value = match d.d_tag {
+            DT_NEEDED | DT_SONAME => elf
+                .dynstrtab
+                .get_at(d.d_val as usize)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("0x{:x}", d.d_val)),
+            _ => format!("0x{:x}", d.d_val),
+        };
+        println!("  {:<20} {}", tag_to_str(d.d_tag), value);
+    }
+}
@@ -1,4 +1,7 @@
 //! Error types for cargo-tako
+//!
+//! Each failure category maps to its own process exit code (see
+//! [`Error::exit_code`]), so scripts and CI can branch on the kind of failure.
 
 use std::fmt;
 use std::io;
@@ -11,11 +14,63 @@ pub enum Error {
     ProjectExists(String),
     InvalidTemplate(String),
     BuildFailed(String),
+    VerifyFailed(String),
     TestFailed(String),
+    ToolchainMissing(String),
+    Network(String),
     Config(String),
     Other(String),
 }
 
+/// Exit code for errors without a more specific category
+pub const EXIT_FAILURE: i32 = 1;
+/// Exit code for invalid Tako.toml, Cargo.toml or other configuration
+pub const EXIT_CONFIG: i32 = 3;
+/// Exit code for a failed contract build
+pub const EXIT_BUILD: i32 = 4;
+/// Exit code for a contract that failed verification
+pub const EXIT_VERIFY: i32 = 5;
+/// Exit code for failed tests
+pub const EXIT_TEST: i32 = 6;
+/// Exit code for a missing toolchain program (cargo, llvm tools)
+pub const EXIT_TOOLCHAIN: i32 = 7;
+/// Exit code for a failed download or remote lookup
+pub const EXIT_NETWORK: i32 = 8;
+
+impl Error {
+    /// Process exit code for this error
+    ///
+    /// Code 2 is left to clap for usage errors and codes above 128 to
+    /// interrupts (`128 + signal`).
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Config(_) => EXIT_CONFIG,
+            Error::BuildFailed(_) => EXIT_BUILD,
+            Error::VerifyFailed(_) => EXIT_VERIFY,
+            Error::TestFailed(_) => EXIT_TEST,
+            Error::ToolchainMissing(_) => EXIT_TOOLCHAIN,
+            Error::Network(_) => EXIT_NETWORK,
+            Error::Io(_)
+            | Error::ProjectExists(_)
+            | Error::InvalidTemplate(_)
+            | Error::Other(_) => EXIT_FAILURE,
+        }
+    }
+
+    /// Error for a command that could not be started, e.g. `cargo metadata`
+    ///
+    /// A program missing from PATH is reported as a missing toolchain; other
+    /// failures are wrapped with `other`.
+    pub fn spawn(command: &str, err: io::Error, other: fn(String) -> Error) -> Error {
+        if err.kind() == io::ErrorKind::NotFound {
+            let program = command.split(' ').next().unwrap_or(command);
+            Error::ToolchainMissing(format!("{program} not found"))
+        } else {
+            other(format!("Failed to execute {command}: {err}"))
+        }
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -23,7 +78,10 @@ impl fmt::Display for Error {
             Error::ProjectExists(name) => write!(f, "Project '{name}' already exists"),
             Error::InvalidTemplate(name) => write!(f, "Invalid template: {name}"),
             Error::BuildFailed(msg) => write!(f, "Build failed: {msg}"),
+            Error::VerifyFailed(msg) => write!(f, "Verification failed: {msg}"),
             Error::TestFailed(msg) => write!(f, "Tests failed: {msg}"),
+            Error::ToolchainMissing(msg) => write!(f, "Toolchain missing: {msg}"),
+            Error::Network(msg) => write!(f, "Network error: {msg}"),
             Error::Config(msg) => write!(f, "Configuration error: {msg}"),
             Error::Other(msg) => write!(f, "{msg}"),
         }
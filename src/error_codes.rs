@@ -0,0 +1,385 @@
+//! Catalog of the custom error codes a contract returns
+//!
+//! Codes are read from the sources: explicit or implicit discriminants of
+//! error enums (enums named `*Error`), and `match` expressions mapping error
+//! variants to integer literals inside a `fn code(&self) -> u32` (or
+//! `fn *_code`) or a `From<MyError> for u64` / `Into<u64> for MyError` impl.
+//! Other matches are ignored. Two errors sharing a code are
+//! indistinguishable to clients, so collisions are reported by the checks.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use syn::visit::Visit;
+use walkdir::WalkDir;
+
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorCode {
+    pub code: i128,
+    /// Enum the variant belongs to
+    #[serde(rename = "enum")]
+    pub enum_name: String,
+    pub variant: String,
+    pub doc: String,
+}
+
+impl ErrorCode {
+    pub fn path(&self) -> String {
+        format!("{}::{}", self.enum_name, self.variant)
+    }
+}
+
+/// Extract the error codes defined under `src_dir`, sorted by code
+pub fn extract_catalog(src_dir: &Path) -> Result<Vec<ErrorCode>> {
+    let mut collector = CodeCollector::default();
+    for entry in WalkDir::new(src_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
+    {
+        let content = fs::read_to_string(entry.path())?;
+        let file = syn::parse_file(&content).map_err(|e| {
+            Error::Other(format!("Failed to parse {}: {e}", entry.path().display()))
+        })?;
+        collector.visit_file(&file);
+    }
+
+    // Codes assigned by a match take precedence over discriminants
+    let mut codes = collector.discriminants;
+    codes.extend(collector.mapped);
+    let mut catalog: Vec<ErrorCode> = codes
+        .into_iter()
+        .map(|((enum_name, variant), code)| ErrorCode {
+            code,
+            doc: collector
+                .docs
+                .get(&(enum_name.clone(), variant.clone()))
+                .cloned()
+                .unwrap_or_default(),
+            enum_name,
+            variant,
+        })
+        .collect();
+    catalog.sort_by_key(|e| (e.code, e.path()));
+    Ok(catalog)
+}
+
+/// Codes used by more than one error
+pub fn collisions(catalog: &[ErrorCode]) -> Vec<(i128, Vec<&ErrorCode>)> {
+    let mut by_code: BTreeMap<i128, Vec<&ErrorCode>> = BTreeMap::new();
+    for error in catalog {
+        by_code.entry(error.code).or_default().push(error);
+    }
+    by_code
+        .into_iter()
+        .filter(|(_, errors)| errors.len() > 1)
+        .collect()
+}
+
+#[derive(Default)]
+struct CodeCollector {
+    /// (enum, variant) -> doc comment
+    docs: BTreeMap<(String, String), String>,
+    /// (enum, variant) -> discriminant
+    discriminants: BTreeMap<(String, String), i128>,
+    /// (enum, variant) -> code assigned in a match
+    mapped: BTreeMap<(String, String), i128>,
+    /// Enum of the impl being visited, for `Self::Variant` patterns
+    current_impl: Option<String>,
+    /// Inside a function or impl that maps errors to codes
+    in_code_mapping: bool,
+}
+
+const INTEGER_TYPES: &[&str] = &[
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+/// Whether a function maps errors to codes by its name (`code`, `error_code`)
+fn is_code_fn(ident: &syn::Ident) -> bool {
+    let name = ident.to_string();
+    name == "code" || name.ends_with("_code")
+}
+
+/// Whether an impl converts between an error and an integer:
+/// `From<E> for u64` or `Into<u64> for E`
+fn is_code_conversion(item: &syn::ItemImpl) -> bool {
+    let Some((_, trait_path, _)) = &item.trait_ else {
+        return false;
+    };
+    let Some(segment) = trait_path.segments.last() else {
+        return false;
+    };
+    let is_integer = |ty: &syn::Type| match ty {
+        syn::Type::Path(p) => p
+            .path
+            .get_ident()
+            .is_some_and(|i| INTEGER_TYPES.contains(&i.to_string().as_str())),
+        _ => false,
+    };
+    let trait_arg_is_integer = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| match arg {
+            syn::GenericArgument::Type(ty) => is_integer(ty),
+            _ => false,
+        }),
+        _ => false,
+    };
+    match segment.ident.to_string().as_str() {
+        "From" => is_integer(&item.self_ty),
+        "Into" => trait_arg_is_integer,
+        _ => false,
+    }
+}
+
+impl<'ast> Visit<'ast> for CodeCollector {
+    fn visit_item_enum(&mut self, item: &'ast syn::ItemEnum) {
+        let name = item.ident.to_string();
+        if !name.ends_with("Error") {
+            return;
+        }
+        let explicit = item.variants.iter().any(|v| v.discriminant.is_some());
+        let mut next = 0;
+        for variant in &item.variants {
+            let key = (name.clone(), variant.ident.to_string());
+            if let Some((_, expr)) = &variant.discriminant {
+                match int_literal(expr) {
+                    Some(code) => next = code,
+                    // A computed discriminant; later implicit codes are unknown
+                    None => {
+                        next = i128::MIN;
+                        continue;
+                    }
+                }
+            }
+            if explicit && next != i128::MIN {
+                self.discriminants.insert(key.clone(), next);
+                next += 1;
+            }
+            self.docs.insert(key, doc_comment(&variant.attrs));
+        }
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast syn::ItemImpl) {
+        let previous = self.current_impl.take();
+        let previous_mapping = self.in_code_mapping;
+        if let syn::Type::Path(p) = item.self_ty.as_ref() {
+            self.current_impl = p.path.segments.last().map(|s| s.ident.to_string());
+        }
+        self.in_code_mapping = is_code_conversion(item);
+        syn::visit::visit_item_impl(self, item);
+        self.current_impl = previous;
+        self.in_code_mapping = previous_mapping;
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+        let previous = self.in_code_mapping;
+        self.in_code_mapping |= is_code_fn(&item.sig.ident);
+        syn::visit::visit_impl_item_fn(self, item);
+        self.in_code_mapping = previous;
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        let previous = self.in_code_mapping;
+        self.in_code_mapping = is_code_fn(&item.sig.ident);
+        syn::visit::visit_item_fn(self, item);
+        self.in_code_mapping = previous;
+    }
+
+    fn visit_expr_match(&mut self, expr: &'ast syn::ExprMatch) {
+        if !self.in_code_mapping {
+            syn::visit::visit_expr_match(self, expr);
+            return;
+        }
+        for arm in &expr.arms {
+            let Some(code) = int_literal(&arm.body) else {
+                continue;
+            };
+            for path in pattern_paths(&arm.pat) {
+                let segments: Vec<String> =
+                    path.segments.iter().map(|s| s.ident.to_string()).collect();
+                let (enum_name, variant) = match segments.as_slice() {
+                    [.., e, v] if e == "Self" => match &self.current_impl {
+                        Some(current) => (current.clone(), v.clone()),
+                        None => continue,
+                    },
+                    [.., e, v] => (e.clone(), v.clone()),
+                    _ => continue,
+                };
+                if enum_name.ends_with("Error") {
+                    self.mapped.insert((enum_name, variant), code);
+                }
+            }
+        }
+        syn::visit::visit_expr_match(self, expr);
+    }
+}
+
+/// Paths of the variants matched by a pattern (`A`, `A(..)`, `A { .. }`, `A | B`)
+fn pattern_paths(pat: &syn::Pat) -> Vec<&syn::Path> {
+    match pat {
+        syn::Pat::Path(p) => vec![&p.path],
+        syn::Pat::TupleStruct(p) => vec![&p.path],
+        syn::Pat::Struct(p) => vec![&p.path],
+        syn::Pat::Or(p) => p.cases.iter().flat_map(pattern_paths).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Value of an integer literal, possibly negated, cast or in a block
+fn int_literal(expr: &syn::Expr) -> Option<i128> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(i),
+            ..
+        }) => i.base10_parse().ok(),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => int_literal(expr).map(|v| -v),
+        syn::Expr::Cast(c) => int_literal(&c.expr),
+        syn::Expr::Paren(p) => int_literal(&p.expr),
+        syn::Expr::Block(b) if b.block.stmts.len() == 1 => match &b.block.stmts[0] {
+            syn::Stmt::Expr(e, None) => int_literal(e),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn doc_comment(attrs: &[syn::Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            syn::Meta::NameValue(nv) => match &nv.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) => Some(s.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn catalog(source: &str) -> Vec<ErrorCode> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "tako-errors-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("lib.rs"), source).unwrap();
+        let catalog = extract_catalog(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        catalog
+    }
+
+    #[test]
+    fn test_extract_catalog() {
+        let errors = catalog(
+            r#"
+pub enum TokenError {
+    /// Balance too low
+    InsufficientFunds = 6000,
+    Unauthorized,
+    Paused = 6010,
+}
+
+pub enum VaultError {
+    Locked = 6001,
+}
+
+pub enum Kind { A = 1 }
+
+impl VaultError {
+    pub fn code(&self) -> u32 {
+        match self {
+            Self::Locked => 6010,
+        }
+    }
+}
+"#,
+        );
+        let codes: Vec<(i128, String)> = errors.iter().map(|e| (e.code, e.path())).collect();
+        assert_eq!(
+            codes,
+            [
+                (6000, "TokenError::InsufficientFunds".to_string()),
+                (6001, "TokenError::Unauthorized".to_string()),
+                (6010, "TokenError::Paused".to_string()),
+                (6010, "VaultError::Locked".to_string()),
+            ]
+        );
+        assert_eq!(errors[0].doc, "Balance too low");
+
+        let collisions = collisions(&errors);
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].0, 6010);
+    }
+
+    #[test]
+    fn test_unrelated_matches_ignored() {
+        let errors = catalog(
+            r#"
+pub enum TokenError {
+    InsufficientFunds = 6000,
+    Unauthorized = 6001,
+}
+
+impl TokenError {
+    fn severity(&self) -> u8 {
+        match self {
+            Self::InsufficientFunds => 1,
+            Self::Unauthorized => 1,
+        }
+    }
+}
+
+fn retries(e: &TokenError) -> u32 {
+    match e {
+        TokenError::InsufficientFunds => 3,
+        _ => 0,
+    }
+}
+
+pub enum VaultError {
+    Locked,
+    Closed,
+}
+
+impl From<VaultError> for u64 {
+    fn from(e: VaultError) -> u64 {
+        match e {
+            VaultError::Locked => 7000,
+            VaultError::Closed => 7001,
+        }
+    }
+}
+"#,
+        );
+        let codes: Vec<(i128, String)> = errors.iter().map(|e| (e.code, e.path())).collect();
+        assert_eq!(
+            codes,
+            [
+                (6000, "TokenError::InsufficientFunds".to_string()),
+                (6001, "TokenError::Unauthorized".to_string()),
+                (7000, "VaultError::Locked".to_string()),
+                (7001, "VaultError::Closed".to_string()),
+            ]
+        );
+        assert!(collisions(&errors).is_empty());
+    }
+}
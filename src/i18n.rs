@@ -0,0 +1,235 @@
+//! Message catalog for user-facing CLI output
+//!
+//! Status lines are looked up by key in the language selected with the
+//! `TAKO_LANG` environment variable or `[ui] lang` in Tako.toml, falling back
+//! to English. Only status lines are translated: error messages and the
+//! detail lines commands print stay in English, and machine-readable output
+//! (JSON reports, `env` statements, files written to disk) never goes through
+//! the catalog, so it stays the same in every locale.
+
+use std::env;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    Zh,
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// Catalog entries: (key, English, Chinese)
+///
+/// `{name}` placeholders are filled in by [`format`]; both translations must
+/// use the same ones.
+const MESSAGES: &[(&str, &str, &str)] = &[
+    // Status verbs, printed in color before the subject
+    ("status.analyzing", "Analyzing", "分析"),
+    ("status.building", "Building", "构建"),
+    ("status.checking", "Checking", "检查"),
+    ("status.cleaning", "Cleaning", "清理"),
+    ("status.comparing", "Comparing", "比较"),
+    ("status.configuring", "Configuring", "配置"),
+    ("status.creating", "Creating", "创建"),
+    ("status.debugging", "Debugging", "调试"),
+    ("status.documenting", "Documenting", "生成文档"),
+    ("status.dumping", "Dumping", "导出"),
+    ("status.initializing", "Initializing", "初始化"),
+    ("status.migrating", "Migrating", "迁移"),
+    ("status.pruning", "Pruning", "清理"),
+    ("status.reading", "Reading", "读取"),
+    ("status.renaming", "Renaming", "重命名"),
+    ("status.running", "Running", "运行"),
+    ("status.symbolizing", "Symbolizing", "符号化"),
+    ("status.updating", "Updating", "更新"),
+    ("status.validating", "Validating", "校验"),
+    ("status.vendoring", "Vendoring", "打包"),
+    ("status.verifying", "Verifying", "验证"),
+    // Subjects and results
+    (
+        "new.subject",
+        "TAKO contract project...",
+        "TAKO 合约项目...",
+    ),
+    (
+        "new.created",
+        "Created contract project: {name}",
+        "已创建合约项目：{name}",
+    ),
+    ("new.next_steps", "Next steps:", "后续步骤："),
+    (
+        "init.subject",
+        "TAKO in current project...",
+        "当前项目的 TAKO 配置...",
+    ),
+    ("init.done", "TAKO initialized", "TAKO 已初始化"),
+    (
+        "template.subject",
+        "template {template}...",
+        "模板 {template}...",
+    ),
+    ("build.subject", "TAKO contract...", "TAKO 合约..."),
+    ("build.done", "Built contract:", "合约构建完成："),
+    ("build.binary", "Binary: {path}", "二进制文件：{path}"),
+    ("build.size", "Size: {size} bytes", "大小：{size} 字节"),
+    ("build.arch", "Arch: {arch}", "架构：{arch}"),
+    ("build.copied", "Copied to: {path}", "已复制到：{path}"),
+    (
+        "build.optimization",
+        "Optimization: {summary}",
+        "优化：{summary}",
+    ),
+    ("verify.subject", "contract...", "合约..."),
+    ("verify.done", "Contract verified", "合约验证通过"),
+    ("dump.subject", "ELF information...", "ELF 信息..."),
+    ("check.subject", "contract sources...", "合约源码..."),
+    ("compat.subject", "SDK compatibility...", "SDK 兼容性..."),
+    ("ci.subject", "CI checks...", "CI 检查..."),
+    ("ci.done", "All CI checks passed", "所有 CI 检查均已通过"),
+    ("ide.subject", "rust-analyzer...", "rust-analyzer..."),
+    (
+        "migrate.subject",
+        "Solana dependencies...",
+        "Solana 依赖...",
+    ),
+    (
+        "prune.subject",
+        "unused platform-tools versions...",
+        "未使用的 platform-tools 版本...",
+    ),
+    ("rename.subject", "contract project...", "合约项目..."),
+    (
+        "sdk.subject",
+        "TAKO SDK dependencies...",
+        "TAKO SDK 依赖...",
+    ),
+    ("vendor.subject", "dependencies...", "依赖..."),
+    (
+        "verify_build.subject",
+        "build environment...",
+        "构建环境...",
+    ),
+    ("test.subject", "tests...", "测试..."),
+    ("test.seed", "Seed: {seed}", "随机种子：{seed}"),
+    (
+        "test.bless",
+        "Blessing golden files in tests/golden/",
+        "正在更新 tests/golden/ 中的基准文件",
+    ),
+    ("debug.subject", "test {test}...", "测试 {test}..."),
+    ("clean.subject", "build artifacts...", "构建产物..."),
+    ("clean.done", "Build artifacts removed", "构建产物已删除"),
+    ("info.subject", "contract information...", "合约信息..."),
+    ("symbolize.subject", "addresses...", "地址..."),
+    ("asm.subject", "assembly...", "汇编..."),
+    ("tree.subject", "linked crates...", "链接的 crate..."),
+    ("docs.subject", "contract...", "合约..."),
+    ("storage.subject", "storage layout...", "存储布局..."),
+];
+
+/// Select the output language
+///
+/// # Arguments
+/// * `configured` - `[ui] lang` from Tako.toml, used when `TAKO_LANG` is unset
+pub fn init(configured: Option<&str>) {
+    let lang = env::var("TAKO_LANG")
+        .ok()
+        .and_then(|name| parse_lang(&name))
+        .or_else(|| configured.and_then(parse_lang))
+        .unwrap_or(Lang::En);
+    let _ = LANG.set(lang);
+}
+
+/// The selected output language
+pub fn lang() -> Lang {
+    LANG.get().copied().unwrap_or(Lang::En)
+}
+
+/// Parse a language name such as `zh`, `zh_CN.UTF-8` or `en-US`
+fn parse_lang(name: &str) -> Option<Lang> {
+    let code = name
+        .split(['_', '-', '.'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match code.as_str() {
+        "en" | "c" | "posix" => Some(Lang::En),
+        "zh" => Some(Lang::Zh),
+        _ => None,
+    }
+}
+
+/// Look up a message; unknown keys are returned as is
+pub fn tr(key: &'static str) -> &'static str {
+    tr_in(lang(), key)
+}
+
+fn tr_in(lang: Lang, key: &'static str) -> &'static str {
+    match MESSAGES.iter().find(|(k, _, _)| *k == key) {
+        Some((_, en, zh)) => match lang {
+            Lang::En => en,
+            Lang::Zh => zh,
+        },
+        None => key,
+    }
+}
+
+/// Look up a message and fill in its `{name}` placeholders
+pub fn format(key: &'static str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    fill(tr(key), args)
+}
+
+fn fill(message: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    args.iter()
+        .fold(message.to_string(), |message, (name, value)| {
+            message.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+}
+
+/// Translate a catalog message: `t!("build.done")` or
+/// `t!("build.size", size = bytes)`
+macro_rules! t {
+    ($key:literal) => {
+        $crate::i18n::tr($key)
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::format($key, &[$((stringify!($name), &$value as &dyn std::fmt::Display)),+])
+    };
+}
+pub(crate) use t;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(message: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = message
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_catalog() {
+        for (i, (key, en, zh)) in MESSAGES.iter().enumerate() {
+            assert!(
+                !MESSAGES[..i].iter().any(|(k, _, _)| k == key),
+                "duplicate key {key}"
+            );
+            assert_eq!(placeholders(en), placeholders(zh), "placeholders of {key}");
+        }
+
+        assert_eq!(parse_lang("zh_CN.UTF-8"), Some(Lang::Zh));
+        assert_eq!(parse_lang("en-US"), Some(Lang::En));
+        assert_eq!(parse_lang("fr"), None);
+
+        assert_eq!(tr_in(Lang::Zh, "verify.done"), "合约验证通过");
+        assert_eq!(
+            fill(tr_in(Lang::En, "build.size"), &[("size", &1024)]),
+            "Size: 1024 bytes"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Ctrl-C (SIGINT) and SIGTERM handling
+//!
+//! The signal handler only records the signal. A monitor thread then stops
+//! child processes registered with [`track_child`], removes paths registered
+//! with [`remove_on_interrupt`] and exits with `128 + signal`, so an
+//! interrupted build or test run leaves neither orphan processes nor half
+//! written temporary projects behind. A second signal exits immediately.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::logs;
+
+/// Child processes to stop on interrupt
+static CHILDREN: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+/// Files and directories to remove on interrupt
+static CLEANUP: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Set while an interactive child (a debugger) owns Ctrl-C
+static INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Install the signal handlers and start the monitor thread
+pub fn install() {
+    #[cfg(unix)]
+    unix::install();
+}
+
+/// Stop `child` if the command is interrupted while it runs
+pub fn track_child(child: &Child) {
+    if let Ok(mut children) = CHILDREN.lock() {
+        children.push(child.id());
+    }
+}
+
+/// Stop tracking a child that has exited
+pub fn untrack_child(child: &Child) {
+    if let Ok(mut children) = CHILDREN.lock() {
+        children.retain(|pid| *pid != child.id());
+    }
+}
+
+/// Like [`Command::output`], stopping the child if the command is interrupted
+/// and writing a log (see [`logs`])
+pub fn output(cmd: &mut Command) -> io::Result<Output> {
+    let child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    track_child(&child);
+    let pid = child.id();
+    let output = child.wait_with_output();
+    if let Ok(mut children) = CHILDREN.lock() {
+        children.retain(|p| *p != pid);
+    }
+    if let Ok(output) = &output {
+        logs::record(cmd, &output.status, &output.stdout, &output.stderr);
+    }
+    output
+}
+
+/// Remove `path` (a file or directory) if the command is interrupted
+pub fn remove_on_interrupt(path: &Path) {
+    if let Ok(mut paths) = CLEANUP.lock() {
+        paths.push(path.to_path_buf());
+    }
+}
+
+/// Keep `path` on interrupt again, e.g. once it is complete
+pub fn keep(path: &Path) {
+    if let Ok(mut paths) = CLEANUP.lock() {
+        paths.retain(|p| p != path);
+    }
+}
+
+/// Let Ctrl-C reach an interactive child instead of stopping the command
+///
+/// Debuggers use Ctrl-C to pause the program they run, so it must not end
+/// cargo-tako while one is in the foreground. SIGTERM is still handled.
+pub fn set_interactive(interactive: bool) {
+    INTERACTIVE.store(interactive, Ordering::SeqCst);
+}
+
+/// Stop tracked children and remove registered paths
+#[cfg_attr(not(unix), allow(dead_code))]
+fn clean_up() {
+    #[cfg(unix)]
+    if let Ok(children) = CHILDREN.lock() {
+        for pid in children.iter() {
+            // SAFETY: sending a signal has no memory safety requirements
+            unsafe {
+                libc::kill(*pid as libc::pid_t, libc::SIGTERM);
+            }
+        }
+    }
+
+    if let Ok(paths) = CLEANUP.lock() {
+        for path in paths.iter() {
+            let _ = if path.is_dir() {
+                std::fs::remove_dir_all(path)
+            } else {
+                std::fs::remove_file(path)
+            };
+        }
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    /// Signal received, or 0
+    static SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+    extern "C" fn on_signal(signal: libc::c_int) {
+        if signal == libc::SIGINT && super::INTERACTIVE.load(Ordering::SeqCst) {
+            return;
+        }
+        if SIGNAL.swap(signal, Ordering::SeqCst) != 0 {
+            // Second signal while cleaning up
+            // SAFETY: _exit is async-signal-safe
+            unsafe { libc::_exit(128 + signal) };
+        }
+    }
+
+    pub fn install() {
+        let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        // SAFETY: the handler only touches atomics and calls _exit
+        unsafe {
+            libc::signal(libc::SIGINT, handler);
+            libc::signal(libc::SIGTERM, handler);
+        }
+
+        thread::spawn(|| loop {
+            thread::sleep(Duration::from_millis(100));
+            let signal = SIGNAL.load(Ordering::SeqCst);
+            if signal == 0 {
+                continue;
+            }
+            eprintln!();
+            eprintln!("Interrupted, cleaning up...");
+            super::clean_up();
+            std::process::exit(128 + signal);
+        });
+    }
+}
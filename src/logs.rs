@@ -0,0 +1,242 @@
+//! Full logs of external commands in target/tako/logs/
+//!
+//! Every run of cargo, an LLVM tool, git, tar or curl writes its command
+//! line, environment overrides, exit status and complete stdout and stderr
+//! to `target/tako/logs/<timestamp>-<program>.log`, so a failure can be
+//! investigated beyond the excerpt in the error message. Output shown on
+//! the terminal as the command runs is written to the log as well. Logs go
+//! to the project the command runs in, found from its working directory. The
+//! log of the last failed command is printed with the error.
+
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::commands::clang;
+use crate::interrupt;
+use crate::util::tako_dir;
+
+/// Logs kept in target/tako/logs/; older ones are removed
+const MAX_LOGS: usize = 100;
+
+/// Log of the last command that failed
+static LAST_FAILED: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Directory the logs are written to
+pub fn logs_dir() -> PathBuf {
+    tako_dir().join("logs")
+}
+
+/// Like [`Command::output`], also writing a log
+pub fn output(cmd: &mut Command) -> io::Result<Output> {
+    let output = cmd.output()?;
+    record(cmd, &output.status, &output.stdout, &output.stderr);
+    Ok(output)
+}
+
+/// Like [`Command::status`], passing stdout and stderr through to the
+/// terminal while also writing them to the log
+pub fn status(cmd: &mut Command) -> io::Result<ExitStatus> {
+    // cargo stops coloring output that is not a terminal; keep the colors
+    // the user would have seen without the pipe
+    let colored = colored::control::SHOULD_COLORIZE.should_colorize();
+    let color_set = env::var_os("CARGO_TERM_COLOR").is_some()
+        || cmd.get_envs().any(|(key, _)| key == "CARGO_TERM_COLOR");
+    if colored && io::stderr().is_terminal() && !color_set {
+        cmd.env("CARGO_TERM_COLOR", "always");
+    }
+
+    let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    interrupt::track_child(&child);
+    let stdout = child.stdout.take().map(|pipe| tee(pipe, io::stdout()));
+    let stderr = child.stderr.take().map(|pipe| tee(pipe, io::stderr()));
+    let status = child.wait();
+    interrupt::untrack_child(&child);
+
+    let collect = |tee: Option<JoinHandle<Vec<u8>>>| {
+        tee.and_then(|handle| handle.join().ok())
+            .unwrap_or_default()
+    };
+    let (stdout, stderr) = (collect(stdout), collect(stderr));
+    let status = status?;
+    record(cmd, &status, &stdout, &stderr);
+    Ok(status)
+}
+
+/// Copy a child's output to `sink` as it arrives, returning all of it
+fn tee<R, W>(mut pipe: R, mut sink: W) -> JoinHandle<Vec<u8>>
+where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut captured = Vec::new();
+        let mut buf = [0; 8192];
+        while let Ok(n) = pipe.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            let _ = sink.write_all(&buf[..n]);
+            let _ = sink.flush();
+            captured.extend_from_slice(&buf[..n]);
+        }
+        captured
+    })
+}
+
+/// Write the log of a finished command
+///
+/// Logging never fails the command: outside a cargo or C contract project,
+/// or if the log cannot be written, nothing is recorded.
+pub fn record(cmd: &Command, status: &ExitStatus, stdout: &[u8], stderr: &[u8]) {
+    let Some(root) = project_root(cmd) else {
+        return;
+    };
+    let Ok(path) = write(&root.join(logs_dir()), cmd, status, stdout, stderr) else {
+        return;
+    };
+    if !status.success() {
+        if let Ok(mut last) = LAST_FAILED.lock() {
+            *last = Some(path);
+        }
+    }
+}
+
+/// Log of the last command that failed, if any
+pub fn last_failed() -> Option<PathBuf> {
+    LAST_FAILED.lock().ok()?.clone()
+}
+
+/// Project a command runs in: the nearest directory at or above its working
+/// directory with a Cargo.toml or a C contract
+///
+/// Commands run with `current_dir` (e.g. `cargo check` in a project created
+/// by `cargo tako new`) are logged to that project.
+fn project_root(cmd: &Command) -> Option<PathBuf> {
+    let cwd = env::current_dir().ok()?;
+    let start = match cmd.get_current_dir() {
+        Some(dir) => cwd.join(dir),
+        None => cwd,
+    };
+    start
+        .ancestors()
+        .find(|dir| dir.join("Cargo.toml").is_file() || dir.join(clang::C_ENTRY).is_file())
+        .map(Path::to_path_buf)
+}
+
+fn write(
+    dir: &Path,
+    cmd: &Command,
+    status: &ExitStatus,
+    stdout: &[u8],
+    stderr: &[u8],
+) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+
+    let program = Path::new(cmd.get_program())
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "command".to_string());
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let mut path = dir.join(format!("{millis}-{program}.log"));
+    let mut n = 1;
+    while path.exists() {
+        n += 1;
+        path = dir.join(format!("{millis}-{program}-{n}.log"));
+    }
+
+    fs::write(&path, render(cmd, status, stdout, stderr))?;
+    prune(dir);
+    Ok(path)
+}
+
+fn render(cmd: &Command, status: &ExitStatus, stdout: &[u8], stderr: &[u8]) -> String {
+    let mut log = format!("$ {}", cmd.get_program().to_string_lossy());
+    for arg in cmd.get_args() {
+        log.push(' ');
+        log.push_str(&arg.to_string_lossy());
+    }
+    log.push('\n');
+    if let Some(dir) = cmd.get_current_dir() {
+        log.push_str(&format!("cwd: {}\n", dir.display()));
+    }
+    for (key, value) in cmd.get_envs() {
+        let value = value.map(|v| v.to_string_lossy()).unwrap_or_default();
+        log.push_str(&format!("env: {}={value}\n", key.to_string_lossy()));
+    }
+    log.push_str(&format!("exit: {status}\n"));
+    log.push_str("\n--- stdout ---\n");
+    log.push_str(&String::from_utf8_lossy(stdout));
+    log.push_str("\n--- stderr ---\n");
+    log.push_str(&String::from_utf8_lossy(stderr));
+    log
+}
+
+/// Remove the oldest logs beyond [`MAX_LOGS`]
+fn prune(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut logs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "log"))
+        .collect();
+    if logs.len() <= MAX_LOGS {
+        return;
+    }
+    // Timestamps have the same width, so names sort by age
+    logs.sort();
+    for old in &logs[..logs.len() - MAX_LOGS] {
+        let _ = fs::remove_file(old);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_status_logs_output() {
+        let root = env::temp_dir().join(format!("tako-logs-{}", std::process::id()));
+        let subdir = root.join("src");
+        fs::create_dir_all(&subdir).unwrap();
+        fs::write(root.join("Cargo.toml"), "").unwrap();
+
+        // Run from a subdirectory of the project: the log goes to its root
+        let status = status(
+            Command::new("sh")
+                .args(["-c", "echo to-stdout; echo to-stderr >&2; exit 3"])
+                .current_dir(&subdir),
+        )
+        .unwrap();
+        assert_eq!(status.code(), Some(3));
+
+        let logs: Vec<PathBuf> = fs::read_dir(root.join(logs_dir()))
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(logs.len(), 1);
+        let content = fs::read_to_string(&logs[0]).unwrap();
+        assert!(content.contains("--- stdout ---\nto-stdout\n"));
+        assert!(content.contains("--- stderr ---\nto-stderr\n"));
+
+        // Outside any project nothing is recorded
+        fs::remove_file(root.join("Cargo.toml")).unwrap();
+        assert_eq!(
+            project_root(Command::new("true").current_dir(&subdir)),
+            None
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -3,18 +3,47 @@
 //! This tool provides a streamlined workflow for creating, building, testing,
 //! and deploying TAKO smart contracts.
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 use colored::Colorize;
+use std::path::{Path, PathBuf};
 
+mod address_book;
+mod arch_compat;
+mod build_info;
 mod commands;
+mod compat;
 mod config;
+mod elf;
 mod error;
+mod error_codes;
+mod i18n;
+mod interrupt;
+mod logs;
+mod manifest;
+mod metadata;
+mod progress;
+mod schema;
+mod size;
+mod source_map;
+mod std_audit;
+mod style;
+mod syscalls;
+mod target_spec;
 mod template;
+mod test_report;
 mod toolchain;
 mod util;
+mod watch;
 
-use commands::{build, init, test};
+use commands::{
+    addresses, asm, build, check, ci, clang, debug, docs, env, explain, hexdump, ide, init, rename,
+    sdk, solana, storage, symbolize, test, tree, vendor, version,
+};
+use config::TakoConfig;
 use error::Result;
+use i18n::t;
+use style::Styled;
+use target_spec::TargetSpec;
 
 #[derive(Parser)]
 #[command(name = "cargo")]
@@ -32,9 +61,34 @@ enum Commands {
 }
 
 #[derive(Parser)]
+#[command(arg_required_else_help = true)]
 struct TakoArgs {
+    /// Path to the contract's Cargo.toml (defaults to the current directory)
+    #[arg(long, global = true, value_name = "PATH")]
+    manifest_path: Option<PathBuf>,
+
+    /// Use this config file instead of the nearest Tako.toml (e.g. tako.staging.toml)
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Disable colored output (also disabled by NO_COLOR, CI, or when not writing to a terminal)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Print version information
+    #[arg(short = 'V', long)]
+    version: bool,
+
+    /// With --version: also print the git commit, platform-tools, rustc and supported architectures
+    #[arg(short, long, requires = "version")]
+    verbose: bool,
+
+    /// With --version: output format
+    #[arg(long, default_value = "human", value_parser = ["human", "json"], requires = "version")]
+    message_format: String,
+
     #[command(subcommand)]
-    command: TakoCommands,
+    command: Option<TakoCommands>,
 }
 
 #[derive(Subcommand)]
@@ -48,16 +102,38 @@ enum TakoCommands {
         #[arg(long)]
         path: Option<String>,
 
-        /// Use a specific template (default, erc20, erc721, empty)
+        /// Use a specific template (default, erc20, erc721, empty, upgradeable, c) or a template directory
         #[arg(long, default_value = "default")]
         template: String,
+
+        /// Set a template variable (e.g. --var license=MIT --var with_tests=false)
+        #[arg(long = "var", value_name = "KEY=VALUE")]
+        vars: Vec<String>,
     },
 
     /// Initialize TAKO in an existing Rust project
     Init {
-        /// Use a specific template (default, erc20, erc721, empty)
+        /// Use a specific template (default, erc20, erc721, empty, upgradeable, c) or a template directory
         #[arg(long, default_value = "default")]
         template: String,
+
+        /// Set a template variable (e.g. --var license=MIT --var with_tests=false)
+        #[arg(long = "var", value_name = "KEY=VALUE")]
+        vars: Vec<String>,
+
+        /// Replace an existing .cargo/config.toml and template sources (keeping .bak copies)
+        #[arg(long)]
+        force: bool,
+
+        /// Show the changes to Cargo.toml, .cargo/config.toml and sources without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Work with project templates
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommands,
     },
 
     /// Build the TAKO smart contract
@@ -66,14 +142,22 @@ enum TakoCommands {
         #[arg(long)]
         release: bool,
 
-        /// TBPF architecture version (v0, v1, v2, v3, v4)
-        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4"])]
+        /// TBPF architecture version (v0, v1, v2, v3, v4; sbfv1-sbfv3, sbf and bpf with --sbf-compat)
+        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4", "sbfv1", "sbfv2", "sbfv3", "sbf", "bpf"])]
         arch: String,
 
         /// Target to build for (auto-detected from arch if not specified)
         #[arg(long)]
         target: Option<String>,
 
+        /// Custom target specification (JSON) to build for instead of a built-in target
+        #[arg(long, value_name = "PATH", conflicts_with = "target")]
+        target_spec: Option<PathBuf>,
+
+        /// Contract language (detected from src/contract.c if not specified)
+        #[arg(long, value_parser = ["rust", "c"])]
+        lang: Option<String>,
+
         /// Verify the built contract
         #[arg(long)]
         verify: bool,
@@ -81,6 +165,174 @@ enum TakoCommands {
         /// Dump ELF information after build
         #[arg(long)]
         dump: bool,
+
+        /// Optimization preset for release builds (size, speed)
+        #[arg(long, value_parser = ["size", "speed"])]
+        optimize: Option<String>,
+
+        /// Link-time optimization for release builds (overrides `[build] lto`)
+        #[arg(long, value_parser = ["fat", "thin", "off", "true", "false"])]
+        lto: Option<String>,
+
+        /// Panic strategy for release builds (overrides `[build] panic`)
+        #[arg(long, value_parser = ["abort", "unwind"])]
+        panic: Option<String>,
+
+        /// Run cargo with --offline (use vendored dependencies)
+        #[arg(long)]
+        offline_cargo: bool,
+
+        /// Build for every architecture (v0-v4)
+        #[arg(long, conflicts_with_all = ["arch", "target", "target_spec", "dump"])]
+        all_archs: bool,
+
+        /// With --all-archs, continue after a failed build and report all failures
+        #[arg(long, requires = "all_archs")]
+        keep_going: bool,
+
+        /// Copy the artifact, its build info and source map to this directory
+        /// (named by `[build] out_name`, default `{name}.so`)
+        #[arg(long)]
+        out_dir: Option<PathBuf>,
+
+        /// Accept cargo-build-sbf flag spellings (--arch sbfv2, --sbf-out-dir)
+        #[arg(long)]
+        sbf_compat: bool,
+
+        /// cargo-build-sbf's output directory; receives <lib_name>.so
+        #[arg(long, requires = "sbf_compat", conflicts_with = "out_dir")]
+        sbf_out_dir: Option<PathBuf>,
+    },
+
+    /// Move Cargo.toml from Solana crates to the TAKO SDK and list what to port by hand
+    MigrateFromSolana {
+        /// Show the change to Cargo.toml without writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check the contract sources for configuration problems
+    Check {
+        /// Also compile a probe without std to find dependencies that pull it in
+        #[arg(long)]
+        no_std_audit: bool,
+
+        /// Output format; `json` type-checks for the TBPF target and prints
+        /// cargo's JSON messages (for rust-analyzer)
+        #[arg(long, default_value = "human", value_parser = ["human", "json"])]
+        message_format: String,
+
+        /// TBPF architecture version for --message-format json
+        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4"])]
+        arch: String,
+
+        /// Custom target specification (JSON) to validate and check against
+        #[arg(long, value_name = "PATH")]
+        target_spec: Option<PathBuf>,
+    },
+
+    /// Show the SDK compatibility table and check the project against it
+    Compat {
+        /// TBPF architecture version (v0, v1, v2, v3, v4)
+        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4"])]
+        arch: String,
+
+        /// Download the latest compatibility table first
+        #[arg(long)]
+        refresh: bool,
+    },
+
+    /// Run fmt, clippy, lint, build --release --verify and tests as one CI gate
+    Ci {
+        /// TBPF architecture version (v0, v1, v2, v3, v4)
+        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4"])]
+        arch: String,
+
+        /// Skip a step (can be repeated)
+        #[arg(long, value_parser = ["fmt", "clippy", "lint", "build", "test"])]
+        skip: Vec<String>,
+    },
+
+    /// Configure editors for TBPF development
+    Ide {
+        #[command(subcommand)]
+        command: IdeCommands,
+    },
+
+    /// Print the environment used for builds as shell statements
+    Env {
+        /// TBPF architecture version (v0, v1, v2, v3, v4)
+        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4"])]
+        arch: String,
+
+        /// Shell syntax to print
+        #[arg(long, default_value = "bash", value_parser = ["bash", "fish", "powershell"])]
+        shell: String,
+    },
+
+    /// Rename the contract project (package, lib and type names, address book entries)
+    Rename {
+        /// New project name
+        new_name: String,
+
+        /// Show the changes without writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Pin the TAKO SDK crates in Cargo.toml to a release tag
+    UpdateSdk {
+        /// Tag to pin to (defaults to the newest release in the compatibility table
+        /// that the installed platform-tools supports)
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Show the change to Cargo.toml without writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Vendor dependencies, including build-std sources, for offline builds
+    Vendor {
+        /// Vendor directory
+        #[arg(long, default_value = "vendor")]
+        dir: String,
+    },
+
+    /// Manage installed platform-tools versions
+    Toolchain {
+        #[command(subcommand)]
+        command: ToolchainCommands,
+    },
+
+    /// Verify a built contract without rebuilding
+    Verify {
+        /// Path to the contract binary (defaults to the latest build)
+        #[arg(long)]
+        contract: Option<String>,
+
+        /// TBPF architecture version (v0, v1, v2, v3, v4)
+        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4"])]
+        arch: String,
+
+        /// Also check segment layout, symbols and relocations
+        #[arg(long)]
+        strict: bool,
+
+        /// Re-run all checks even if this binary was verified before
+        #[arg(long)]
+        no_cache: bool,
+    },
+
+    /// Compare the build environment of the latest build with a reference build
+    VerifyBuild {
+        /// tako-build-info.json of the reference build
+        #[arg(long)]
+        against: PathBuf,
+
+        /// TBPF architecture version (v0, v1, v2, v3, v4)
+        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4"])]
+        arch: String,
     },
 
     /// Run tests for the smart contract
@@ -91,6 +343,48 @@ enum TakoCommands {
         /// Run tests in release mode
         #[arg(long)]
         release: bool,
+
+        /// Re-run tests whenever sources change
+        #[arg(long)]
+        watch: bool,
+
+        /// Run tests affected by changed files first, then the rest
+        #[arg(long)]
+        changed: bool,
+
+        /// Show output printed by tests as they run
+        #[arg(long)]
+        no_capture: bool,
+
+        /// Run doctests and compile examples/ for the TBPF target instead of the unit tests
+        #[arg(long, conflicts_with_all = ["watch", "changed"])]
+        doc: bool,
+
+        /// Rewrite golden files checked with assert_golden (sets TAKO_BLESS)
+        #[arg(long)]
+        bless: bool,
+
+        /// Seed for the random source of TestContext (sets TAKO_TEST_SEED)
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// TBPF architecture version to compile examples for (with --doc)
+        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4"])]
+        arch: String,
+    },
+
+    /// Run a test under gdb or lldb with breakpoints on the test and on contract calls
+    Debug {
+        /// Name of the test (or a unique part of it)
+        test: String,
+
+        /// Debugger to use: gdb, lldb or a path (defaults to rust-gdb, rust-lldb, gdb, lldb)
+        #[arg(long)]
+        debugger: Option<String>,
+
+        /// Debug the release build of the tests
+        #[arg(long)]
+        release: bool,
     },
 
     /// Clean build artifacts
@@ -101,87 +395,832 @@ enum TakoCommands {
         /// Path to the contract binary
         #[arg(long)]
         contract: Option<String>,
+
+        /// Compare with another artifact side by side instead
+        #[arg(long, value_name = "OTHER.so")]
+        compare: Option<PathBuf>,
+
+        /// TBPF architecture version used to find the latest build with --compare
+        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4"])]
+        arch: String,
+    },
+
+    /// Explain a TBPF loader, verifier or runtime error
+    Explain {
+        /// Error name, numeric code (e.g. 6001 or 0x1771) or log message (lists known errors if omitted)
+        error: Option<String>,
+    },
+
+    /// Map program counters from node logs to Rust source locations
+    Symbolize {
+        /// Addresses to resolve (hex with 0x prefix, or decimal)
+        #[arg(required = true)]
+        addresses: Vec<String>,
+
+        /// Path to the contract binary (defaults to the latest build)
+        #[arg(long)]
+        contract: Option<String>,
+
+        /// TBPF architecture version (v0, v1, v2, v3, v4)
+        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4"])]
+        arch: String,
+
+        /// Treat addresses as instruction indices into .text
+        #[arg(long)]
+        instruction: bool,
+    },
+
+    /// Show the TBPF assembly of a function, interleaved with its Rust source
+    Asm {
+        /// Function name or path suffix (e.g. transfer, Token::transfer); lists functions if omitted
+        symbol: Option<String>,
+
+        /// Compile with the release profile, as deployed
+        #[arg(long)]
+        release: bool,
+
+        /// TBPF architecture version (v0, v1, v2, v3, v4)
+        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4"])]
+        arch: String,
+
+        /// Optimization preset for release builds (size, speed)
+        #[arg(long, value_parser = ["size", "speed"])]
+        optimize: Option<String>,
+    },
+
+    /// Dump the contract binary with ELF header, section and entrypoint annotations
+    #[command(alias = "bindump")]
+    Hexdump {
+        /// Path to the contract binary (defaults to the latest build)
+        #[arg(long)]
+        contract: Option<String>,
+
+        /// TBPF architecture version (v0, v1, v2, v3, v4)
+        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4"])]
+        arch: String,
+
+        /// Only dump this section (e.g. .text)
+        #[arg(long)]
+        section: Option<String>,
+
+        /// First byte offset to dump (hex with 0x prefix, or decimal)
+        #[arg(long)]
+        start: Option<String>,
+
+        /// Number of bytes to dump
+        #[arg(long)]
+        length: Option<String>,
+    },
+
+    /// Show the dependency tree of crates linked into the contract, with sizes
+    Tree {
+        /// Path to the contract binary (defaults to the latest build)
+        #[arg(long)]
+        contract: Option<String>,
+
+        /// TBPF architecture version (v0, v1, v2, v3, v4)
+        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4"])]
+        arch: String,
+    },
+
+    /// Build rustdoc and an instruction reference into target/tako/docs/
+    Docs {
+        /// Open the instruction reference in a browser
+        #[arg(long)]
+        open: bool,
+
+        /// Also write the instruction reference as markdown (instructions.md)
+        #[arg(long)]
+        markdown: bool,
+    },
+
+    /// Inspect the contract storage layout
+    Storage {
+        #[command(subcommand)]
+        command: StorageCommands,
+    },
+
+    /// Print the JSON Schema of files written under target/tako/
+    Schema {
+        /// Document name (build-manifest, verify-report, verify-cache, build-info, test-summary, ci-report, source-map); all if omitted
+        name: Option<String>,
+    },
+
+    /// Manage deployed contract addresses recorded in Tako.lock
+    Addresses {
+        #[command(subcommand)]
+        command: Option<AddressesCommands>,
+    },
+}
+
+#[derive(Subcommand)]
+enum StorageCommands {
+    /// Show storage keys and value types of the contract
+    Layout {
+        /// Write the layout as JSON to this file
+        #[arg(long)]
+        output: Option<String>,
+    },
+
+    /// Check that the current layout is compatible with a previous one
+    Check {
+        /// Layout JSON written by `storage layout --output`
+        #[arg(long)]
+        against: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TemplateCommands {
+    /// Instantiate a template into a temporary project and check that it compiles
+    Validate {
+        /// Template name (default, erc20, erc721, empty, upgradeable, c) or template directory
+        template: String,
+
+        /// Set a template variable (e.g. --var license=MIT --var with_tests=false)
+        #[arg(long = "var", value_name = "KEY=VALUE")]
+        vars: Vec<String>,
+
+        /// Also build the instantiated project for TBPF
+        #[arg(long)]
+        build: bool,
+
+        /// Keep the instantiated project instead of removing it
+        #[arg(long)]
+        keep: bool,
     },
 }
 
-fn main() -> Result<()> {
+#[derive(Subcommand)]
+enum ToolchainCommands {
+    /// Remove platform-tools versions no build has used recently (keeps the
+    /// default version and the one the project's SDK needs)
+    Prune {
+        /// Remove versions unused for at least this many days
+        #[arg(long, default_value_t = 30)]
+        days: u64,
+
+        /// Show what would be removed without removing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum IdeCommands {
+    /// Write rust-analyzer settings to .vscode/settings.json
+    Setup {
+        /// TBPF architecture version (v0, v1, v2, v3, v4)
+        #[arg(long, default_value = "v3", value_parser = ["v0", "v1", "v2", "v3", "v4"])]
+        arch: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AddressesCommands {
+    /// List recorded addresses
+    List {
+        /// Only show this network
+        #[arg(long)]
+        network: Option<String>,
+    },
+
+    /// Print the address of a contract
+    Get {
+        /// Contract name
+        name: String,
+
+        /// Network the contract is deployed to
+        #[arg(long, default_value = "localnet")]
+        network: String,
+    },
+
+    /// Record the address of a contract
+    Set {
+        /// Contract name
+        name: String,
+
+        /// Deployed address
+        address: String,
+
+        /// Network the contract is deployed to
+        #[arg(long, default_value = "localnet")]
+        network: String,
+    },
+
+    /// Remove a contract from the address book
+    Remove {
+        /// Contract name
+        name: String,
+
+        /// Network the contract is deployed to
+        #[arg(long, default_value = "localnet")]
+        network: String,
+    },
+}
+
+impl TakoCommands {
+    /// Make paths given on the command line absolute, so they keep pointing
+    /// where the user meant after `--manifest-path` changes directory
+    fn absolutize_paths(&mut self, cwd: &Path) {
+        let absolute = |path: &mut PathBuf| *path = cwd.join(&*path);
+        let absolute_str =
+            |path: &mut String| *path = cwd.join(&*path).to_string_lossy().to_string();
+        match self {
+            TakoCommands::Build {
+                target,
+                target_spec,
+                out_dir,
+                sbf_out_dir,
+                ..
+            } => {
+                // --target also accepts a spec file
+                target
+                    .iter_mut()
+                    .filter(|t| TargetSpec::is_spec(t))
+                    .for_each(absolute_str);
+                target_spec.iter_mut().for_each(absolute);
+                out_dir.iter_mut().for_each(absolute);
+                sbf_out_dir.iter_mut().for_each(absolute);
+            }
+            TakoCommands::Check { target_spec, .. } => target_spec.iter_mut().for_each(absolute),
+            TakoCommands::VerifyBuild { against, .. } => absolute(against),
+            TakoCommands::Info {
+                contract, compare, ..
+            } => {
+                contract.iter_mut().for_each(absolute_str);
+                compare.iter_mut().for_each(absolute);
+            }
+            TakoCommands::Verify { contract, .. }
+            | TakoCommands::Symbolize { contract, .. }
+            | TakoCommands::Hexdump { contract, .. }
+            | TakoCommands::Tree { contract, .. } => contract.iter_mut().for_each(absolute_str),
+            TakoCommands::Storage { command } => match command {
+                StorageCommands::Layout { output } => output.iter_mut().for_each(absolute_str),
+                StorageCommands::Check { against } => absolute_str(against),
+            },
+            _ => {}
+        }
+    }
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("{} {err}", "Error:".failure().bold());
+        if let Some(log) = logs::last_failed() {
+            eprintln!("  Full log: {}", log.display());
+        }
+        std::process::exit(err.exit_code());
+    }
+}
+
+fn run() -> Result<()> {
     let cli = Cli::parse();
+    let Commands::Tako(args) = cli.command;
 
-    match cli.command {
-        Commands::Tako(args) => match args.command {
-            TakoCommands::New {
-                name,
-                path,
+    // Stop child processes and remove temporary files on Ctrl-C
+    interrupt::install();
+
+    // Resolved before changing directory so relative paths work as typed
+    if let Some(config_path) = &args.config {
+        config::set_config_override(config_path)?;
+    }
+
+    // Run every command from the contract's directory
+    let mut command = args.command;
+    if let Some(manifest_path) = &args.manifest_path {
+        if let Some(command) = &mut command {
+            command.absolutize_paths(&std::env::current_dir()?);
+        }
+        util::enter_manifest_dir(manifest_path)?;
+    }
+
+    // Make .env variables available to config interpolation and child processes
+    config::load_dotenv(".env")?;
+
+    // A broken Tako.toml is reported by the command itself, not here
+    let ui = TakoConfig::load_or_default()
+        .map(|config| config.ui)
+        .unwrap_or_default();
+    style::init(args.no_color, ui.theme.as_deref())?;
+    i18n::init(ui.lang.as_deref());
+
+    let Some(command) = command else {
+        if args.version {
+            return version::print_version(args.verbose, &args.message_format);
+        }
+        // Only global options were given
+        TakoArgs::command().bin_name("cargo tako").print_help()?;
+        return Ok(());
+    };
+
+    match command {
+        TakoCommands::New {
+            name,
+            path,
+            template,
+            vars,
+        } => {
+            println!(
+                "{} {}",
+                t!("status.creating").success().bold(),
+                t!("new.subject")
+            );
+            init::create_new_project(&name, path.as_deref(), &template, &vars)?;
+            println!();
+            println!(
+                "{} {}",
+                "✓".success().bold(),
+                t!("new.created", name = name.bold())
+            );
+            println!();
+            println!("{}", t!("new.next_steps"));
+            println!("  cd {name}");
+            println!("  cargo tako build");
+            // C contracts have no cargo test harness
+            if template != "c" {
+                println!("  cargo tako test");
+            }
+        }
+
+        TakoCommands::Init {
+            template,
+            vars,
+            force,
+            dry_run,
+        } => {
+            println!(
+                "{} {}",
+                t!("status.initializing").success().bold(),
+                t!("init.subject")
+            );
+            init::init_current_project(&template, &vars, force, dry_run)?;
+            if !dry_run {
+                println!();
+                println!("{} {}", "✓".success().bold(), t!("init.done"));
+            }
+        }
+
+        TakoCommands::Template { command } => match command {
+            TemplateCommands::Validate {
                 template,
+                vars,
+                build,
+                keep,
             } => {
-                println!("{} TAKO contract project...", "Creating".green().bold());
-                init::create_new_project(&name, path.as_deref(), &template)?;
-                println!();
                 println!(
-                    "{} Created contract project: {}",
-                    "✓".green().bold(),
-                    name.bold()
+                    "{} {}",
+                    t!("status.validating").accent().bold(),
+                    t!("template.subject", template = template)
                 );
-                println!();
-                println!("Next steps:");
-                println!("  cd {name}");
-                println!("  cargo tako build");
-                println!("  cargo tako test");
+                init::validate_template(&template, &vars, build, keep)?;
+            }
+        },
+
+        TakoCommands::Build {
+            release,
+            arch,
+            target,
+            target_spec,
+            lang,
+            verify,
+            dump,
+            optimize,
+            lto,
+            panic,
+            offline_cargo,
+            all_archs,
+            keep_going,
+            out_dir,
+            sbf_compat,
+            sbf_out_dir,
+        } => {
+            let config = TakoConfig::load_or_default()?;
+            let optimization = build::OptimizationSettings::resolve(
+                &config.build,
+                optimize.as_deref(),
+                lto.as_deref(),
+                panic.as_deref(),
+            )?;
+            let mut output_settings = build::OutputSettings::resolve(
+                &config.build,
+                out_dir.as_deref().and_then(|dir| dir.to_str()),
+            );
+
+            if !sbf_compat && !arch.starts_with('v') {
+                return Err(error::Error::Config(format!(
+                    "--arch {arch} is a cargo-build-sbf name; add --sbf-compat or use v0-v4"
+                )));
+            }
+            let (arch, sbf_note) = solana::map_arch(&arch)?;
+            if sbf_compat {
+                solana::print_build_notes(&arch, sbf_note, sbf_out_dir.is_some());
+            }
+            if let Some(dir) = sbf_out_dir {
+                output_settings = Some(build::OutputSettings {
+                    dir,
+                    name: "{lib}.so".to_string(),
+                });
+            }
+
+            // A spec is passed on as a `--target` ending in .json
+            let target = target_spec
+                .map(|path| path.to_string_lossy().to_string())
+                .or(target);
+
+            let lang = lang.unwrap_or_else(|| {
+                let detected = if clang::is_c_project() { "c" } else { "rust" };
+                detected.to_string()
+            });
+            if lang == "c" && (all_archs || target.is_some()) {
+                return Err(error::Error::Config(
+                    "--target, --target-spec and --all-archs apply to Rust contracts".to_string(),
+                ));
+            }
+
+            if all_archs {
+                build::build_matrix(
+                    build::ALL_ARCHS,
+                    release,
+                    &optimization,
+                    offline_cargo,
+                    verify,
+                    keep_going,
+                    output_settings.as_ref(),
+                )?;
+                return Ok(());
             }
 
-            TakoCommands::Init { template } => {
+            println!(
+                "{} {}",
+                t!("status.building").success().bold(),
+                t!("build.subject")
+            );
+            let output = if lang == "c" {
+                clang::build_c_contract(release, &arch, &optimization)?
+            } else {
+                build::build_contract(
+                    release,
+                    &arch,
+                    target.as_deref(),
+                    &optimization,
+                    offline_cargo,
+                )?
+            };
+            println!();
+            println!("{} {}", "✓".success().bold(), t!("build.done"));
+            println!("  {}", t!("build.binary", path = output.display()));
+            println!("  {}", t!("build.size", size = util::file_size(&output)?));
+            println!("  {}", t!("build.arch", arch = arch));
+            // LTO, codegen units and panic strategy are cargo settings
+            if release && lang == "rust" {
                 println!(
-                    "{} TAKO in current project...",
-                    "Initializing".green().bold()
+                    "  {}",
+                    t!("build.optimization", summary = optimization.summary())
                 );
-                init::init_current_project(&template)?;
+            }
+            if let Some(settings) = &output_settings {
+                let target = match target {
+                    Some(target) => TargetSpec::name_of(&target),
+                    None => build::get_target_triple(&arch),
+                };
+                let profile = if release { "release" } else { "debug" };
+                let copy = build::export_artifact(&output, settings, &arch, &target, profile)?;
+                println!("  {}", t!("build.copied", path = copy.display()));
+            }
+
+            if verify {
                 println!();
-                println!("{} TAKO initialized", "✓".green().bold());
+                println!(
+                    "{} {}",
+                    t!("status.verifying").accent().bold(),
+                    t!("verify.subject")
+                );
+                build::verify_contract(&output, &arch, false, true)?;
+                println!("{} {}", "✓".success().bold(), t!("verify.done"));
             }
 
-            TakoCommands::Build {
-                release,
-                arch,
-                target,
-                verify,
-                dump,
-            } => {
-                println!("{} TAKO contract...", "Building".green().bold());
-                let output = build::build_contract(release, &arch, target.as_deref())?;
+            if dump {
                 println!();
-                println!("{} Built contract:", "✓".green().bold());
-                println!("  Binary: {}", output.display());
-                println!("  Size: {} bytes", util::file_size(&output)?);
-                println!("  Arch: {}", arch);
+                println!(
+                    "{} {}",
+                    t!("status.dumping").accent().bold(),
+                    t!("dump.subject")
+                );
+                build::dump_elf(&output)?;
+            }
+        }
 
-                if verify {
-                    println!();
-                    println!("{} contract...", "Verifying".cyan().bold());
-                    build::verify_contract(&output, &arch)?;
-                    println!("{} Contract verified", "✓".green().bold());
-                }
+        TakoCommands::MigrateFromSolana { dry_run } => {
+            println!(
+                "{} {}",
+                t!("status.migrating").accent().bold(),
+                t!("migrate.subject")
+            );
+            solana::migrate_from_solana(dry_run)?;
+        }
 
-                if dump {
-                    println!();
-                    println!("{} ELF information...", "Dumping".cyan().bold());
-                    build::dump_elf(&output)?;
-                }
+        TakoCommands::Check {
+            no_std_audit,
+            message_format,
+            arch,
+            target_spec,
+        } => {
+            if message_format == "json" {
+                check::check_json(&arch, target_spec.as_deref())?;
+            } else {
+                println!(
+                    "{} {}",
+                    t!("status.checking").accent().bold(),
+                    t!("check.subject")
+                );
+                check::run_checks(&arch, no_std_audit, target_spec.as_deref())?;
+            }
+        }
+
+        TakoCommands::Compat { arch, refresh } => {
+            println!(
+                "{} {}",
+                t!("status.checking").accent().bold(),
+                t!("compat.subject")
+            );
+            check::compat_table(&arch, refresh)?;
+        }
+
+        TakoCommands::Ci { arch, skip } => {
+            println!(
+                "{} {}",
+                t!("status.running").success().bold(),
+                t!("ci.subject")
+            );
+            ci::run(&arch, &skip)?;
+            println!("{} {}", "✓".success().bold(), t!("ci.done"));
+        }
+
+        TakoCommands::Ide { command } => match command {
+            IdeCommands::Setup { arch } => {
+                println!(
+                    "{} {}",
+                    t!("status.configuring").success().bold(),
+                    t!("ide.subject")
+                );
+                ide::setup(&arch)?;
             }
+        },
+
+        TakoCommands::Env { arch, shell } => {
+            env::print_env(&arch, &shell)?;
+        }
+
+        TakoCommands::Rename { new_name, dry_run } => {
+            println!(
+                "{} {}",
+                t!("status.renaming").accent().bold(),
+                t!("rename.subject")
+            );
+            rename::rename(&new_name, dry_run)?;
+        }
 
-            TakoCommands::Test { filter, release } => {
-                println!("{} tests...", "Running".green().bold());
-                test::run_tests(filter.as_deref(), release)?;
+        TakoCommands::UpdateSdk { tag, dry_run } => {
+            println!(
+                "{} {}",
+                t!("status.updating").accent().bold(),
+                t!("sdk.subject")
+            );
+            sdk::update_sdk(tag.as_deref(), dry_run)?;
+        }
+
+        TakoCommands::Vendor { dir } => {
+            println!(
+                "{} {}",
+                t!("status.vendoring").success().bold(),
+                t!("vendor.subject")
+            );
+            vendor::vendor(&dir)?;
+        }
+
+        TakoCommands::Toolchain { command } => match command {
+            ToolchainCommands::Prune { days, dry_run } => {
+                println!(
+                    "{} {}",
+                    t!("status.pruning").accent().bold(),
+                    t!("prune.subject")
+                );
+                toolchain::prune(days, dry_run)?;
             }
+        },
 
-            TakoCommands::Clean => {
-                println!("{} build artifacts...", "Cleaning".green().bold());
-                util::clean_build_artifacts()?;
-                println!("{} Build artifacts removed", "✓".green().bold());
+        TakoCommands::Verify {
+            contract,
+            arch,
+            strict,
+            no_cache,
+        } => {
+            let path = match contract {
+                Some(p) => PathBuf::from(p),
+                None => build::latest_artifact(&arch)?,
+            };
+            println!(
+                "{} {}...",
+                t!("status.verifying").accent().bold(),
+                path.display()
+            );
+            build::verify_contract(&path, &arch, strict, !no_cache)?;
+            println!("{} {}", "✓".success().bold(), t!("verify.done"));
+        }
+
+        TakoCommands::VerifyBuild { against, arch } => {
+            println!(
+                "{} {}",
+                t!("status.comparing").accent().bold(),
+                t!("verify_build.subject")
+            );
+            build::verify_build(&against, &arch)?;
+        }
+
+        TakoCommands::Test {
+            filter,
+            release,
+            watch,
+            changed,
+            no_capture,
+            doc,
+            bless,
+            seed,
+            arch,
+        } => {
+            println!(
+                "{} {}",
+                t!("status.running").success().bold(),
+                t!("test.subject")
+            );
+            // Inherited by cargo test and read by TestContext::new
+            if let Some(seed) = seed {
+                println!("{}", t!("test.seed", seed = seed));
+                std::env::set_var("TAKO_TEST_SEED", seed.to_string());
+            }
+            if bless {
+                println!("{}", t!("test.bless"));
+                std::env::set_var("TAKO_BLESS", "1");
             }
+            if doc {
+                test::run_doc_tests(filter.as_deref(), release, no_capture, &arch)?;
+            } else if watch {
+                test::watch_tests(filter.as_deref(), release, no_capture, changed)?;
+            } else if changed {
+                let files = test::git_changed_files()?;
+                test::run_changed_tests(filter.as_deref(), release, no_capture, &files)?;
+            } else {
+                test::run_tests(filter.as_deref(), release, no_capture)?;
+            }
+        }
+
+        TakoCommands::Debug {
+            test,
+            debugger,
+            release,
+        } => {
+            println!(
+                "{} {}",
+                t!("status.debugging").success().bold(),
+                t!("debug.subject", test = test)
+            );
+            debug::debug(&test, debugger.as_deref(), release)?;
+        }
 
-            TakoCommands::Info { contract } => {
-                println!("{} contract information...", "Reading".cyan().bold());
-                util::show_contract_info(contract.as_deref())?;
+        TakoCommands::Clean => {
+            println!(
+                "{} {}",
+                t!("status.cleaning").success().bold(),
+                t!("clean.subject")
+            );
+            util::clean_build_artifacts()?;
+            println!("{} {}", "✓".success().bold(), t!("clean.done"));
+        }
+
+        TakoCommands::Info {
+            contract,
+            compare,
+            arch,
+        } => {
+            println!(
+                "{} {}",
+                t!("status.reading").accent().bold(),
+                t!("info.subject")
+            );
+            match compare {
+                Some(other) => util::compare_contracts(contract.as_deref(), &other, &arch)?,
+                None => util::show_contract_info(contract.as_deref())?,
+            }
+        }
+
+        TakoCommands::Explain { error } => {
+            explain::explain(error.as_deref())?;
+        }
+
+        TakoCommands::Symbolize {
+            addresses,
+            contract,
+            arch,
+            instruction,
+        } => {
+            println!(
+                "{} {}",
+                t!("status.symbolizing").accent().bold(),
+                t!("symbolize.subject")
+            );
+            symbolize::symbolize(contract.as_deref(), &arch, &addresses, instruction)?;
+        }
+
+        TakoCommands::Asm {
+            symbol,
+            release,
+            arch,
+            optimize,
+        } => {
+            let config = TakoConfig::load_or_default()?;
+            let optimization = build::OptimizationSettings::resolve(
+                &config.build,
+                optimize.as_deref(),
+                None,
+                None,
+            )?;
+            println!(
+                "{} {}",
+                t!("status.analyzing").accent().bold(),
+                t!("asm.subject")
+            );
+            asm::show_asm(symbol.as_deref(), release, &arch, &optimization)?;
+        }
+
+        TakoCommands::Hexdump {
+            contract,
+            arch,
+            section,
+            start,
+            length,
+        } => {
+            hexdump::hexdump(
+                contract.as_deref(),
+                &arch,
+                section.as_deref(),
+                start.as_deref(),
+                length.as_deref(),
+            )?;
+        }
+
+        TakoCommands::Tree { contract, arch } => {
+            println!(
+                "{} {}",
+                t!("status.analyzing").accent().bold(),
+                t!("tree.subject")
+            );
+            tree::tree(contract.as_deref(), &arch)?;
+        }
+
+        TakoCommands::Docs { open, markdown } => {
+            println!(
+                "{} {}",
+                t!("status.documenting").success().bold(),
+                t!("docs.subject")
+            );
+            docs::docs(open, markdown)?;
+        }
+
+        TakoCommands::Storage { command } => match command {
+            StorageCommands::Layout { output } => {
+                println!(
+                    "{} {}",
+                    t!("status.reading").accent().bold(),
+                    t!("storage.subject")
+                );
+                storage::show_layout(output.as_deref())?;
+            }
+            StorageCommands::Check { against } => {
+                println!(
+                    "{} {}",
+                    t!("status.checking").accent().bold(),
+                    t!("storage.subject")
+                );
+                storage::check_layout(&against)?;
+            }
+        },
+
+        TakoCommands::Schema { name } => {
+            schema::print_schema(name.as_deref())?;
+        }
+
+        TakoCommands::Addresses { command } => match command {
+            None => addresses::list(None)?,
+            Some(AddressesCommands::List { network }) => addresses::list(network.as_deref())?,
+            Some(AddressesCommands::Get { name, network }) => addresses::get(&network, &name)?,
+            Some(AddressesCommands::Set {
+                name,
+                address,
+                network,
+            }) => addresses::set(&network, &name, &address)?,
+            Some(AddressesCommands::Remove { name, network }) => {
+                addresses::remove(&network, &name)?
             }
         },
     }
@@ -0,0 +1,159 @@
+//! Build manifest written to target/tako/build-manifest.json
+//!
+//! Records the latest built artifact and, for every stripped release binary,
+//! where its unstripped copy with debug info is kept. The outcome of the last
+//! verification is written next to it as target/tako/verify-report.json, and
+//! all verdicts are cached in target/tako/verify-cache.json.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::{Error, Result};
+use crate::schema::{self, SCHEMA_VERSION};
+use crate::util::{ensure_dir, tako_dir};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BuildManifest {
+    #[serde(default = "schema::unversioned")]
+    pub schema_version: u32,
+
+    /// Most recently built artifact
+    #[serde(default)]
+    pub artifact: Option<ArtifactRecord>,
+
+    /// SHA-256 of a stripped binary -> path of its debug-symbols copy
+    #[serde(default)]
+    pub debug_symbols: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactRecord {
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+    pub arch: String,
+    /// Target triple, or the file stem of a custom target spec
+    pub target: String,
+    /// Custom target spec the artifact was built with
+    #[serde(default)]
+    pub target_spec: Option<String>,
+    /// SHA-256 of the target spec at build time
+    #[serde(default)]
+    pub target_spec_sha256: Option<String>,
+    pub profile: String,
+    /// Linker map written while linking the artifact
+    #[serde(default)]
+    pub link_map: Option<String>,
+    /// Source map (address -> Rust line) written next to the artifact
+    #[serde(default)]
+    pub source_map: Option<String>,
+}
+
+impl BuildManifest {
+    /// Path of the manifest file
+    pub fn path() -> PathBuf {
+        tako_dir().join("build-manifest.json")
+    }
+
+    /// Load the manifest, returning an empty one if none was written yet
+    pub fn load() -> Result<Self> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| Error::Other(format!("Invalid {}: {e}", path.display())))
+    }
+
+    /// Write the manifest to target/tako/ with the current schema version
+    pub fn save(&mut self) -> Result<()> {
+        self.schema_version = SCHEMA_VERSION;
+        ensure_dir(tako_dir())?;
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Other(format!("Failed to serialize build manifest: {e}")))?;
+        fs::write(Self::path(), content)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyReport {
+    pub schema_version: u32,
+    pub path: String,
+    /// SHA-256 of the verified binary, if it could be read
+    pub sha256: Option<String>,
+    pub arch: String,
+    pub strict: bool,
+    pub passed: bool,
+    /// Why verification failed
+    pub error: Option<String>,
+}
+
+impl VerifyReport {
+    /// Path of the report file
+    pub fn path() -> PathBuf {
+        tako_dir().join("verify-report.json")
+    }
+
+    /// Write the report to target/tako/
+    pub fn save(&self) -> Result<()> {
+        ensure_dir(tako_dir())?;
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Other(format!("Failed to serialize verify report: {e}")))?;
+        fs::write(Self::path(), content)?;
+        Ok(())
+    }
+}
+
+/// Cached verification verdicts
+///
+/// Keyed by binary hash, architecture, check level and rule-set version, so a
+/// verdict is reused only for the exact same binary and checks.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct VerifyCache {
+    #[serde(default = "schema::unversioned")]
+    pub schema_version: u32,
+
+    #[serde(default)]
+    pub entries: BTreeMap<String, VerifyVerdict>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyVerdict {
+    pub passed: bool,
+    pub error: Option<String>,
+}
+
+impl VerifyCache {
+    /// Path of the cache file
+    pub fn path() -> PathBuf {
+        tako_dir().join("verify-cache.json")
+    }
+
+    /// Cache key for a binary verified with the given settings
+    pub fn key(sha256: &str, arch: &str, strict: bool, entry: &str, rules_version: u32) -> String {
+        let level = if strict { "strict" } else { "basic" };
+        format!("{sha256}:{arch}:{level}:{entry}:r{rules_version}")
+    }
+
+    /// Load the cache; a missing or unreadable cache is treated as empty
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the cache to target/tako/ with the current schema version
+    pub fn save(&mut self) -> Result<()> {
+        self.schema_version = SCHEMA_VERSION;
+        ensure_dir(tako_dir())?;
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Other(format!("Failed to serialize verify cache: {e}")))?;
+        fs::write(Self::path(), content)?;
+        Ok(())
+    }
+}
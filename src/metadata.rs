@@ -0,0 +1,132 @@
+//! Workspace information from `cargo metadata`
+
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::{Error, Result};
+use crate::logs;
+
+#[derive(Debug, Deserialize)]
+pub struct Metadata {
+    /// Workspace member packages (with `--no-deps`, only members are listed)
+    pub packages: Vec<Package>,
+    pub workspace_root: PathBuf,
+    /// Package ids of the workspace members
+    #[serde(default)]
+    pub workspace_members: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Package {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    pub manifest_path: PathBuf,
+    #[serde(default)]
+    pub edition: String,
+    /// Minimum supported Rust version (`package.rust-version`)
+    #[serde(default)]
+    pub rust_version: Option<String>,
+    #[serde(default)]
+    pub dependencies: Vec<Dependency>,
+    #[serde(default)]
+    pub targets: Vec<Target>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Target {
+    #[serde(default)]
+    pub name: String,
+    /// Target kinds, e.g. `lib`, `cdylib` or `proc-macro`
+    pub kind: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Dependency {
+    pub name: String,
+    /// Set for path dependencies (including other workspace members)
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+impl Package {
+    /// Directory containing the package manifest
+    pub fn root(&self) -> &Path {
+        self.manifest_path.parent().unwrap_or(Path::new("."))
+    }
+
+    /// Whether the package is a proc macro, which runs on the host
+    pub fn is_proc_macro(&self) -> bool {
+        self.targets
+            .iter()
+            .any(|t| t.kind.iter().any(|k| k == "proc-macro"))
+    }
+}
+
+/// Load workspace metadata for the current directory
+pub fn load() -> Result<Metadata> {
+    run(&["--no-deps"])
+}
+
+/// Load metadata for the workspace members and all of their dependencies
+pub fn load_with_deps() -> Result<Metadata> {
+    run(&[])
+}
+
+fn run(args: &[&str]) -> Result<Metadata> {
+    let output = logs::output(
+        Command::new("cargo")
+            .args(["metadata", "--format-version", "1"])
+            .args(args),
+    )
+    .map_err(|e| Error::spawn("cargo metadata", e, Error::Other))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Other(format!("cargo metadata failed:\n{stderr}")));
+    }
+
+    serde_json::from_slice(&output.stdout)
+        .map_err(|e| Error::Other(format!("Failed to parse cargo metadata: {e}")))
+}
+
+impl Metadata {
+    /// Packages that are not workspace members (empty with `--no-deps`)
+    pub fn dependencies(&self) -> impl Iterator<Item = &Package> {
+        self.packages
+            .iter()
+            .filter(|p| !self.workspace_members.contains(&p.id))
+    }
+
+    /// Find the workspace package owning a file (the deepest package root containing it)
+    pub fn package_for_file(&self, file: &Path) -> Option<&Package> {
+        self.packages
+            .iter()
+            .filter(|p| file.starts_with(p.root()))
+            .max_by_key(|p| p.root().components().count())
+    }
+
+    /// Names of the packages plus all workspace members depending on them, transitively
+    pub fn with_dependents(&self, names: &BTreeSet<String>) -> BTreeSet<String> {
+        let mut affected = names.clone();
+        loop {
+            let before = affected.len();
+            for package in &self.packages {
+                if package
+                    .dependencies
+                    .iter()
+                    .any(|d| d.path.is_some() && affected.contains(&d.name))
+                {
+                    affected.insert(package.name.clone());
+                }
+            }
+            if affected.len() == before {
+                return affected;
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Single-line progress display for long-running subprocesses
+//!
+//! Redraws one status line on stderr as work items complete. Nothing is drawn
+//! when stderr is not a terminal, so logs in CI stay free of carriage returns.
+
+use std::io::{self, IsTerminal, Write};
+
+pub struct Progress {
+    label: &'static str,
+    count: usize,
+    /// Width of the last drawn line, to blank it out on redraw
+    width: usize,
+    enabled: bool,
+}
+
+impl Progress {
+    /// Start a progress line such as `Compiling [12] serde`
+    pub fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            count: 0,
+            width: 0,
+            enabled: io::stderr().is_terminal(),
+        }
+    }
+
+    /// Record a finished item and redraw the line
+    pub fn step(&mut self, item: &str) {
+        self.count += 1;
+        if !self.enabled {
+            return;
+        }
+        let line = format!("  {} [{}] {item}", self.label, self.count);
+        let mut stderr = io::stderr();
+        let _ = write!(stderr, "\r{line:<width$}", width = self.width);
+        let _ = stderr.flush();
+        self.width = line.chars().count();
+    }
+
+    /// Number of items recorded so far
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Clear the line
+    pub fn finish(&mut self) {
+        if self.enabled && self.width > 0 {
+            let mut stderr = io::stderr();
+            let _ = write!(stderr, "\r{:width$}\r", "", width = self.width);
+            let _ = stderr.flush();
+            self.width = 0;
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
@@ -0,0 +1,243 @@
+//! Machine-readable output schemas
+//!
+//! Every JSON document cargo-tako writes under `target/tako/` carries a
+//! `schema_version`. Fields may be added within a version; renaming or
+//! removing a field, or changing its type, bumps the version.
+
+use serde_json::{json, Value};
+
+use crate::error::{Error, Result};
+
+/// Current version of all documents written under target/tako/
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Serde default for documents written before versioning was introduced
+pub fn unversioned() -> u32 {
+    0
+}
+
+/// Names of the documents with a published schema
+pub const DOCUMENTS: &[&str] = &[
+    "build-manifest",
+    "verify-report",
+    "verify-cache",
+    "build-info",
+    "test-summary",
+    "ci-report",
+    "source-map",
+];
+
+/// Get the JSON Schema of a document
+pub fn schema(name: &str) -> Result<Value> {
+    let (title, properties, required) = match name {
+        "build-manifest" => (
+            "cargo-tako build manifest (target/tako/build-manifest.json)",
+            json!({
+                "artifact": {
+                    "type": ["object", "null"],
+                    "description": "Most recently built artifact",
+                    "properties": {
+                        "path": { "type": "string" },
+                        "sha256": { "type": "string" },
+                        "size": { "type": "integer", "minimum": 0 },
+                        "arch": { "type": "string" },
+                        "target": {
+                            "type": "string",
+                            "description": "Target triple, or the file stem of a custom target spec"
+                        },
+                        "target_spec": {
+                            "type": ["string", "null"],
+                            "description": "Custom target spec (--target-spec) the artifact was built with"
+                        },
+                        "target_spec_sha256": {
+                            "type": ["string", "null"],
+                            "description": "SHA-256 of the target spec at build time"
+                        },
+                        "profile": { "type": "string", "enum": ["debug", "release"] },
+                        "link_map": {
+                            "type": ["string", "null"],
+                            "description": "lld linker map written while linking the artifact"
+                        },
+                        "source_map": {
+                            "type": ["string", "null"],
+                            "description": "Source map (<name>.srcmap.json) written next to the artifact"
+                        }
+                    },
+                    "required": ["path", "sha256", "size", "arch", "target", "profile"]
+                },
+                "debug_symbols": {
+                    "type": "object",
+                    "description": "SHA-256 of a stripped binary -> path of its unstripped copy",
+                    "additionalProperties": { "type": "string" }
+                }
+            }),
+            json!(["schema_version", "debug_symbols"]),
+        ),
+        "verify-report" => (
+            "cargo-tako verify report (target/tako/verify-report.json)",
+            json!({
+                "path": { "type": "string" },
+                "sha256": { "type": ["string", "null"] },
+                "arch": { "type": "string" },
+                "strict": { "type": "boolean" },
+                "passed": { "type": "boolean" },
+                "error": {
+                    "type": ["string", "null"],
+                    "description": "Why verification failed"
+                }
+            }),
+            json!(["schema_version", "path", "arch", "strict", "passed"]),
+        ),
+        "verify-cache" => (
+            "cargo-tako verify cache (target/tako/verify-cache.json)",
+            json!({
+                "entries": {
+                    "type": "object",
+                    "description": "<sha256>:<arch>:<basic|strict>:<entry>:r<rules version> -> verdict",
+                    "additionalProperties": {
+                        "type": "object",
+                        "properties": {
+                            "passed": { "type": "boolean" },
+                            "error": { "type": ["string", "null"] }
+                        },
+                        "required": ["passed"]
+                    }
+                }
+            }),
+            json!(["schema_version", "entries"]),
+        ),
+        "build-info" => (
+            "cargo-tako build environment (tako-build-info.json next to the artifact)",
+            json!({
+                "rustc": { "type": "string", "description": "Output of rustc -vV" },
+                "host": { "type": "string" },
+                "arch": { "type": "string" },
+                "target": { "type": "string" },
+                "profile": { "type": "string", "enum": ["debug", "release"] },
+                "env": {
+                    "type": "object",
+                    "description": "Build-affecting environment variables",
+                    "additionalProperties": { "type": "string" }
+                },
+                "command": {
+                    "type": "array",
+                    "description": "Cargo command line; paths inside the project are relative",
+                    "items": { "type": "string" }
+                },
+                "artifact_sha256": { "type": "string" }
+            }),
+            json!([
+                "schema_version",
+                "rustc",
+                "host",
+                "arch",
+                "target",
+                "profile",
+                "command",
+                "artifact_sha256"
+            ]),
+        ),
+        "test-summary" => (
+            "cargo-tako test summary (target/tako/test-summary.json)",
+            json!({
+                "passed": { "type": "integer", "minimum": 0 },
+                "failed": { "type": "integer", "minimum": 0 },
+                "ignored": { "type": "integer", "minimum": 0 },
+                "tests": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                            "outcome": { "type": "string", "enum": ["passed", "failed", "ignored"] },
+                            "exec_time": { "type": "number", "description": "Seconds" },
+                            "stdout": { "type": ["string", "null"] }
+                        },
+                        "required": ["name", "outcome", "exec_time"]
+                    }
+                }
+            }),
+            json!(["schema_version", "passed", "failed", "ignored", "tests"]),
+        ),
+        "ci-report" => (
+            "cargo-tako CI report (target/tako/ci-report.json)",
+            json!({
+                "passed": { "type": "boolean" },
+                "arch": { "type": "string" },
+                "steps": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string", "enum": ["fmt", "clippy", "lint", "build", "test"] },
+                            "status": { "type": "string", "enum": ["passed", "failed", "skipped"] },
+                            "duration": { "type": "number", "description": "Seconds" },
+                            "error": { "type": ["string", "null"] }
+                        },
+                        "required": ["name", "status", "duration"]
+                    }
+                }
+            }),
+            json!(["schema_version", "passed", "arch", "steps"]),
+        ),
+        "source-map" => (
+            "cargo-tako source map (<name>.srcmap.json next to the artifact)",
+            json!({
+                "artifact_sha256": { "type": "string" },
+                "ranges": {
+                    "type": "array",
+                    "description": "Address ranges of .text sorted by start address",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "start": { "type": "integer", "minimum": 0 },
+                            "end": { "type": "integer", "minimum": 0, "description": "Exclusive" },
+                            "function": { "type": "string" },
+                            "file": { "type": "string" },
+                            "line": { "type": "integer", "minimum": 1 }
+                        },
+                        "required": ["start", "end", "function", "file", "line"]
+                    }
+                }
+            }),
+            json!(["schema_version", "artifact_sha256", "ranges"]),
+        ),
+        _ => {
+            return Err(Error::Other(format!(
+                "Unknown schema '{name}' (expected one of: {})",
+                DOCUMENTS.join(", ")
+            )))
+        }
+    };
+
+    let mut properties = properties;
+    properties["schema_version"] = json!({ "type": "integer", "const": SCHEMA_VERSION });
+
+    Ok(json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!("urn:cargo-tako:schema:v{SCHEMA_VERSION}:{name}"),
+        "title": title,
+        "type": "object",
+        "properties": properties,
+        "required": required
+    }))
+}
+
+/// Print the JSON Schema of one document, or of all documents keyed by name
+pub fn print_schema(name: Option<&str>) -> Result<()> {
+    let value = match name {
+        Some(name) => schema(name)?,
+        None => {
+            let mut all = serde_json::Map::new();
+            for name in DOCUMENTS {
+                all.insert(name.to_string(), schema(name)?);
+            }
+            Value::Object(all)
+        }
+    };
+
+    let content = serde_json::to_string_pretty(&value)
+        .map_err(|e| Error::Other(format!("Failed to serialize schema: {e}")))?;
+    println!("{content}");
+    Ok(())
+}
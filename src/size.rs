@@ -0,0 +1,263 @@
+//! Code size attribution
+//!
+//! Attributes the bytes of a contract binary to the crates they came from.
+//! The linker map written by `cargo tako build` is used when available; it
+//! covers every byte of the loaded sections, including code without a named
+//! symbol. Otherwise sizes are estimated from the binary's symbol table.
+
+use goblin::elf::sym::{STT_FUNC, STT_OBJECT};
+use goblin::elf::Elf;
+use std::collections::BTreeMap;
+
+/// Crate name used for symbols that are not Rust paths (C, assembly, linker)
+pub const UNATTRIBUTED: &str = "(unattributed)";
+
+/// Crate name used for sections synthesized by the linker
+pub const LINKER: &str = "(linker)";
+
+/// Crates shipped with the toolchain rather than resolved by Cargo
+pub const SYSROOT_CRATES: &[&str] = &["core", "alloc", "std", "compiler_builtins"];
+
+/// Code and data bytes attributed to each crate, keyed by library name
+pub fn crate_sizes(elf: &Elf) -> BTreeMap<String, u64> {
+    let mut sizes = BTreeMap::new();
+    for sym in elf.syms.iter() {
+        if sym.st_shndx == 0 || sym.st_size == 0 {
+            continue;
+        }
+        if !matches!(sym.st_type(), STT_FUNC | STT_OBJECT) {
+            continue;
+        }
+        let name = elf.strtab.get_at(sym.st_name).unwrap_or_default();
+        *sizes.entry(crate_of(name)).or_default() += sym.st_size;
+    }
+    sizes
+}
+
+/// Bytes attributed to each crate from an lld linker map (`--Map`)
+///
+/// Each byte of an input section goes to the crate of the symbol covering it;
+/// bytes before the first symbol, and symbols that are not Rust paths, go to
+/// the crate of the object file the section came from.
+pub fn map_crate_sizes(map: &str) -> BTreeMap<String, u64> {
+    let mut sizes = BTreeMap::new();
+    let mut counted = false;
+    let mut section: Option<InputSection> = None;
+
+    for line in map.lines().skip(1) {
+        let Some(entry) = MapEntry::parse(line) else {
+            continue;
+        };
+        match entry.level {
+            MapLevel::Output => {
+                if let Some(section) = section.take() {
+                    section.attribute(&mut sizes);
+                }
+                counted = is_loaded_section(entry.name);
+            }
+            MapLevel::Input if counted => {
+                if let Some(section) = section.take() {
+                    section.attribute(&mut sizes);
+                }
+                section = Some(InputSection {
+                    object: object_crate(entry.name),
+                    start: entry.address,
+                    end: entry.address + entry.size,
+                    symbols: Vec::new(),
+                });
+            }
+            MapLevel::Symbol => {
+                if let Some(section) = &mut section {
+                    let krate = match crate_of(entry.name) {
+                        krate if krate == UNATTRIBUTED => section.object.clone(),
+                        krate => krate,
+                    };
+                    section.symbols.push((entry.address, krate));
+                }
+            }
+            MapLevel::Input => {}
+        }
+    }
+    if let Some(section) = section {
+        section.attribute(&mut sizes);
+    }
+
+    sizes.retain(|_, bytes| *bytes > 0);
+    sizes
+}
+
+#[derive(Debug, PartialEq)]
+enum MapLevel {
+    Output,
+    Input,
+    Symbol,
+}
+
+/// A line of an lld map: `VMA LMA Size Align Out/In/Symbol`
+struct MapEntry<'a> {
+    address: u64,
+    size: u64,
+    level: MapLevel,
+    name: &'a str,
+}
+
+impl<'a> MapEntry<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        let mut rest = line;
+        let mut fields = [0u64; 4];
+        for field in &mut fields {
+            let trimmed = rest.trim_start();
+            let end = trimmed.find(' ').unwrap_or(trimmed.len());
+            *field = u64::from_str_radix(&trimmed[..end], 16).ok()?;
+            rest = &trimmed[end..];
+        }
+
+        // Names are indented by 1, 9 and 17 spaces after the Align column
+        let indent = rest.len() - rest.trim_start().len();
+        let level = match indent {
+            0..=8 => MapLevel::Output,
+            9..=16 => MapLevel::Input,
+            _ => MapLevel::Symbol,
+        };
+        Some(Self {
+            address: fields[0],
+            size: fields[2],
+            level,
+            name: rest.trim(),
+        })
+    }
+}
+
+/// An input section of a loaded output section
+struct InputSection {
+    /// Crate of the object file the section came from
+    object: String,
+    start: u64,
+    end: u64,
+    /// Symbol addresses and their crates
+    symbols: Vec<(u64, String)>,
+}
+
+impl InputSection {
+    /// Add the bytes of the section to the crates of its symbols
+    fn attribute(mut self, sizes: &mut BTreeMap<String, u64>) {
+        self.symbols.sort_by_key(|(address, _)| *address);
+
+        let mut covered = self.start;
+        let mut owner = self.object;
+        for (address, krate) in self.symbols {
+            let address = address.clamp(covered, self.end);
+            *sizes.entry(owner).or_default() += address - covered;
+            covered = address;
+            owner = krate;
+        }
+        *sizes.entry(owner).or_default() += self.end - covered;
+    }
+}
+
+/// Whether an output section is loaded into the VM (as opposed to debug info
+/// and symbol tables, which are stripped from the deployed binary)
+fn is_loaded_section(name: &str) -> bool {
+    !(name.starts_with(".debug")
+        || matches!(name, ".comment" | ".symtab" | ".strtab" | ".shstrtab"))
+}
+
+/// Get the crate an object file in a linker map belongs to
+///
+/// Handles `deps/my_token-<hash>.my_token.<hash>-cgu.0.rcgu.o:(.text.foo)` and
+/// archive members such as `libcore-<hash>.rlib(core-<hash>.core.<hash>-cgu.0.rcgu.o):(.text)`.
+fn object_crate(input: &str) -> String {
+    if input.starts_with('<') {
+        return LINKER.to_string();
+    }
+    let object = input.rsplit_once(":(").map(|(o, _)| o).unwrap_or(input);
+    let file = match object.split_once('(') {
+        Some((archive, _)) => archive,
+        None => object,
+    };
+    let file = file.rsplit(['/', '\\']).next().unwrap_or(file);
+    if !(file.ends_with(".rlib") || file.ends_with(".rcgu.o")) {
+        return UNATTRIBUTED.to_string();
+    }
+    let file = if file.ends_with(".rlib") {
+        file.strip_prefix("lib").unwrap_or(file)
+    } else {
+        file
+    };
+    let end = file.find(['-', '.']).unwrap_or(file.len());
+    file[..end].to_string()
+}
+
+/// Get the crate a (mangled) symbol belongs to
+///
+/// For trait impls (`<alloc::vec::Vec<T> as core::ops::Drop>::drop`) this is
+/// the crate of the implementing type.
+pub fn crate_of(symbol: &str) -> String {
+    let demangled = format!("{:#}", rustc_demangle::demangle(symbol));
+    if demangled == symbol && !symbol.contains("::") {
+        return UNATTRIBUTED.to_string();
+    }
+
+    let path = demangled.trim_start_matches(['<', '&', '*', ' ']);
+    let path = path.strip_prefix("mut ").unwrap_or(path);
+    let path = path.strip_prefix("const ").unwrap_or(path);
+    match path.split_once("::") {
+        Some((krate, _)) if is_identifier(krate) => krate.to_string(),
+        _ => UNATTRIBUTED.to_string(),
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Format a byte count for display
+pub fn format_size(bytes: u64) -> String {
+    const KIB: f64 = 1024.0;
+    match bytes as f64 {
+        b if b < KIB => format!("{bytes} B"),
+        b if b < KIB * KIB => format!("{:.1} KiB", b / KIB),
+        b if b < KIB * KIB * KIB => format!("{:.1} MiB", b / (KIB * KIB)),
+        b => format!("{:.1} GiB", b / (KIB * KIB * KIB)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crate_of() {
+        assert_eq!(crate_of("_ZN4core3fmt5write17h0123456789abcdefE"), "core");
+        assert_eq!(
+            crate_of("_ZN66_$LT$alloc..vec..Vec$LT$T$GT$$u20$as$u20$core..ops..drop..Drop$GT$4drop17h0123456789abcdefE"),
+            "alloc"
+        );
+        assert_eq!(crate_of("_RNvC8my_token4mint"), "my_token");
+        assert_eq!(crate_of("memcpy"), UNATTRIBUTED);
+        assert_eq!(crate_of("entrypoint"), UNATTRIBUTED);
+    }
+
+    #[test]
+    fn test_map_crate_sizes() {
+        let map = "\
+             VMA              LMA     Size Align Out     In      Symbol
+             120              120       60     8 .text
+             120              120       40     1         /t/deps/my_token-0a1b.my_token.2c3d-cgu.0.rcgu.o:(.text)
+             120              120       10     1                 my_token::mint
+             130              130       30     1                 helper::mix
+             160              160       20     1         /t/deps/libcore-9f8e.rlib(core-9f8e.core.7a6b-cgu.0.rcgu.o):(.text.memcpy)
+             160              160       20     1                 memcpy
+             180              180       10     8 .dynamic
+             180              180       10     8         <internal>:(.dynamic)
+               0                0      400     1 .debug_info
+               0                0      400     1         /t/deps/my_token-0a1b.my_token.2c3d-cgu.0.rcgu.o:(.debug_info)
+";
+        let sizes = map_crate_sizes(map);
+        assert_eq!(sizes["my_token"], 0x10);
+        assert_eq!(sizes["helper"], 0x30);
+        assert_eq!(sizes["core"], 0x20);
+        assert_eq!(sizes[LINKER], 0x10);
+        assert_eq!(sizes.values().sum::<u64>(), 0x70);
+    }
+}
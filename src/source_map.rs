@@ -0,0 +1,211 @@
+//! Source map written next to each artifact as `<name>.srcmap.json`
+//!
+//! Maps ranges of .text addresses to the Rust function and source line they
+//! were compiled from, resolved from DWARF with llvm-symbolizer at build time.
+//! Low-level views read it instead of the debug info, so they show source
+//! lines for stripped release artifacts and without platform-tools installed.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::elf;
+use crate::error::{Error, Result};
+use crate::logs;
+use crate::schema::{self, SCHEMA_VERSION};
+use crate::toolchain::find_llvm_tool;
+
+/// TBPF instructions are 8 bytes wide
+const INSTRUCTION_SIZE: u64 = 8;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SourceMap {
+    #[serde(default = "schema::unversioned")]
+    pub schema_version: u32,
+
+    /// SHA-256 of the artifact the map describes
+    pub artifact_sha256: String,
+
+    /// Address ranges sorted by start address
+    pub ranges: Vec<SourceRange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceRange {
+    /// First virtual address of the range
+    pub start: u64,
+    /// Address after the last instruction of the range
+    pub end: u64,
+    pub function: String,
+    pub file: String,
+    pub line: u32,
+}
+
+impl SourceRange {
+    /// `file:line`, as printed by the low-level views
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.file, self.line)
+    }
+}
+
+impl SourceMap {
+    /// Path of the source map for an artifact
+    pub fn path_for(artifact: &Path) -> PathBuf {
+        artifact.with_extension("srcmap.json")
+    }
+
+    /// Resolve every instruction of .text in a binary with debug info
+    ///
+    /// Returns None if the binary has no debug info or llvm-symbolizer is
+    /// not available; the build goes on without a source map then.
+    ///
+    /// # Arguments
+    /// * `binary` - Unstripped binary to read DWARF from
+    /// * `artifact_sha256` - Hash of the artifact the map is for (the stripped
+    ///   binary for release builds; stripping leaves addresses unchanged)
+    pub fn generate(binary: &Path, artifact_sha256: &str) -> Result<Option<Self>> {
+        let bytes = elf::read(binary)?;
+        let parsed = elf::parse(&bytes)?;
+        if !elf::has_debug_info(&parsed) {
+            return Ok(None);
+        }
+        let Some(text) = parsed
+            .section_headers
+            .iter()
+            .find(|sh| parsed.shdr_strtab.get_at(sh.sh_name) == Some(".text"))
+        else {
+            return Ok(None);
+        };
+        let Some(symbolizer) = find_llvm_tool("llvm-symbolizer") else {
+            return Ok(None);
+        };
+
+        let addresses: Vec<u64> = (text.sh_addr..text.sh_addr + text.sh_size)
+            .step_by(INSTRUCTION_SIZE as usize)
+            .collect();
+        let input: String = addresses.iter().map(|a| format!("0x{a:x}\n")).collect();
+
+        let mut cmd = Command::new(&symbolizer);
+        cmd.arg(format!("--obj={}", binary.display()))
+            .arg("--no-inlines")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| Error::spawn("llvm-symbolizer", e, Error::BuildFailed))?;
+        // Written from a thread so a full stdout pipe cannot block the write
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+        let output = child.wait_with_output()?;
+        let _ = writer.join();
+        logs::record(&cmd, &output.status, &output.stdout, &output.stderr);
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::BuildFailed(format!(
+                "llvm-symbolizer failed: {stderr}"
+            )));
+        }
+
+        Ok(Some(Self {
+            schema_version: SCHEMA_VERSION,
+            artifact_sha256: artifact_sha256.to_string(),
+            ranges: parse_ranges(&addresses, &String::from_utf8_lossy(&output.stdout)),
+        }))
+    }
+
+    /// Load the source map of an artifact, if one was written for exactly
+    /// this binary
+    pub fn load_for(artifact: &Path, artifact_sha256: &str) -> Option<Self> {
+        let content = fs::read_to_string(Self::path_for(artifact)).ok()?;
+        let map: Self = serde_json::from_str(&content).ok()?;
+        (map.artifact_sha256 == artifact_sha256).then_some(map)
+    }
+
+    pub fn save(&mut self, path: &Path) -> Result<()> {
+        self.schema_version = SCHEMA_VERSION;
+        let content = serde_json::to_string(self)
+            .map_err(|e| Error::Other(format!("Failed to serialize source map: {e}")))?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// The range containing a virtual address
+    pub fn lookup(&self, address: u64) -> Option<&SourceRange> {
+        let index = self.ranges.partition_point(|r| r.end <= address);
+        self.ranges.get(index).filter(|r| r.start <= address)
+    }
+}
+
+/// Pair llvm-symbolizer frames (function line, `file:line:column` line, blank
+/// line) with the queried addresses, merging consecutive instructions from
+/// the same line. Instructions without line info are left out.
+fn parse_ranges(addresses: &[u64], output: &str) -> Vec<SourceRange> {
+    let mut ranges: Vec<SourceRange> = Vec::new();
+    let frames = output.split("\n\n").filter(|f| !f.trim().is_empty());
+    for (&address, frame) in addresses.iter().zip(frames) {
+        let mut lines = frame.lines();
+        let (Some(function), Some(location)) = (lines.next(), lines.next()) else {
+            continue;
+        };
+        // file:line:column, where the file may itself contain colons
+        let mut parts = location.rsplitn(3, ':');
+        let (Some(_column), Some(line), Some(file)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let Ok(line) = line.parse::<u32>() else {
+            continue;
+        };
+        if line == 0 || file == "??" {
+            continue;
+        }
+
+        let end = address + INSTRUCTION_SIZE;
+        if let Some(last) = ranges.last_mut() {
+            if last.end == address && last.line == line && last.file == file {
+                last.end = end;
+                continue;
+            }
+        }
+        ranges.push(SourceRange {
+            start: address,
+            end,
+            function: function.to_string(),
+            file: file.to_string(),
+            line,
+        });
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ranges() {
+        let output = "entrypoint\n/src/lib.rs:10:5\n\n\
+                      entrypoint\n/src/lib.rs:10:9\n\n\
+                      entrypoint\n/src/lib.rs:11:5\n\n\
+                      ??\n??:0:0\n\n\
+                      c:\\proj\\helper\nC:\\proj\\src\\util.rs:3:1\n\n";
+        let ranges = parse_ranges(&[0x120, 0x128, 0x130, 0x138, 0x140], output);
+        assert_eq!(ranges.len(), 3);
+        assert_eq!((ranges[0].start, ranges[0].end), (0x120, 0x130));
+        assert_eq!(ranges[1].location(), "/src/lib.rs:11");
+        assert_eq!(ranges[2].file, "C:\\proj\\src\\util.rs");
+
+        let map = SourceMap {
+            schema_version: SCHEMA_VERSION,
+            artifact_sha256: String::new(),
+            ranges,
+        };
+        assert_eq!(map.lookup(0x128).map(|r| r.line), Some(10));
+        assert_eq!(map.lookup(0x138), None);
+        assert_eq!(map.lookup(0x140).map(|r| r.line), Some(3));
+        assert_eq!(map.lookup(0x148), None);
+    }
+}
@@ -0,0 +1,408 @@
+//! Audit of `std` usage pulled in through dependencies
+//!
+//! A compile probe checks the contract for a target without `std` and
+//! reports the crates that fail with "can't find crate for `std`". The
+//! inverted feature graph from `cargo tree` then explains which dependency
+//! edge enabled their `std` feature, so the fix can point at a line in a
+//! Cargo.toml.
+
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::commands::build;
+use crate::error::{Error, Result};
+use crate::logs;
+use crate::toolchain::{find_platform_tools, DEFAULT_PLATFORM_TOOLS_VERSION};
+
+/// Feature names crates commonly use to opt into `std`
+const STD_FEATURES: &[&str] = &["std", "use_std"];
+
+/// Target without `std` used when TOS platform-tools are not installed
+const FALLBACK_PROBE_TARGET: &str = "x86_64-unknown-none";
+
+/// A dependency that pulls in `std`
+#[derive(Debug)]
+pub struct StdLeak {
+    pub krate: String,
+    pub version: String,
+    /// The enabled std feature, if the crate has one
+    pub feature: Option<String>,
+    /// Feature graph path from the std feature up to the root package
+    pub chain: Vec<String>,
+    /// The probe build confirmed the crate fails without std
+    pub confirmed: bool,
+}
+
+/// Dependency edge to change to stop enabling a std feature
+#[derive(Debug, PartialEq)]
+pub struct FeatureFix {
+    /// Package whose Cargo.toml declares the dependency
+    pub package: String,
+    pub dependency: String,
+    /// Feature it enables (`default` means default features are on)
+    pub feature: String,
+}
+
+impl StdLeak {
+    /// The edge closest to the root package that enables the std feature
+    pub fn fix(&self) -> Option<FeatureFix> {
+        self.chain
+            .windows(2)
+            .filter_map(|pair| {
+                let (dependency, feature) = parse_feature_node(&pair[0])?;
+                if parse_feature_node(&pair[1]).is_some() {
+                    return None;
+                }
+                let package = pair[1].split_whitespace().next()?;
+                Some(FeatureFix {
+                    package: package.to_string(),
+                    dependency: dependency.to_string(),
+                    feature: feature.to_string(),
+                })
+            })
+            .next_back()
+    }
+}
+
+/// Outcome of the compile probe
+pub enum Probe {
+    /// Packages that failed to compile because `std` is missing
+    Completed(BTreeSet<String>),
+    /// The probe could not run (e.g. no rust-src for -Zbuild-std)
+    Unavailable(String),
+}
+
+#[derive(Deserialize)]
+struct ProbeMessage {
+    reason: String,
+    #[serde(default)]
+    manifest_path: Option<PathBuf>,
+    #[serde(default)]
+    target: Option<ProbeTarget>,
+    #[serde(default)]
+    message: Option<Diagnostic>,
+}
+
+#[derive(Deserialize)]
+struct ProbeTarget {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Diagnostic {
+    message: String,
+    #[serde(default)]
+    code: Option<DiagnosticCode>,
+}
+
+#[derive(Deserialize)]
+struct DiagnosticCode {
+    code: String,
+}
+
+/// Run the audit for the package in the current directory
+pub fn audit() -> Result<(Probe, Vec<StdLeak>)> {
+    let probe = probe()?;
+
+    let mut leaks = Vec::new();
+    for (krate, version, feature) in std_enabled()? {
+        let output = cargo_tree(&["-e", "features", "-i", &krate, "--prefix", "depth"])?;
+        let chain = feature_chain(&output, &krate, &feature);
+        leaks.push(StdLeak {
+            confirmed: matches!(&probe, Probe::Completed(failed) if failed.contains(&krate)),
+            krate,
+            version,
+            feature: Some(feature),
+            chain,
+        });
+    }
+
+    // Crates that need std without a feature to turn it off
+    if let Probe::Completed(failed) = &probe {
+        for krate in failed {
+            if leaks.iter().any(|leak| &leak.krate == krate) {
+                continue;
+            }
+            let output = cargo_tree(&["-e", "normal", "-i", krate, "--prefix", "depth"])?;
+            let chain = dependency_chain(&output);
+            leaks.push(StdLeak {
+                krate: krate.clone(),
+                version: chain
+                    .first()
+                    .and_then(|node| node.split_whitespace().nth(1))
+                    .unwrap_or_default()
+                    .trim_start_matches('v')
+                    .to_string(),
+                feature: None,
+                chain,
+                confirmed: true,
+            });
+        }
+    }
+
+    Ok((probe, leaks))
+}
+
+/// Check the package for a target without `std`
+fn probe() -> Result<Probe> {
+    let platform_tools = find_platform_tools(Some(DEFAULT_PLATFORM_TOOLS_VERSION));
+    let (cargo_bin, rustc) = build::get_cargo_and_rustc(&platform_tools);
+
+    let mut cmd = Command::new(&cargo_bin);
+    cmd.args(["check", "--message-format", "json", "--target"]);
+    if platform_tools.is_some() {
+        cmd.arg(build::get_target_triple("v3"));
+    } else {
+        // -Zbuild-std on a stable toolchain
+        cmd.arg(FALLBACK_PROBE_TARGET).env("RUSTC_BOOTSTRAP", "1");
+    }
+    cmd.arg("-Zbuild-std=core,alloc")
+        .args(["--target-dir", "target/tako/no-std-probe"]);
+    if let Some(rustc) = rustc {
+        cmd.env("RUSTC", rustc);
+    }
+
+    let output = logs::output(&mut cmd)
+        .map_err(|e| Error::Other(format!("Failed to run no_std probe: {e}")))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut diagnostics = 0;
+    let mut failed = BTreeSet::new();
+    for line in stdout.lines() {
+        let Ok(message) = serde_json::from_str::<ProbeMessage>(line) else {
+            continue;
+        };
+        let Some(diagnostic) = message.message else {
+            continue;
+        };
+        if message.reason != "compiler-message" {
+            continue;
+        }
+        diagnostics += 1;
+        let missing_std = diagnostic.code.is_some_and(|code| code.code == "E0463")
+            && diagnostic.message.contains("`std`");
+        if missing_std {
+            let name = message
+                .manifest_path
+                .and_then(|path| package_name(&path))
+                .or(message.target.map(|target| target.name));
+            failed.extend(name);
+        }
+    }
+
+    if !output.status.success() && diagnostics == 0 {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr
+            .lines()
+            .find(|line| line.starts_with("error"))
+            .unwrap_or("probe build failed")
+            .trim_end_matches(", try:")
+            .to_string();
+        return Ok(Probe::Unavailable(reason));
+    }
+
+    Ok(Probe::Completed(failed))
+}
+
+/// Read the package name from a Cargo.toml
+fn package_name(manifest_path: &Path) -> Option<String> {
+    let content = fs::read_to_string(manifest_path).ok()?;
+    let manifest: toml::Value = toml::from_str(&content).ok()?;
+    manifest
+        .get("package")?
+        .get("name")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Dependencies with a std feature enabled, as (name, version, feature)
+///
+/// Proc macros and their dependencies run on the host and are skipped.
+fn std_enabled() -> Result<Vec<(String, String, String)>> {
+    let output = cargo_tree(&["-e", "normal", "--prefix", "depth", "--format", "{p}|{f}"])?;
+
+    let mut seen = BTreeSet::new();
+    let mut enabled = Vec::new();
+    // Depth of the proc macro whose subtree is being skipped
+    let mut host_depth: Option<usize> = None;
+    for (depth, line) in parse_lines(&output) {
+        if host_depth.is_some_and(|host| depth > host) {
+            continue;
+        }
+        host_depth = None;
+
+        let Some((package, features)) = line.split_once('|') else {
+            continue;
+        };
+        if package.ends_with("(proc-macro)") {
+            host_depth = Some(depth);
+            continue;
+        }
+        // The root package is the contract itself
+        if depth == 0 {
+            continue;
+        }
+        let mut parts = package.split_whitespace();
+        let (Some(name), Some(version)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let Some(feature) = features
+            .split(',')
+            .find(|feature| STD_FEATURES.contains(feature))
+        else {
+            continue;
+        };
+        if seen.insert(name.to_string()) {
+            enabled.push((
+                name.to_string(),
+                version.trim_start_matches('v').to_string(),
+                feature.to_string(),
+            ));
+        }
+    }
+    Ok(enabled)
+}
+
+fn cargo_tree(args: &[&str]) -> Result<String> {
+    let output = logs::output(Command::new("cargo").arg("tree").args(args))
+        .map_err(|e| Error::spawn("cargo tree", e, Error::Other))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Other(format!("cargo tree failed:\n{stderr}")));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Parse `cargo tree --prefix depth` output into (depth, label) lines
+fn parse_lines(output: &str) -> Vec<(usize, &str)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+            let depth = line[..digits].parse().ok()?;
+            Some((depth, &line[digits..]))
+        })
+        .collect()
+}
+
+/// Follow the first path from `start` towards the root of an inverted tree
+///
+/// Nodes marked `(*)` were expanded earlier in the output, so the walk
+/// continues from that earlier occurrence.
+fn walk_up(lines: &[(usize, &str)], start: usize) -> Vec<String> {
+    let mut chain = Vec::new();
+    let mut index = start;
+    loop {
+        let raw = lines[index].1;
+        let label = raw.trim_end_matches(" (*)");
+        let label = label.trim_end_matches(" (command-line)");
+        if chain.iter().any(|node| node == label) {
+            break;
+        }
+        chain.push(label.to_string());
+
+        if raw.ends_with(" (*)") {
+            match lines.iter().position(|(_, other)| *other == label) {
+                Some(expanded) => index = expanded,
+                None => break,
+            }
+        }
+        match lines.get(index + 1) {
+            Some((depth, _)) if *depth == lines[index].0 + 1 => index += 1,
+            _ => break,
+        }
+    }
+    chain
+}
+
+/// Path from `krate feature "<feature>"` up to the root package in the
+/// output of `cargo tree -e features -i <krate>`
+fn feature_chain(output: &str, krate: &str, feature: &str) -> Vec<String> {
+    let lines = parse_lines(output);
+    let node = format!("{krate} feature \"{feature}\"");
+    let Some(start) = lines
+        .iter()
+        .position(|(depth, label)| *depth == 1 && *label == node)
+    else {
+        return Vec::new();
+    };
+
+    // Features of the root package are not part of the explanation
+    let mut chain = walk_up(&lines, start);
+    while chain.len() > 1
+        && chain
+            .last()
+            .is_some_and(|n| parse_feature_node(n).is_some())
+    {
+        chain.pop();
+    }
+    chain
+}
+
+/// Path from a crate up to the root package in the output of
+/// `cargo tree -e normal -i <krate>`
+fn dependency_chain(output: &str) -> Vec<String> {
+    let lines = parse_lines(output);
+    if lines.is_empty() {
+        return Vec::new();
+    }
+    walk_up(&lines, 0)
+}
+
+/// Split a feature node (`serde feature "std"`) into crate and feature
+fn parse_feature_node(node: &str) -> Option<(&str, &str)> {
+    let (krate, feature) = node.split_once(" feature ")?;
+    Some((krate, feature.trim_matches('"')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_chain() {
+        let output = "\
+0serde v1.0.200
+1serde feature \"default\"
+2serde_json v1.0.100
+3serde_json feature \"std\"
+4serde_json feature \"default\"
+5my-token v0.1.0 (/work/my-token)
+6my-token feature \"default\" (command-line)
+1serde feature \"std\"
+2serde feature \"default\" (*)
+";
+        let chain = feature_chain(output, "serde", "std");
+        assert_eq!(
+            chain,
+            vec![
+                "serde feature \"std\"",
+                "serde feature \"default\"",
+                "serde_json v1.0.100",
+                "serde_json feature \"std\"",
+                "serde_json feature \"default\"",
+                "my-token v0.1.0 (/work/my-token)",
+            ]
+        );
+
+        let leak = StdLeak {
+            krate: "serde".to_string(),
+            version: "1.0.200".to_string(),
+            feature: Some("std".to_string()),
+            chain,
+            confirmed: true,
+        };
+        assert_eq!(
+            leak.fix(),
+            Some(FeatureFix {
+                package: "my-token".to_string(),
+                dependency: "serde_json".to_string(),
+                feature: "default".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Terminal styling shared by all commands
+//!
+//! Commands color their output through the [`Styled`] methods instead of
+//! naming colors directly, so one place decides whether color is used at all
+//! and which palette suits the terminal background.
+//!
+//! Color is turned off by `--no-color`, a non-empty `NO_COLOR`, `TERM=dumb`,
+//! a `CI` environment, or when stdout is not a terminal. `CLICOLOR_FORCE=1`
+//! turns it back on for everything but `--no-color` and `NO_COLOR`.
+
+use colored::{Color, ColoredString, Colorize};
+use std::env;
+use std::io::{self, IsTerminal};
+use std::sync::OnceLock;
+
+use crate::error::{Error, Result};
+
+/// Palette for dark or light terminal backgrounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Dark,
+    Light,
+}
+
+impl Theme {
+    fn parse(name: &str) -> Result<Self> {
+        match name {
+            "dark" => Ok(Theme::Dark),
+            "light" => Ok(Theme::Light),
+            _ => Err(Error::Config(format!(
+                "Unknown theme '{name}' (expected dark or light)"
+            ))),
+        }
+    }
+
+    fn accent(self) -> Color {
+        match self {
+            Theme::Dark => Color::Cyan,
+            Theme::Light => Color::Blue,
+        }
+    }
+
+    fn warning(self) -> Color {
+        match self {
+            Theme::Dark => Color::Yellow,
+            Theme::Light => Color::Magenta,
+        }
+    }
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Decide whether to use color and pick the theme
+///
+/// # Arguments
+/// * `no_color` - The global `--no-color` flag
+/// * `theme` - `[ui] theme` from Tako.toml, used when `TAKO_THEME` is unset
+pub fn init(no_color: bool, theme: Option<&str>) -> Result<()> {
+    let enabled = use_color(
+        no_color,
+        &|name| env::var(name).ok(),
+        io::stdout().is_terminal(),
+    );
+    colored::control::set_override(enabled);
+
+    let theme = match env::var("TAKO_THEME").ok().as_deref().or(theme) {
+        Some(name) => Theme::parse(name)?,
+        None => Theme::Dark,
+    };
+    let _ = THEME.set(theme);
+    Ok(())
+}
+
+fn theme() -> Theme {
+    THEME.get().copied().unwrap_or(Theme::Dark)
+}
+
+fn use_color(no_color: bool, lookup: &dyn Fn(&str) -> Option<String>, terminal: bool) -> bool {
+    // Any non-empty NO_COLOR disables color (no-color.org), even "0"
+    if no_color || lookup("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        return false;
+    }
+    if lookup("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0") {
+        return true;
+    }
+    if lookup("CI").is_some() || lookup("TERM").as_deref() == Some("dumb") {
+        return false;
+    }
+    terminal
+}
+
+/// Semantic colors, resolved against the selected theme
+pub trait Styled {
+    /// Progress and completion of work (`Building`, `✓`)
+    fn success(self) -> ColoredString;
+    /// Inspection steps and highlighted names (`Checking`, section names)
+    fn accent(self) -> ColoredString;
+    /// Non-fatal problems
+    fn warning(self) -> ColoredString;
+    /// Errors and failed checks
+    fn failure(self) -> ColoredString;
+}
+
+impl<T: Colorize> Styled for T {
+    fn success(self) -> ColoredString {
+        self.color(Color::Green)
+    }
+
+    fn accent(self) -> ColoredString {
+        self.color(theme().accent())
+    }
+
+    fn warning(self) -> ColoredString {
+        self.color(theme().warning())
+    }
+
+    fn failure(self) -> ColoredString {
+        self.color(Color::Red)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_use_color() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+
+        assert!(use_color(false, &env(&[]), true));
+        assert!(!use_color(false, &env(&[]), false));
+        assert!(!use_color(true, &env(&[]), true));
+        assert!(!use_color(false, &env(&[("NO_COLOR", "1")]), true));
+        assert!(!use_color(false, &env(&[("NO_COLOR", "0")]), true));
+        assert!(use_color(false, &env(&[("NO_COLOR", "")]), true));
+        assert!(!use_color(false, &env(&[("CLICOLOR_FORCE", "0")]), false));
+        assert!(!use_color(false, &env(&[("CI", "true")]), true));
+        assert!(!use_color(false, &env(&[("TERM", "dumb")]), true));
+        assert!(use_color(
+            false,
+            &env(&[("CI", "true"), ("CLICOLOR_FORCE", "1")]),
+            false
+        ));
+        assert!(!use_color(true, &env(&[("CLICOLOR_FORCE", "1")]), true));
+
+        assert_eq!(Theme::parse("light").unwrap(), Theme::Light);
+        assert!(Theme::parse("solarized").is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Syscall usage report
+//!
+//! Contracts built for dynamic syscalls (V0-V2) import each syscall as an
+//! undefined dynamic symbol resolved by the loader. Listing those imports shows
+//! the runtime surface a contract depends on.
+
+use colored::Colorize;
+use goblin::elf::Elf;
+
+use crate::style::Styled;
+
+/// Runtime area a syscall belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SyscallCategory {
+    Logging,
+    Crypto,
+    Invoke,
+    Memory,
+    Other,
+}
+
+impl SyscallCategory {
+    fn as_str(self) -> &'static str {
+        match self {
+            SyscallCategory::Logging => "logging",
+            SyscallCategory::Crypto => "crypto",
+            SyscallCategory::Invoke => "cross-contract invoke",
+            SyscallCategory::Memory => "memory",
+            SyscallCategory::Other => "other",
+        }
+    }
+}
+
+/// Name fragments identifying each category, checked in order
+const CATEGORY_PATTERNS: &[(&str, SyscallCategory)] = &[
+    ("log", SyscallCategory::Logging),
+    ("invoke", SyscallCategory::Invoke),
+    ("mem", SyscallCategory::Memory),
+    ("alloc", SyscallCategory::Memory),
+    ("sha256", SyscallCategory::Crypto),
+    ("keccak", SyscallCategory::Crypto),
+    ("blake3", SyscallCategory::Crypto),
+    ("secp256k1", SyscallCategory::Crypto),
+    ("ed25519", SyscallCategory::Crypto),
+    ("curve", SyscallCategory::Crypto),
+    ("poseidon", SyscallCategory::Crypto),
+    ("alt_bn128", SyscallCategory::Crypto),
+    ("big_mod_exp", SyscallCategory::Crypto),
+];
+
+/// Deprecated syscalls (name without the `tos_`/`sol_` prefix) and their replacement
+const DEPRECATED: &[(&str, &str)] = &[("alloc_free_", "a global allocator over the heap region")];
+
+/// Strip the runtime prefix from a syscall name
+fn base_name(name: &str) -> &str {
+    name.strip_prefix("tos_")
+        .or_else(|| name.strip_prefix("sol_"))
+        .unwrap_or(name)
+}
+
+/// Categorize a syscall by name
+pub fn categorize(name: &str) -> SyscallCategory {
+    let base = base_name(name);
+    CATEGORY_PATTERNS
+        .iter()
+        .find(|(pattern, _)| base.contains(pattern))
+        .map(|(_, category)| *category)
+        .unwrap_or(SyscallCategory::Other)
+}
+
+/// Get the replacement for a deprecated syscall
+pub fn deprecation(name: &str) -> Option<&'static str> {
+    let base = base_name(name);
+    DEPRECATED
+        .iter()
+        .find(|(deprecated, _)| *deprecated == base)
+        .map(|(_, replacement)| *replacement)
+}
+
+/// Names of undefined dynamic symbols, i.e. functions resolved by the loader
+pub fn imported_syscalls(elf: &Elf) -> Vec<String> {
+    let mut names: Vec<String> = elf
+        .dynsyms
+        .iter()
+        .filter(|sym| sym.st_shndx == 0)
+        .filter_map(|sym| elf.dynstrtab.get_at(sym.st_name))
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Print imported syscalls grouped by category, flagging deprecated ones
+pub fn print_report(elf: &Elf) {
+    println!("{}", "Syscalls".accent().bold());
+
+    let mut syscalls: Vec<(SyscallCategory, String)> = imported_syscalls(elf)
+        .into_iter()
+        .map(|name| (categorize(&name), name))
+        .collect();
+    syscalls.sort();
+
+    if syscalls.is_empty() {
+        println!("  No imported syscalls (static syscalls are resolved by hash and not listed)");
+        return;
+    }
+
+    let mut current = None;
+    for (category, name) in &syscalls {
+        if current != Some(*category) {
+            println!("  {}:", category.as_str());
+            current = Some(*category);
+        }
+        match deprecation(name) {
+            Some(replacement) => println!(
+                "    {} {}",
+                name,
+                format!("(deprecated, use {replacement})").warning()
+            ),
+            None => println!("    {name}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_categorize() {
+        assert_eq!(categorize("tos_log_64_"), SyscallCategory::Logging);
+        assert_eq!(categorize("sol_sha256"), SyscallCategory::Crypto);
+        assert_eq!(categorize("tos_invoke_signed_c"), SyscallCategory::Invoke);
+        assert_eq!(categorize("tos_memcpy_"), SyscallCategory::Memory);
+        assert_eq!(categorize("tos_get_clock_sysvar"), SyscallCategory::Other);
+    }
+
+    #[test]
+    fn test_deprecation() {
+        assert!(deprecation("sol_alloc_free_").is_some());
+        assert!(deprecation("tos_memcpy_").is_none());
+    }
+}
@@ -0,0 +1,132 @@
+//! Custom target specifications (`--target-spec path.json`)
+//!
+//! For TBPF variants the toolchain has no built-in target for. cargo is
+//! given the spec's path as `--target` and builds core and alloc for it with
+//! `-Zbuild-std`; artifacts land in `target/<file stem>/<profile>/`. The spec
+//! is validated up front so a malformed file fails with a clear message
+//! rather than a rustc error in the middle of the build.
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+
+/// Keys rustc requires in every target specification
+const REQUIRED_KEYS: &[&str] = &["llvm-target", "arch", "data-layout", "target-pointer-width"];
+
+#[derive(Debug, Clone)]
+pub struct TargetSpec {
+    /// Absolute path of the JSON file
+    pub path: PathBuf,
+    /// File stem, the name cargo uses for the target directory
+    pub name: String,
+    /// SHA-256 of the file, recorded in the build manifest
+    pub sha256: String,
+}
+
+impl TargetSpec {
+    /// Whether a `--target` value names a spec file rather than a triple
+    pub fn is_spec(target: &str) -> bool {
+        target.ends_with(".json")
+    }
+
+    /// Name artifacts are recorded under for a `--target` value: the file
+    /// stem of a spec, or the triple itself
+    pub fn name_of(target: &str) -> String {
+        if !Self::is_spec(target) {
+            return target.to_string();
+        }
+        Path::new(target)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| target.to_string())
+    }
+
+    /// Read and validate a target specification
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read(path).map_err(|e| {
+            Error::Config(format!("Cannot read target spec {}: {e}", path.display()))
+        })?;
+        let value: Value = serde_json::from_slice(&content)
+            .map_err(|e| Error::Config(format!("Invalid target spec {}: {e}", path.display())))?;
+        let problems = validate(&value);
+        if !problems.is_empty() {
+            return Err(Error::Config(format!(
+                "Invalid target spec {}: {}",
+                path.display(),
+                problems.join("; ")
+            )));
+        }
+
+        let path = path.canonicalize()?;
+        Ok(Self {
+            name: Self::name_of(&path.to_string_lossy()),
+            path,
+            sha256: format!("{:x}", Sha256::digest(&content)),
+        })
+    }
+}
+
+/// Problems that would make rustc reject the spec or build an unusable contract
+fn validate(value: &Value) -> Vec<String> {
+    let Some(spec) = value.as_object() else {
+        return vec!["expected a JSON object".to_string()];
+    };
+    let mut problems: Vec<String> = REQUIRED_KEYS
+        .iter()
+        .filter(|key| !spec.contains_key(**key))
+        .map(|key| format!("missing \"{key}\""))
+        .collect();
+
+    // Older specs give the width as a string, newer ones as a number
+    if let Some(width) = spec.get("target-pointer-width") {
+        let width = match width {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        if width != "64" {
+            problems.push(format!(
+                "\"target-pointer-width\" is {width}, TBPF requires 64"
+            ));
+        }
+    }
+    for key in ["llvm-target", "arch", "data-layout"] {
+        if spec.get(key).is_some_and(|v| !v.is_string()) {
+            problems.push(format!("\"{key}\" must be a string"));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let spec = serde_json::json!({
+            "llvm-target": "tbpf",
+            "arch": "bpf",
+            "data-layout": "e-m:e-p:64:64-i64:64-n32:64-S128",
+            "target-pointer-width": "64",
+        });
+        assert!(validate(&spec).is_empty());
+
+        let spec = serde_json::json!({
+            "llvm-target": "tbpf",
+            "arch": 1,
+            "target-pointer-width": 32,
+        });
+        assert_eq!(
+            validate(&spec),
+            [
+                "missing \"data-layout\"",
+                "\"target-pointer-width\" is 32, TBPF requires 64",
+                "\"arch\" must be a string",
+            ]
+        );
+        assert_eq!(validate(&serde_json::json!([])).len(), 1);
+    }
+}
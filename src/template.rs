@@ -1,81 +1,565 @@
 //! Project templates for cargo-tako
+//!
+//! A template is a set of files whose paths and contents may contain
+//! placeholders:
+//!
+//! - `{{name}}` substitutes a variable (see [`TemplateContext::new`])
+//! - `{{#if name}} ... {{else}} ... {{/if}}` keeps a block when the variable is
+//!   set to anything other than "", "false" or "0"
+//!
+//! Files that render to empty content are not written, so whole files can be
+//! made conditional. Besides the built-in templates, `--template` accepts a
+//! directory of files (a `.template` suffix is stripped from file names).
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+use walkdir::WalkDir;
 
 use crate::error::{Error, Result};
+use crate::logs;
+
+/// Repository of the TAKO SDK crates
+pub const TAKO_SDK_REPO: &str = "https://github.com/tos-network/tako";
+
+/// TAKO SDK release the built-in templates are tested against; it must have
+/// an entry in compat.toml
+pub const TAKO_SDK_VERSION: &str = "v0.1.0";
+
+/// Cargo git dependency key selecting [`TAKO_SDK_VERSION`] (`branch`, `tag` or `rev`)
+pub const TAKO_SDK_REF_KIND: &str = "tag";
+
+/// Default license for generated projects
+const DEFAULT_LICENSE: &str = "MIT OR Apache-2.0";
 
 pub struct Template {
     #[allow(dead_code)]
     pub name: String,
     #[allow(dead_code)]
     pub description: String,
-    pub cargo_toml: String,
-    pub lib_rs: String,
-    pub readme: String,
+    pub files: Vec<TemplateFile>,
+}
+
+impl Template {
+    /// Whether the template creates a C contract rather than a cargo project
+    pub fn is_c(&self) -> bool {
+        self.files
+            .iter()
+            .any(|f| f.path == crate::commands::clang::C_ENTRY)
+    }
 }
 
+pub struct TemplateFile {
+    /// Path relative to the project root (may contain placeholders)
+    pub path: String,
+    pub content: String,
+}
+
+/// Test support module shared by all templates
+const TEST_SUPPORT: &str = include_str!("../templates/shared/test_support.rs.template");
+
 pub fn get_template(name: &str) -> Result<Template> {
     match name {
         "default" => Ok(default_template()),
         "erc20" => Ok(erc20_template()),
         "erc721" => Ok(erc721_template()),
         "empty" => Ok(empty_template()),
+        "upgradeable" => Ok(upgradeable_template()),
+        "c" => Ok(c_template()),
+        _ if Path::new(name).is_dir() => load_template_dir(Path::new(name)),
         _ => Err(Error::InvalidTemplate(name.to_string())),
     }
 }
 
 #[allow(dead_code)]
 pub fn list_templates() -> Vec<&'static str> {
-    vec!["default", "erc20", "erc721", "empty"]
+    vec!["default", "erc20", "erc721", "empty", "upgradeable", "c"]
 }
 
-fn default_template() -> Template {
+fn builtin_template(
+    name: &str,
+    description: &str,
+    cargo_toml: &str,
+    lib_rs: &str,
+    readme: &str,
+) -> Template {
+    let file = |path: &str, content: &str| TemplateFile {
+        path: path.to_string(),
+        content: content.to_string(),
+    };
+
     Template {
-        name: "default".to_string(),
-        description: "Basic counter contract".to_string(),
-        cargo_toml: include_str!("../templates/default/Cargo.toml.template").to_string(),
-        lib_rs: include_str!("../templates/default/lib.rs.template").to_string(),
-        readme: include_str!("../templates/default/README.md.template").to_string(),
+        name: name.to_string(),
+        description: description.to_string(),
+        files: vec![
+            file("Cargo.toml", cargo_toml),
+            file("src/lib.rs", lib_rs),
+            file("src/test_support.rs", TEST_SUPPORT),
+            file("README.md", readme),
+        ],
     }
 }
 
+fn default_template() -> Template {
+    builtin_template(
+        "default",
+        "Basic counter contract",
+        include_str!("../templates/default/Cargo.toml.template"),
+        include_str!("../templates/default/lib.rs.template"),
+        include_str!("../templates/default/README.md.template"),
+    )
+}
+
 fn erc20_template() -> Template {
-    Template {
-        name: "erc20".to_string(),
-        description: "ERC-20 fungible token".to_string(),
-        cargo_toml: include_str!("../templates/erc20/Cargo.toml.template").to_string(),
-        lib_rs: include_str!("../templates/erc20/lib.rs.template").to_string(),
-        readme: include_str!("../templates/erc20/README.md.template").to_string(),
-    }
+    let mut template = builtin_template(
+        "erc20",
+        "ERC-20 fungible token",
+        include_str!("../templates/erc20/Cargo.toml.template"),
+        include_str!("../templates/erc20/lib.rs.template"),
+        include_str!("../templates/erc20/README.md.template"),
+    );
+    // Golden file of the wire format test (empty, so skipped, without tests)
+    template.files.push(TemplateFile {
+        path: "tests/golden/transfer_instruction.hex".to_string(),
+        content: include_str!("../templates/erc20/transfer_instruction.hex.template").to_string(),
+    });
+    template
 }
 
 fn erc721_template() -> Template {
-    Template {
-        name: "erc721".to_string(),
-        description: "ERC-721 non-fungible token (NFT)".to_string(),
-        cargo_toml: include_str!("../templates/erc721/Cargo.toml.template").to_string(),
-        lib_rs: include_str!("../templates/erc721/lib.rs.template").to_string(),
-        readme: include_str!("../templates/erc721/README.md.template").to_string(),
-    }
+    builtin_template(
+        "erc721",
+        "ERC-721 non-fungible token (NFT)",
+        include_str!("../templates/erc721/Cargo.toml.template"),
+        include_str!("../templates/erc721/lib.rs.template"),
+        include_str!("../templates/erc721/README.md.template"),
+    )
 }
 
 fn empty_template() -> Template {
+    builtin_template(
+        "empty",
+        "Minimal boilerplate",
+        include_str!("../templates/empty/Cargo.toml.template"),
+        include_str!("../templates/empty/lib.rs.template"),
+        include_str!("../templates/empty/README.md.template"),
+    )
+}
+
+fn upgradeable_template() -> Template {
+    builtin_template(
+        "upgradeable",
+        "Upgradeable contract with versioned state and migrations",
+        include_str!("../templates/upgradeable/Cargo.toml.template"),
+        include_str!("../templates/upgradeable/lib.rs.template"),
+        include_str!("../templates/upgradeable/README.md.template"),
+    )
+}
+
+/// C contract built with the platform-tools clang (no Cargo.toml)
+fn c_template() -> Template {
+    let file = |path: &str, content: &str| TemplateFile {
+        path: path.to_string(),
+        content: content.to_string(),
+    };
+
     Template {
-        name: "empty".to_string(),
-        description: "Minimal boilerplate".to_string(),
-        cargo_toml: include_str!("../templates/empty/Cargo.toml.template").to_string(),
-        lib_rs: include_str!("../templates/empty/lib.rs.template").to_string(),
-        readme: include_str!("../templates/empty/README.md.template").to_string(),
+        name: "c".to_string(),
+        description: "C contract built with clang".to_string(),
+        files: vec![
+            file(
+                "src/contract.c",
+                include_str!("../templates/c/contract.c.template"),
+            ),
+            file(
+                "include/tako.h",
+                include_str!("../templates/c/tako.h.template"),
+            ),
+            file(
+                "README.md",
+                include_str!("../templates/c/README.md.template"),
+            ),
+        ],
     }
 }
 
-/// Replace placeholders in template string
-pub fn process_template(content: &str, project_name: &str) -> String {
-    content
-        .replace("{{project_name}}", &to_pascal_case(project_name))
-        .replace("{{project_name_snake}}", &to_snake_case(project_name))
-        .replace("{{project_name_kebab}}", &to_kebab_case(project_name))
+/// Load a custom template from a directory
+fn load_template_dir(dir: &Path) -> Result<Template> {
+    let mut files = Vec::new();
+
+    for entry in WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let relative = entry
+            .path()
+            .strip_prefix(dir)
+            .map_err(|e| Error::Other(e.to_string()))?;
+        let path = relative.to_string_lossy().replace('\\', "/");
+        let path = path.strip_suffix(".template").unwrap_or(&path).to_string();
+        let content = fs::read_to_string(entry.path())?;
+        files.push(TemplateFile { path, content });
+    }
+
+    if files.is_empty() {
+        return Err(Error::InvalidTemplate(format!(
+            "{} contains no files",
+            dir.display()
+        )));
+    }
+
+    Ok(Template {
+        name: dir.display().to_string(),
+        description: "Custom template".to_string(),
+        files,
+    })
 }
 
-fn to_pascal_case(s: &str) -> String {
+/// Variables available to template placeholders
+pub struct TemplateContext {
+    vars: BTreeMap<String, String>,
+}
+
+impl TemplateContext {
+    /// Create a context with the built-in variables:
+    ///
+    /// - `project_name` (PascalCase), `project_name_snake`, `project_name_kebab`
+    /// - `author` (from git config, falling back to $USER)
+    /// - `year`, `license`, `tako_sdk_version`, `arch`
+    /// - `tako_sdk` (git source of the SDK crates, e.g.
+    ///   `git = "https://github.com/tos-network/tako", tag = "v0.1.0"`)
+    /// - `with_tests` (true unless overridden)
+    pub fn new(project_name: &str) -> Self {
+        let mut vars = BTreeMap::new();
+        vars.insert("project_name".to_string(), to_pascal_case(project_name));
+        vars.insert(
+            "project_name_snake".to_string(),
+            to_snake_case(project_name),
+        );
+        vars.insert(
+            "project_name_kebab".to_string(),
+            to_kebab_case(project_name),
+        );
+        vars.insert("author".to_string(), default_author());
+        vars.insert("year".to_string(), current_year().to_string());
+        vars.insert("license".to_string(), DEFAULT_LICENSE.to_string());
+        vars.insert("tako_sdk_version".to_string(), TAKO_SDK_VERSION.to_string());
+        vars.insert(
+            "tako_sdk".to_string(),
+            format!("git = \"{TAKO_SDK_REPO}\", {TAKO_SDK_REF_KIND} = \"{TAKO_SDK_VERSION}\""),
+        );
+        vars.insert("arch".to_string(), "v3".to_string());
+        vars.insert("with_tests".to_string(), "true".to_string());
+        Self { vars }
+    }
+
+    /// Set or override a variable
+    pub fn set(&mut self, key: &str, value: &str) {
+        self.vars.insert(key.to_string(), value.to_string());
+    }
+
+    /// Apply `key=value` pairs from the command line
+    pub fn apply_vars(&mut self, vars: &[String]) -> Result<()> {
+        for var in vars {
+            let (key, value) = var.split_once('=').ok_or_else(|| {
+                Error::Other(format!("Invalid --var '{var}' (expected key=value)"))
+            })?;
+            self.set(key.trim(), value);
+        }
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.vars.get(key).map(String::as_str)
+    }
+
+    fn is_truthy(&self, key: &str) -> bool {
+        self.get(key)
+            .is_some_and(|v| !v.is_empty() && v != "false" && v != "0")
+    }
+}
+
+/// Render placeholders and conditional blocks in template content
+pub fn process_template(content: &str, ctx: &TemplateContext) -> Result<String> {
+    let tokens = tokenize(content)?;
+    let mut pos = 0;
+    let nodes = parse(&tokens, &mut pos)?;
+    if pos < tokens.len() {
+        return Err(Error::InvalidTemplate(
+            "unexpected {{else}} or {{/if}} without {{#if}}".to_string(),
+        ));
+    }
+
+    let mut output = String::with_capacity(content.len());
+    render(&nodes, ctx, &mut output)?;
+    Ok(output)
+}
+
+/// Check a template without writing it: syntax errors and unknown
+/// placeholders in any branch, and the files a contract project needs
+///
+/// Returns one message per problem, prefixed with the template file path.
+pub fn validate(tmpl: &Template, ctx: &TemplateContext) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut rendered = BTreeMap::new();
+
+    for file in &tmpl.files {
+        let path_problems = check_placeholders(&file.path, ctx);
+        let content_problems = check_placeholders(&file.content, ctx);
+        let failed = !path_problems.is_empty() || !content_problems.is_empty();
+        for problem in path_problems.into_iter().chain(content_problems) {
+            problems.push(format!("{}: {problem}", file.path));
+        }
+        if failed {
+            continue;
+        }
+
+        if let (Ok(path), Ok(content)) = (
+            process_template(&file.path, ctx),
+            process_template(&file.content, ctx),
+        ) {
+            rendered.insert(path, content);
+        }
+    }
+
+    // C templates are built by clang and have no cargo manifest
+    if tmpl.is_c() {
+        return problems;
+    }
+    match rendered.get("Cargo.toml") {
+        Some(manifest) => {
+            if let Err(e) = toml::from_str::<toml::Value>(manifest) {
+                problems.push(format!("Cargo.toml: renders to invalid TOML: {e}"));
+            }
+        }
+        None if problems.is_empty() => problems.push("no Cargo.toml in template".to_string()),
+        None => {}
+    }
+    if problems.is_empty() && !rendered.contains_key("src/lib.rs") {
+        problems.push("no src/lib.rs in template".to_string());
+    }
+
+    problems
+}
+
+/// Syntax errors and placeholders unknown to `ctx`, including those in
+/// branches the current variables do not select
+fn check_placeholders(content: &str, ctx: &TemplateContext) -> Vec<String> {
+    let nodes = tokenize(content).and_then(|tokens| {
+        let mut pos = 0;
+        let nodes = parse(&tokens, &mut pos)?;
+        if pos < tokens.len() {
+            return Err(Error::InvalidTemplate(
+                "unexpected {{else}} or {{/if}} without {{#if}}".to_string(),
+            ));
+        }
+        Ok(nodes)
+    });
+    let nodes = match nodes {
+        Ok(nodes) => nodes,
+        Err(e) => return vec![e.to_string()],
+    };
+
+    let mut unknown = Vec::new();
+    collect_unknown(&nodes, ctx, &mut unknown);
+    unknown
+        .into_iter()
+        .map(|name| format!("unknown placeholder '{{{{{name}}}}}'"))
+        .collect()
+}
+
+fn collect_unknown(nodes: &[Node], ctx: &TemplateContext, unknown: &mut Vec<String>) {
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Var(name) => {
+                if ctx.get(name).is_none() && !unknown.contains(name) {
+                    unknown.push(name.clone());
+                }
+            }
+            Node::If {
+                then, otherwise, ..
+            } => {
+                collect_unknown(then, ctx, unknown);
+                collect_unknown(otherwise, ctx, unknown);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Token {
+    Text(String),
+    Var(String),
+    If(String),
+    Else,
+    EndIf,
+}
+
+enum Node {
+    Text(String),
+    Var(String),
+    If {
+        condition: String,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+/// Whether a tag is a placeholder name (`{{project_name}}`)
+fn is_placeholder(tag: &str) -> bool {
+    let mut chars = tag.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Split a template into text and tags. Only `{{name}}`, `{{#if name}}`,
+/// `{{else}}` and `{{/if}}` are tags; any other `{{` (a Rust format escape,
+/// JSON in a string) is kept as text.
+fn tokenize(content: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}").map(|e| start + e) else {
+            break;
+        };
+        let tag = rest[start + 2..end].trim();
+
+        let token = if let Some(condition) = tag.strip_prefix("#if ") {
+            Token::If(condition.trim().to_string())
+        } else if tag == "else" {
+            Token::Else
+        } else if tag == "/if" {
+            Token::EndIf
+        } else if is_placeholder(tag) {
+            Token::Var(tag.to_string())
+        } else {
+            text.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            continue;
+        };
+        text.push_str(&rest[..start]);
+        rest = &rest[end + 2..];
+
+        // A block tag alone on its line is removed together with that line
+        if !matches!(token, Token::Var(_)) {
+            let line_start = text.rfind('\n').map(|i| i + 1).unwrap_or(0);
+            if text[line_start..].trim().is_empty() && rest.starts_with('\n') {
+                text.truncate(line_start);
+                rest = &rest[1..];
+            }
+        }
+
+        tokens.push(Token::Text(std::mem::take(&mut text)));
+        tokens.push(token);
+    }
+
+    text.push_str(rest);
+    tokens.push(Token::Text(text));
+    Ok(tokens)
+}
+
+/// Parse tokens into nodes until an unmatched `{{else}}` / `{{/if}}` or the end
+fn parse(tokens: &[Token], pos: &mut usize) -> Result<Vec<Node>> {
+    let mut nodes = Vec::new();
+
+    while let Some(token) = tokens.get(*pos) {
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text.clone())),
+            Token::Var(name) => nodes.push(Node::Var(name.clone())),
+            Token::If(condition) => {
+                *pos += 1;
+                let then = parse(tokens, pos)?;
+                let otherwise = if matches!(tokens.get(*pos), Some(Token::Else)) {
+                    *pos += 1;
+                    parse(tokens, pos)?
+                } else {
+                    Vec::new()
+                };
+                if !matches!(tokens.get(*pos), Some(Token::EndIf)) {
+                    return Err(Error::InvalidTemplate(format!(
+                        "{{{{#if {condition}}}}} is missing {{{{/if}}}}"
+                    )));
+                }
+                nodes.push(Node::If {
+                    condition: condition.clone(),
+                    then,
+                    otherwise,
+                });
+            }
+            Token::Else | Token::EndIf => return Ok(nodes),
+        }
+        *pos += 1;
+    }
+
+    Ok(nodes)
+}
+
+fn render(nodes: &[Node], ctx: &TemplateContext, output: &mut String) -> Result<()> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Var(name) => {
+                let value = ctx.get(name).ok_or_else(|| {
+                    Error::InvalidTemplate(format!("unknown placeholder '{{{{{name}}}}}'"))
+                })?;
+                output.push_str(value);
+            }
+            Node::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let branch = if ctx.is_truthy(condition) {
+                    then
+                } else {
+                    otherwise
+                };
+                render(branch, ctx, output)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Author name from git config, falling back to $USER
+fn default_author() -> String {
+    logs::output(Command::new("git").args(["config", "user.name"]))
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_else(|| "Your Name".to_string())
+}
+
+/// Current year (UTC)
+fn current_year() -> i64 {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0) as i64;
+
+    // Civil-from-days conversion (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    yoe + era * 400 + i64::from(month <= 2)
+}
+
+pub fn to_pascal_case(s: &str) -> String {
     s.split(&['-', '_'][..])
         .map(|word| {
             let mut chars = word.chars();
@@ -87,10 +571,106 @@ fn to_pascal_case(s: &str) -> String {
         .collect()
 }
 
-fn to_snake_case(s: &str) -> String {
+pub fn to_snake_case(s: &str) -> String {
     s.replace('-', "_").to_lowercase()
 }
 
-fn to_kebab_case(s: &str) -> String {
+pub fn to_kebab_case(s: &str) -> String {
     s.replace('_', "-").to_lowercase()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders() {
+        let mut ctx = TemplateContext::new("my-token");
+        ctx.set("symbol", "MTK");
+        let out =
+            process_template("{{project_name}} {{project_name_snake}} {{symbol}}", &ctx).unwrap();
+        assert_eq!(out, "MyToken my_token MTK");
+    }
+
+    #[test]
+    fn test_conditionals() {
+        let mut ctx = TemplateContext::new("demo");
+        let content = "a\n{{#if with_tests}}\ntests\n{{else}}\nno tests\n{{/if}}\nb\n";
+        assert_eq!(process_template(content, &ctx).unwrap(), "a\ntests\nb\n");
+
+        ctx.set("with_tests", "false");
+        assert_eq!(process_template(content, &ctx).unwrap(), "a\nno tests\nb\n");
+    }
+
+    #[test]
+    fn test_erc20_golden_file() {
+        let tmpl = get_template("erc20").unwrap();
+        let golden = tmpl
+            .files
+            .iter()
+            .find(|f| f.path == "tests/golden/transfer_instruction.hex")
+            .unwrap();
+        let mut ctx = TemplateContext::new("demo");
+        // 1 selector byte, 32 address bytes and 8 amount bytes, 32 per line
+        let lines: Vec<usize> = process_template(&golden.content, &ctx)
+            .unwrap()
+            .lines()
+            .map(str::len)
+            .collect();
+        assert_eq!(lines, [64, 18]);
+
+        ctx.set("with_tests", "false");
+        assert!(process_template(&golden.content, &ctx).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_template_errors() {
+        let ctx = TemplateContext::new("demo");
+        assert!(process_template("{{unknown_var}}", &ctx).is_err());
+        assert!(process_template("{{#if with_tests}}x", &ctx).is_err());
+        assert!(process_template("x{{/if}}", &ctx).is_err());
+    }
+
+    #[test]
+    fn test_literal_braces() {
+        let ctx = TemplateContext::new("demo");
+        let content = r#"println!("{{}} {{:?}}", a, b);
+let json = "{{\"a\": 1}}";
+// {{project_name}}
+"#;
+        let expected = r#"println!("{{}} {{:?}}", a, b);
+let json = "{{\"a\": 1}}";
+// Demo
+"#;
+        assert_eq!(process_template(content, &ctx).unwrap(), expected);
+        assert_eq!(process_template("{{", &ctx).unwrap(), "{{");
+        assert!(check_placeholders(content, &ctx).is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let ctx = TemplateContext::new("template-check");
+        for name in list_templates() {
+            let problems = validate(&get_template(name).unwrap(), &ctx);
+            assert!(problems.is_empty(), "{name}: {problems:?}");
+        }
+
+        let tmpl = Template {
+            name: "custom".to_string(),
+            description: String::new(),
+            files: vec![TemplateFile {
+                path: "src/lib.rs".to_string(),
+                content: "{{#if with_tests}}a{{else}}{{symbol}}{{/if}}".to_string(),
+            }],
+        };
+        assert_eq!(
+            validate(&tmpl, &ctx),
+            vec!["src/lib.rs: unknown placeholder '{{symbol}}'"]
+        );
+    }
+
+    #[test]
+    fn test_current_year() {
+        assert!(current_year() >= 2024);
+    }
+}
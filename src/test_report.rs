@@ -0,0 +1,258 @@
+//! Summarized test output
+//!
+//! Parses the JSON events emitted by libtest (`--format json --report-time`)
+//! and renders a compact report: results grouped by module, the slowest tests,
+//! and failures with their captured output at the end.
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::{Error, Result};
+use crate::schema::SCHEMA_VERSION;
+use crate::style::Styled;
+use crate::util::{ensure_dir, tako_dir};
+
+/// Number of slowest tests to show
+const SLOWEST_COUNT: usize = 5;
+
+/// Tests faster than this (in seconds) are left out of the slowest list
+const MIN_REPORTED_TIME: f64 = 0.001;
+
+/// Tests slower than this (in seconds) are highlighted
+const SLOW_THRESHOLD: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TestResult {
+    pub name: String,
+    pub outcome: TestOutcome,
+    /// Execution time in seconds
+    pub exec_time: f64,
+    /// Captured output of failed tests
+    pub stdout: Option<String>,
+}
+
+impl TestResult {
+    /// Module path of the test (`tests::test_mint` -> `tests`)
+    fn module(&self) -> &str {
+        self.name
+            .rsplit_once("::")
+            .map(|(module, _)| module)
+            .unwrap_or("(crate root)")
+    }
+}
+
+/// A libtest JSON event; only the fields used by the report are parsed
+#[derive(Debug, Deserialize)]
+struct Event {
+    #[serde(rename = "type")]
+    kind: String,
+    /// Missing from the `report` event of doctests
+    #[serde(default)]
+    event: String,
+    name: Option<String>,
+    exec_time: Option<f64>,
+    stdout: Option<String>,
+}
+
+/// Test summary document written under target/tako/
+#[derive(Serialize)]
+struct TestSummary<'a> {
+    schema_version: u32,
+    passed: usize,
+    failed: usize,
+    ignored: usize,
+    tests: &'a [TestResult],
+}
+
+/// Collects test results from libtest JSON events
+#[derive(Debug, Default)]
+pub struct TestReport {
+    pub results: Vec<TestResult>,
+}
+
+impl TestReport {
+    /// Record a line of test output
+    ///
+    /// Returns false if the line is not a libtest event (e.g. output printed
+    /// by a test run with --nocapture), so the caller can echo it.
+    pub fn handle_line(&mut self, line: &str) -> bool {
+        let Ok(event) = serde_json::from_str::<Event>(line) else {
+            return false;
+        };
+
+        if event.kind != "test" {
+            return true;
+        }
+        let outcome = match event.event.as_str() {
+            "ok" => TestOutcome::Passed,
+            "failed" | "timeout" => TestOutcome::Failed,
+            "ignored" => TestOutcome::Ignored,
+            _ => return true,
+        };
+        let Some(name) = event.name else {
+            return true;
+        };
+
+        self.push(TestResult {
+            name,
+            outcome,
+            exec_time: event.exec_time.unwrap_or_default(),
+            stdout: event.stdout,
+        });
+        true
+    }
+
+    /// Record a result and print its progress marker
+    pub fn push(&mut self, result: TestResult) {
+        match result.outcome {
+            TestOutcome::Passed => print!("{}", ".".success()),
+            TestOutcome::Failed => print!("{}", "F".failure().bold()),
+            TestOutcome::Ignored => print!("{}", "i".warning()),
+        }
+        self.results.push(result);
+    }
+
+    pub fn count(&self, outcome: TestOutcome) -> usize {
+        self.results.iter().filter(|r| r.outcome == outcome).count()
+    }
+
+    /// Path of the summary file
+    pub fn path() -> PathBuf {
+        tako_dir().join("test-summary.json")
+    }
+
+    /// Write the results to target/tako/test-summary.json
+    pub fn save(&self) -> Result<()> {
+        let summary = TestSummary {
+            schema_version: SCHEMA_VERSION,
+            passed: self.count(TestOutcome::Passed),
+            failed: self.count(TestOutcome::Failed),
+            ignored: self.count(TestOutcome::Ignored),
+            tests: &self.results,
+        };
+
+        ensure_dir(tako_dir())?;
+        let content = serde_json::to_string_pretty(&summary)
+            .map_err(|e| Error::Other(format!("Failed to serialize test summary: {e}")))?;
+        fs::write(Self::path(), content)?;
+        Ok(())
+    }
+
+    /// Print the grouped summary, slowest tests and failures
+    pub fn render(&self) {
+        if self.results.is_empty() {
+            return;
+        }
+        println!();
+        println!();
+
+        let mut modules: BTreeMap<&str, Vec<&TestResult>> = BTreeMap::new();
+        for result in &self.results {
+            modules.entry(result.module()).or_default().push(result);
+        }
+
+        for (module, results) in &modules {
+            let passed = results
+                .iter()
+                .filter(|r| r.outcome == TestOutcome::Passed)
+                .count();
+            let failed = results
+                .iter()
+                .filter(|r| r.outcome == TestOutcome::Failed)
+                .count();
+            let time: f64 = results.iter().map(|r| r.exec_time).sum();
+            let status = if failed > 0 {
+                "✗".failure().bold()
+            } else {
+                "✓".success().bold()
+            };
+            let mut counts = format!("{passed} passed");
+            if failed > 0 {
+                counts.push_str(&format!(", {failed} failed"));
+            }
+            println!("{status} {module:<40} {counts:<22} {time:>8.3}s");
+        }
+
+        let mut timed: Vec<&TestResult> = self
+            .results
+            .iter()
+            .filter(|r| r.outcome != TestOutcome::Ignored && r.exec_time >= MIN_REPORTED_TIME)
+            .collect();
+        timed.sort_by(|a, b| b.exec_time.total_cmp(&a.exec_time));
+        if !timed.is_empty() {
+            println!();
+            println!("{}", "Slowest tests:".bold());
+            for result in timed.iter().take(SLOWEST_COUNT) {
+                let time = format!("{:>8.3}s", result.exec_time);
+                let time = if result.exec_time >= SLOW_THRESHOLD {
+                    time.warning().bold()
+                } else {
+                    time.normal()
+                };
+                println!("  {time}  {}", result.name);
+            }
+        }
+
+        let failures: Vec<&TestResult> = self
+            .results
+            .iter()
+            .filter(|r| r.outcome == TestOutcome::Failed)
+            .collect();
+        if !failures.is_empty() {
+            println!();
+            println!("{}", "Failures:".failure().bold());
+            for failure in failures {
+                println!();
+                println!("{} {}", "----".failure(), failure.name.bold());
+                if let Some(stdout) = &failure.stdout {
+                    for line in stdout.lines() {
+                        println!("  {line}");
+                    }
+                }
+            }
+        }
+
+        println!();
+        println!(
+            "{} passed, {} failed, {} ignored",
+            self.count(TestOutcome::Passed),
+            self.count(TestOutcome::Failed),
+            self.count(TestOutcome::Ignored)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handle_events() {
+        let mut report = TestReport::default();
+        assert!(report.handle_line(r#"{ "type": "suite", "event": "started", "test_count": 2 }"#));
+        assert!(report.handle_line(
+            r#"{ "type": "test", "name": "tests::a", "event": "ok", "exec_time": 0.5 }"#
+        ));
+        assert!(report.handle_line(
+            r#"{ "type": "test", "name": "tests::b", "event": "failed", "exec_time": 0.1, "stdout": "boom" }"#
+        ));
+        assert!(report.handle_line(r#"{ "type": "report", "total_time": 0.2 }"#));
+        assert!(!report.handle_line("println from a test"));
+
+        assert_eq!(report.count(TestOutcome::Passed), 1);
+        assert_eq!(report.count(TestOutcome::Failed), 1);
+        assert_eq!(report.results[1].stdout.as_deref(), Some("boom"));
+        assert_eq!(report.results[0].module(), "tests");
+    }
+}
@@ -23,7 +23,13 @@
 
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+use crate::compat::{self, CompatTable};
+use crate::logs;
+use crate::size::format_size;
 
 /// Default platform-tools version
 /// This should match the version of tos-platform-tools releases on GitHub
@@ -32,10 +38,11 @@ pub const DEFAULT_PLATFORM_TOOLS_VERSION: &str = "v1.52";
 
 /// Default Rust version used in platform-tools
 /// This is the rustc version bundled in platform-tools
-#[allow(dead_code)]
 pub const DEFAULT_RUST_VERSION: &str = "1.89.0";
 
 /// Get home directory
+// The closure is only trivial on non-Windows targets
+#[allow(clippy::unnecessary_lazy_evaluations)]
 pub fn home_dir() -> PathBuf {
     PathBuf::from(
         env::var_os("HOME")
@@ -177,6 +184,17 @@ pub fn find_platform_tools(version: Option<&str>) -> Option<PlatformTools> {
     None
 }
 
+/// Locate an LLVM tool, preferring platform-tools over the system PATH
+pub fn find_llvm_tool(name: &str) -> Option<PathBuf> {
+    if let Some(tools) = find_platform_tools(Some(DEFAULT_PLATFORM_TOOLS_VERSION)) {
+        let path = tools.llvm_bin.join(name);
+        if path.exists() {
+            return Some(path);
+        }
+    }
+    which::which(name).ok()
+}
+
 /// Get the download filename for the current platform
 #[allow(dead_code)]
 pub fn get_download_filename() -> String {
@@ -204,6 +222,66 @@ pub fn get_download_url(version: &str) -> String {
     )
 }
 
+/// Shell commands installing a platform-tools version into the cache
+pub fn install_instructions(version: &str) -> String {
+    let dir = platform_tools_path(version);
+    format!(
+        "mkdir -p {dir} && cd {dir}\ncurl -L {url} | tar -xj",
+        dir = dir.display(),
+        url = get_download_url(version),
+    )
+}
+
+/// Compiler version, as reported by `rustc --version --verbose`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RustcVersion {
+    /// Release with channel suffix, e.g. `1.89.0-dev` or `1.90.0-nightly`
+    pub release: String,
+    pub host: String,
+    pub commit_hash: Option<String>,
+}
+
+impl RustcVersion {
+    /// Run `rustc --version --verbose`; None if the compiler cannot be run
+    pub fn query(rustc: &Path) -> Option<Self> {
+        let output =
+            logs::output(std::process::Command::new(rustc).args(["--version", "--verbose"]))
+                .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Self::parse(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn parse(output: &str) -> Option<Self> {
+        let field = |name: &str| {
+            output
+                .lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+                .map(|value| value.trim().to_string())
+        };
+        Some(Self {
+            release: field("release")?,
+            host: field("host").unwrap_or_default(),
+            commit_hash: field("commit-hash").filter(|h| h != "unknown"),
+        })
+    }
+
+    /// Version without the channel suffix
+    pub fn version(&self) -> &str {
+        self.release.split('-').next().unwrap_or(&self.release)
+    }
+
+    /// Whether the compiler accepts `-Z` flags such as `-Zbuild-std`:
+    /// nightly and platform-tools (`-dev`) builds, or any build with
+    /// RUSTC_BOOTSTRAP set
+    pub fn allows_unstable(&self) -> bool {
+        self.release.ends_with("-nightly")
+            || self.release.ends_with("-dev")
+            || std::env::var_os("RUSTC_BOOTSTRAP").is_some()
+    }
+}
+
 /// Platform tools information
 #[derive(Debug, Clone)]
 pub struct PlatformTools {
@@ -228,6 +306,11 @@ impl PlatformTools {
         self.rust_bin.join("cargo")
     }
 
+    /// Get path to the target sysroots (rust/lib/rustlib)
+    pub fn rustlib(&self) -> PathBuf {
+        self.rust_bin.with_file_name("lib").join("rustlib")
+    }
+
     /// Get path to clang
     pub fn clang(&self) -> PathBuf {
         self.llvm_bin.join("clang")
@@ -281,7 +364,7 @@ pub enum ToolchainSource {
 }
 
 /// Install platform-tools from a local archive
-#[allow(dead_code)]
+#[allow(dead_code, clippy::ptr_arg)]
 pub fn install_from_archive(archive_path: &PathBuf, version: &str) -> Result<PathBuf, String> {
     use std::process::Command;
 
@@ -306,10 +389,11 @@ pub fn install_from_archive(archive_path: &PathBuf, version: &str) -> Result<Pat
     println!("Installing platform-tools {} from {}", version, archive_path.display());
 
     // Extract archive using tar command (more reliable than Rust libraries)
-    let status = Command::new("tar")
-        .args(["-xjf", archive_path.to_str().unwrap(), "-C", target_dir.to_str().unwrap()])
-        .status()
-        .map_err(|e| format!("Failed to run tar: {e}"))?;
+    let status = logs::status(
+        Command::new("tar")
+            .args(["-xjf", archive_path.to_str().unwrap(), "-C", target_dir.to_str().unwrap()]),
+    )
+    .map_err(|e| format!("Failed to run tar: {e}"))?;
 
     if !status.success() {
         return Err("Failed to extract archive".to_string());
@@ -324,6 +408,175 @@ pub fn install_from_archive(archive_path: &PathBuf, version: &str) -> Result<Pat
     Ok(platform_tools_dir)
 }
 
+/// Warning for a toolchain that cannot provide core and alloc for `target`
+///
+/// Builds use -Zbuild-std, which compiles core and alloc from the rust-src
+/// component in `rust/lib/rustlib/src/`, so a prebuilt sysroot for each
+/// target is not needed. Returns None if the sources or a prebuilt sysroot
+/// are present, or for toolchains without a rustlib directory (legacy
+/// layouts), which are not checked.
+pub fn sysroot_warning(tools: &PlatformTools, target: &str) -> Option<String> {
+    let rustlib = tools.rustlib();
+    if !rustlib.is_dir()
+        || rustlib.join("src").join("rust").join("library").is_dir()
+        || rustlib.join(target).is_dir()
+    {
+        return None;
+    }
+    Some(format!(
+        "platform-tools {version} has neither the rust-src component ({src}) nor a sysroot for {target}; \
+         the build will fail with \"can't find crate for `core`\". Reinstall platform-tools {version}:\n\n{}",
+        install_instructions(&tools.version),
+        version = tools.version,
+        src = rustlib.join("src").display(),
+    ))
+}
+
+/// File in `~/.cache/tos/<version>/` whose modification time records when
+/// builds last used that version
+const LAST_USED_MARKER: &str = ".last-used";
+
+/// Record that a build used a cached platform-tools version
+pub fn touch_last_used(tools: &PlatformTools) {
+    if tools.source == ToolchainSource::VersionedCache {
+        let _ = fs::write(cache_dir().join(&tools.version).join(LAST_USED_MARKER), "");
+    }
+}
+
+/// Modification times of a cached version's `.last-used` marker and of its
+/// directory (when it was installed)
+fn last_used(version: &str) -> (Option<SystemTime>, Option<SystemTime>) {
+    let dir = cache_dir().join(version);
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    (modified(&dir.join(LAST_USED_MARKER)), modified(&dir))
+}
+
+/// Why `prune` keeps a cached version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PruneKeep {
+    /// The default version or the one the project's SDK needs
+    Pinned,
+    /// Used within the age limit
+    Recent,
+    /// Last use cannot be determined
+    Unknown,
+}
+
+/// Versions `prune` always keeps: the default and the SDK's required one
+fn pinned_versions(sdk_platform_tools: Option<&str>) -> Vec<String> {
+    let mut pinned = vec![DEFAULT_PLATFORM_TOOLS_VERSION.to_string()];
+    pinned.extend(sdk_platform_tools.map(str::to_string));
+    pinned
+}
+
+/// How long ago a version was last used, falling back to its install time
+/// when no build has used it yet or the marker is dated in the future
+fn version_age(
+    marker: Option<SystemTime>,
+    installed: Option<SystemTime>,
+    now: SystemTime,
+) -> Option<Duration> {
+    let age = |t: SystemTime| now.duration_since(t).ok();
+    marker.and_then(age).or_else(|| installed.and_then(age))
+}
+
+/// Decide whether `prune` keeps a cached version; None means remove it.
+/// Versions whose age is unknown are kept.
+///
+/// # Arguments
+/// * `version` - Cached platform-tools version
+/// * `pinned` - Versions that are always kept
+/// * `age` - Time since last use, if known
+/// * `max_age` - Versions unused for at least this long are removed
+fn prune_keep(
+    version: &str,
+    pinned: &[String],
+    age: Option<Duration>,
+    max_age: Duration,
+) -> Option<PruneKeep> {
+    if pinned.iter().any(|v| v == version) {
+        Some(PruneKeep::Pinned)
+    } else {
+        match age {
+            None => Some(PruneKeep::Unknown),
+            Some(age) if age < max_age => Some(PruneKeep::Recent),
+            Some(_) => None,
+        }
+    }
+}
+
+/// Total size of the files under a directory
+fn dir_size(dir: &Path) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Remove cached platform-tools versions that builds have not used recently
+///
+/// The default version and the version the current project's SDK needs
+/// (from the compatibility table) are always kept.
+///
+/// # Arguments
+/// * `days` - Remove versions unused for at least this many days
+/// * `dry_run` - Only report what would be removed
+pub fn prune(days: u64, dry_run: bool) -> crate::error::Result<()> {
+    let table = CompatTable::load();
+    let sdk_entry =
+        compat::project_sdk_ref(Path::new("Cargo.toml")).and_then(|sdk_ref| table.find(&sdk_ref));
+    let pinned = pinned_versions(sdk_entry.map(|e| e.platform_tools.as_str()));
+
+    let mut versions = find_installed_versions();
+    versions.sort();
+    let now = SystemTime::now();
+    let max_age = Duration::from_secs(days * 86_400);
+    let mut reclaimed = 0;
+    for version in &versions {
+        let dir = cache_dir().join(version);
+        let (marker, installed) = last_used(version);
+        let age = version_age(marker, installed, now);
+        let age_days = age.map(|a| a.as_secs() / 86_400);
+        let used = match age_days {
+            Some(d) => format!("last used {d} day(s) ago"),
+            None => "last use unknown".to_string(),
+        };
+
+        match prune_keep(version, &pinned, age, max_age) {
+            Some(PruneKeep::Pinned) => {
+                println!("  {version}: kept ({used}, pinned)");
+                continue;
+            }
+            Some(PruneKeep::Recent | PruneKeep::Unknown) => {
+                println!("  {version}: kept ({used})");
+                continue;
+            }
+            None => {}
+        }
+
+        let size = dir_size(&dir);
+        reclaimed += size;
+        if dry_run {
+            println!("  {version}: would remove {} ({used})", format_size(size));
+        } else {
+            fs::remove_dir_all(&dir)?;
+            println!("  {version}: removed {} ({used})", format_size(size));
+        }
+    }
+
+    if versions.is_empty() {
+        println!("  No platform-tools versions in {}", cache_dir().display());
+    } else if dry_run {
+        println!("Would reclaim {}", format_size(reclaimed));
+    } else {
+        println!("Reclaimed {}", format_size(reclaimed));
+    }
+    Ok(())
+}
+
 /// Print toolchain information
 #[allow(dead_code)]
 pub fn print_toolchain_info(tools: &PlatformTools) {
@@ -361,6 +614,85 @@ mod tests {
         assert!(path.to_string_lossy().contains("platform-tools"));
     }
 
+    #[test]
+    fn test_rustc_version() {
+        let version = RustcVersion::parse(
+            "rustc 1.89.0-dev\nbinary: rustc\ncommit-hash: unknown\nhost: x86_64-unknown-linux-gnu\nrelease: 1.89.0-dev\n",
+        )
+        .unwrap();
+        assert_eq!(version.version(), "1.89.0");
+        assert_eq!(version.commit_hash, None);
+        assert!(version.allows_unstable());
+    }
+
+    #[test]
+    fn test_sysroot_warning() {
+        let root = std::env::temp_dir().join(format!("tako-sysroot-{}", std::process::id()));
+        let tools = PlatformTools {
+            version: "v1.52".to_string(),
+            rust_bin: root.join("rust").join("bin"),
+            llvm_bin: root.join("llvm").join("bin"),
+            source: ToolchainSource::VersionedCache,
+        };
+        // Legacy layout without rustlib
+        assert!(sysroot_warning(&tools, "tbpfv3-tos-tos").is_none());
+
+        let rustlib = tools.rustlib();
+        fs::create_dir_all(&rustlib).unwrap();
+        assert!(sysroot_warning(&tools, "tbpfv3-tos-tos").is_some());
+
+        fs::create_dir_all(rustlib.join("tbpf-tos-tos")).unwrap();
+        assert!(sysroot_warning(&tools, "tbpf-tos-tos").is_none());
+        assert!(sysroot_warning(&tools, "tbpfv3-tos-tos").is_some());
+
+        // build-std only needs the library sources
+        fs::create_dir_all(rustlib.join("src/rust/library")).unwrap();
+        assert!(sysroot_warning(&tools, "tbpfv3-tos-tos").is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_prune_keep() {
+        let day = Duration::from_secs(86_400);
+        let now = SystemTime::now();
+        let pinned = pinned_versions(Some("v1.50"));
+        assert_eq!(pinned, [DEFAULT_PLATFORM_TOOLS_VERSION, "v1.50"]);
+        assert_eq!(pinned_versions(None), [DEFAULT_PLATFORM_TOOLS_VERSION]);
+
+        // The marker wins over the install time; without it the install time is used
+        assert_eq!(
+            version_age(Some(now - day), Some(now - 40 * day), now),
+            Some(day)
+        );
+        assert_eq!(version_age(None, Some(now - 40 * day), now), Some(40 * day));
+        assert_eq!(version_age(None, None, now), None);
+        // A marker dated in the future (clock skew) falls back to the install time
+        assert_eq!(
+            version_age(Some(now + day), Some(now - 2 * day), now),
+            Some(2 * day)
+        );
+        assert_eq!(version_age(Some(now + day), None, now), None);
+
+        let max_age = 30 * day;
+        assert_eq!(
+            prune_keep(DEFAULT_PLATFORM_TOOLS_VERSION, &pinned, None, max_age),
+            Some(PruneKeep::Pinned)
+        );
+        assert_eq!(
+            prune_keep("v1.50", &pinned, Some(90 * day), max_age),
+            Some(PruneKeep::Pinned)
+        );
+        assert_eq!(
+            prune_keep("v1.48", &pinned, Some(day), max_age),
+            Some(PruneKeep::Recent)
+        );
+        assert_eq!(prune_keep("v1.48", &pinned, Some(max_age), max_age), None);
+        assert_eq!(
+            prune_keep("v1.48", &pinned, None, max_age),
+            Some(PruneKeep::Unknown)
+        );
+    }
+
     #[test]
     fn test_get_download_filename() {
         let filename = get_download_filename();
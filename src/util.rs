@@ -1,9 +1,30 @@
 //! Utility functions for cargo-tako
 
+use colored::Colorize;
+use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::build_info::BuildInfo;
+use crate::commands::build;
+use crate::config::TakoConfig;
+use crate::elf;
 use crate::error::{Error, Result};
+use crate::style::Styled;
+use crate::syscalls;
+
+/// Get the cargo-tako output directory (target/tako/)
+pub fn tako_dir() -> PathBuf {
+    PathBuf::from("target").join("tako")
+}
+
+/// Compute the hex-encoded SHA-256 of a byte slice
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
 
 /// Get the size of a file in bytes
 pub fn file_size<P: AsRef<Path>>(path: P) -> Result<u64> {
@@ -129,25 +150,191 @@ pub fn show_contract_info(contract_path: Option<&str>) -> Result<()> {
     println!("  Path: {}", path.display());
     println!("  Size: {} bytes ({:.2} KB)", size, size as f64 / 1024.0);
 
-    // Try to read ELF header
     let content = fs::read(&path)?;
-    if content.len() >= 4 && &content[0..4] == b"\x7FELF" {
-        println!("  Format: ELF (valid)");
-        if content.len() >= 5 {
-            let class = match content[4] {
-                1 => "32-bit",
-                2 => "64-bit",
-                _ => "unknown",
-            };
-            println!("  Class: {class}");
+    match elf::parse(&content) {
+        Ok(parsed) => {
+            println!("  Format: ELF (valid)");
+            let entry = TakoConfig::load_or_default()?.contract.entry;
+            let problems = elf::entry_problems(&parsed, &entry);
+            if problems.is_empty() {
+                println!("  Entrypoint: {entry} (exported, ELF entry)");
+            } else {
+                for problem in &problems {
+                    println!("  Entrypoint: {problem}");
+                }
+            }
+            println!();
+            elf::print_report(&parsed);
+            println!();
+            syscalls::print_report(&parsed);
+        }
+        Err(_) => println!("  Format: Invalid (not ELF)"),
+    }
+
+    Ok(())
+}
+
+/// Print two artifacts side by side, highlighting the fields that differ
+///
+/// A quicker check than a full diff for whether a deployed artifact matches
+/// a local build: sizes, hashes, architecture flags, entrypoints and the
+/// recorded build info of each.
+///
+/// # Arguments
+/// * `contract_path` - Path to a contract binary (defaults to the latest build)
+/// * `other` - Artifact to compare with
+/// * `arch` - TBPF architecture version used to locate the latest build
+pub fn compare_contracts(contract_path: Option<&str>, other: &Path, arch: &str) -> Result<()> {
+    let path = match contract_path {
+        Some(p) => PathBuf::from(p),
+        None => build::latest_artifact(arch)?,
+    };
+    let entry = TakoConfig::load_or_default()?.contract.entry;
+    let ours = summarize(&path, &entry)?;
+    let theirs = summarize(other, &entry)?;
+
+    let width = ours
+        .iter()
+        .map(|(_, value)| value.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(path.display().to_string().len());
+    println!();
+    println!(
+        "  {:<12} {:<width$}  {}",
+        "",
+        path.display(),
+        other.display()
+    );
+    let mut differences = 0;
+    for ((field, a), (_, b)) in ours.iter().zip(&theirs) {
+        if a == b {
+            println!("  {field:<12} {a:<width$}  {b}");
+        } else {
+            differences += 1;
+            println!(
+                "{} {:<12} {:<width$}  {}",
+                "≠".warning().bold(),
+                field.warning(),
+                a,
+                b
+            );
         }
-    } else {
-        println!("  Format: Invalid (not ELF)");
     }
 
+    println!();
+    if ours[SHA256_ROW] == theirs[SHA256_ROW] {
+        println!("{} Artifacts are identical", "✓".success().bold());
+    } else {
+        println!(
+            "{} Artifacts differ ({differences} field(s))",
+            "✗".failure().bold()
+        );
+    }
     Ok(())
 }
 
+/// Row of `summarize` holding the artifact's full SHA-256
+const SHA256_ROW: usize = 1;
+
+/// (field, value) rows describing an artifact; the hash is always `SHA256_ROW`
+fn summarize(path: &Path, entry: &str) -> Result<Vec<(&'static str, String)>> {
+    let content = fs::read(path)
+        .map_err(|e| Error::Other(format!("Failed to read {}: {e}", path.display())))?;
+    let sha256 = sha256_hex(&content);
+    let mut rows = vec![
+        ("Size", format!("{} bytes", content.len())),
+        ("SHA-256", sha256.clone()),
+    ];
+
+    let parsed = elf::parse(&content)?;
+    let flags = parsed.header.e_flags;
+    rows.push((
+        "Arch",
+        match flags {
+            0..=4 => format!("v{flags} (e_flags 0x{flags:x})"),
+            _ => format!("e_flags 0x{flags:x}"),
+        },
+    ));
+    let entry_function = elf::functions_at(&parsed, parsed.header.e_entry)
+        .first()
+        .map(|name| format!(" ({name})"))
+        .unwrap_or_default();
+    rows.push((
+        "ELF entry",
+        format!("0x{:x}{entry_function}", parsed.header.e_entry),
+    ));
+    rows.push((
+        "Entrypoint",
+        if elf::entry_problems(&parsed, entry).is_empty() {
+            format!("{entry} ok")
+        } else {
+            format!("{entry} missing or not the entry")
+        },
+    ));
+    let text_size = parsed
+        .section_headers
+        .iter()
+        .find(|sh| parsed.shdr_strtab.get_at(sh.sh_name) == Some(".text"))
+        .map(|sh| format!("{} bytes", sh.sh_size))
+        .unwrap_or_else(|| "-".to_string());
+    rows.push((".text", text_size));
+    rows.push((
+        "Debug info",
+        if elf::has_debug_info(&parsed) {
+            "yes"
+        } else {
+            "no (stripped)"
+        }
+        .to_string(),
+    ));
+
+    // Build info written next to the artifact, or next to an exported copy
+    let info = [
+        path.with_extension("build-info.json"),
+        BuildInfo::path_for(path),
+    ]
+    .iter()
+    .filter_map(|p| BuildInfo::load(p).ok())
+    .find(|info| info.artifact_sha256 == sha256);
+    let (profile, target, rustc) = match &info {
+        Some(info) => (
+            info.profile.clone(),
+            info.target.clone(),
+            info.rustc
+                .lines()
+                .find_map(|line| line.strip_prefix("release: "))
+                .unwrap_or("-")
+                .to_string(),
+        ),
+        None => ("-".to_string(), "-".to_string(), "-".to_string()),
+    };
+    rows.push(("Profile", profile));
+    rows.push(("Target", target));
+    rows.push(("rustc", rustc));
+    Ok(rows)
+}
+
+/// Change into the directory containing a Cargo.toml
+///
+/// Lets commands operate on a contract elsewhere in a monorepo as if they
+/// had been run from its root.
+pub fn enter_manifest_dir(manifest_path: &Path) -> Result<()> {
+    if !manifest_path.is_file() {
+        return Err(Error::Other(format!(
+            "Manifest not found: {}",
+            manifest_path.display()
+        )));
+    }
+
+    let dir = match manifest_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => return Ok(()),
+    };
+    std::env::set_current_dir(dir)
+        .map_err(|e| Error::Other(format!("Failed to enter {}: {e}", dir.display())))
+}
+
 /// Create directory if it doesn't exist
 pub fn ensure_dir<P: AsRef<Path>>(path: P) -> Result<()> {
     let path = path.as_ref();
@@ -157,6 +344,15 @@ pub fn ensure_dir<P: AsRef<Path>>(path: P) -> Result<()> {
     Ok(())
 }
 
+/// Parse a hex (`0x`-prefixed) or decimal address
+pub fn parse_address(s: &str) -> Result<u64> {
+    let parsed = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+    parsed.map_err(|_| Error::Other(format!("Invalid address: {s}")))
+}
+
 /// Write file with content
 pub fn write_file<P: AsRef<Path>>(path: P, content: &str) -> Result<()> {
     fs::write(path, content)?;
@@ -166,6 +362,7 @@ pub fn write_file<P: AsRef<Path>>(path: P, content: &str) -> Result<()> {
 /// Check if cargo is available
 #[allow(dead_code)]
 pub fn check_cargo_available() -> Result<()> {
-    which::which("cargo").map_err(|_| Error::Other("cargo not found in PATH".to_string()))?;
+    which::which("cargo")
+        .map_err(|_| Error::ToolchainMissing("cargo not found in PATH".to_string()))?;
     Ok(())
 }
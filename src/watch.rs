@@ -0,0 +1,97 @@
+//! Polling file watcher used by watch modes
+//!
+//! Tracks modification times of Rust sources and manifests under a set of
+//! roots. Polling keeps the tool dependency-free and works the same on every
+//! platform; contract projects are small enough for this to be cheap.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// Interval between polls
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct Watcher {
+    roots: Vec<PathBuf>,
+    snapshot: HashMap<PathBuf, SystemTime>,
+}
+
+impl Watcher {
+    /// Start watching the given roots
+    pub fn new(roots: Vec<PathBuf>) -> Self {
+        let mut watcher = Self {
+            roots,
+            snapshot: HashMap::new(),
+        };
+        watcher.snapshot = watcher.scan();
+        watcher
+    }
+
+    /// Block until at least one watched file changes, returning the changed files
+    pub fn wait_for_changes(&mut self) -> Vec<PathBuf> {
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let changed = self.poll();
+            if !changed.is_empty() {
+                // Let editors finish writing related files before reporting
+                thread::sleep(POLL_INTERVAL);
+                let mut more = self.poll();
+                let mut all = changed;
+                all.append(&mut more);
+                all.sort();
+                all.dedup();
+                return all;
+            }
+        }
+    }
+
+    /// Compare against the last snapshot and return added, modified or removed files
+    fn poll(&mut self) -> Vec<PathBuf> {
+        let current = self.scan();
+        let mut changed: Vec<PathBuf> = current
+            .iter()
+            .filter(|(path, mtime)| self.snapshot.get(*path) != Some(mtime))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.extend(
+            self.snapshot
+                .keys()
+                .filter(|path| !current.contains_key(*path))
+                .cloned(),
+        );
+        self.snapshot = current;
+        changed
+    }
+
+    fn scan(&self) -> HashMap<PathBuf, SystemTime> {
+        let mut files = HashMap::new();
+        for root in &self.roots {
+            for entry in WalkDir::new(root)
+                .into_iter()
+                .filter_entry(|e| !is_ignored_dir(e.path()))
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file() && is_watched_file(e.path()))
+            {
+                if let Some(mtime) = entry.metadata().ok().and_then(|m| m.modified().ok()) {
+                    files.insert(entry.path().to_path_buf(), mtime);
+                }
+            }
+        }
+        files
+    }
+}
+
+fn is_ignored_dir(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n == "target" || n.starts_with('.'))
+}
+
+fn is_watched_file(path: &Path) -> bool {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("rs") => true,
+        _ => path.file_name().is_some_and(|n| n == "Cargo.toml"),
+    }
+}